@@ -53,7 +53,7 @@ fn main() {
 
 impl EventHandler for MainState {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        self.controls.update(keyboard::pressed_keys(ctx).clone());
+        self.controls.update_borrowed(keyboard::pressed_keys(ctx));
 
         if self.controls.clicked(Control::Return) {
             self.x = 0.0;
@@ -54,7 +54,7 @@ fn main() {
 impl EventHandler for MainState {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
         self.controls
-            .update(|key| keyboard::is_key_pressed(ctx, key));
+            .update(|key| keyboard::is_key_pressed(ctx, *key));
 
         if self.controls.clicked(Control::Return) {
             self.x = 0.0;
@@ -0,0 +1,64 @@
+/*
+ * C interface to puppetmaster, from the `ffi` feature. See the docs of the `ffi` module for how to build a
+ * library to link against.
+ *
+ * Inputs and controls are both plain integers. Every function taking a handler pointer treats null as a
+ * handler with nothing pressed.
+ *
+ * Keep this in sync with src/ffi.rs; tests/ffi_header.rs checks that it is.
+ */
+
+#ifndef PUPPETMASTER_H
+#define PUPPETMASTER_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An input handler. Only ever use it through a pointer. */
+typedef struct PuppetmasterHandler PuppetmasterHandler;
+
+/* Create a new handler with no control mapping. Free it with puppetmaster_free. */
+PuppetmasterHandler *puppetmaster_new(void);
+
+/* Free a handler made by puppetmaster_new. */
+void puppetmaster_free(PuppetmasterHandler *handler);
+
+/* Map the input to the control, replacing whatever it was mapped to before. */
+void puppetmaster_bind(PuppetmasterHandler *handler, uint32_t input, uint32_t control);
+
+/* Remove the input's mapping, if it has one. */
+void puppetmaster_unbind(PuppetmasterHandler *handler, uint32_t input);
+
+/* Call this when your engine says an input was pressed. */
+void puppetmaster_input_down(PuppetmasterHandler *handler, uint32_t input);
+
+/* Call this when your engine says an input was released. */
+void puppetmaster_input_up(PuppetmasterHandler *handler, uint32_t input);
+
+/* Release everything, like when your window loses focus. */
+void puppetmaster_clear_inputs(PuppetmasterHandler *handler);
+
+/* Update the handler. Call this at the top of your game loop. */
+void puppetmaster_update(PuppetmasterHandler *handler);
+
+/* Get the number of frames the control has been held for. If it's not pressed, this is 0. */
+uint32_t puppetmaster_press_time(const PuppetmasterHandler *handler, uint32_t control);
+
+/* Return if the control is held down. */
+bool puppetmaster_down(const PuppetmasterHandler *handler, uint32_t control);
+
+/* Return if the control is not held down. */
+bool puppetmaster_up(const PuppetmasterHandler *handler, uint32_t control);
+
+/* Return if the control was pressed this frame. */
+bool puppetmaster_clicked(const PuppetmasterHandler *handler, uint32_t control);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PUPPETMASTER_H */
@@ -0,0 +1,477 @@
+use std::hash::Hash;
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::SavedAxes;
+
+/// Two digital controls that act as one axis control, like A and D for walking left and right.
+///
+/// Set this up on the axis control with `set_virtual_axis` on the handlers, and read it with `value` like
+/// any other axis control. It's -1 while the negative control is down, 1 while the positive control is
+/// down, and 0 while neither is. To make an axis out of two raw inputs, bind them to two controls of
+/// their own first.
+///
+/// ```rust
+/// # use puppetmaster::{AxisConflict, PollingInputHandler, VirtualAxis};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![('a', "left"), ('d', "right")]);
+/// input_handler.set_virtual_axis(
+///     "walk",
+///     Some(VirtualAxis {
+///         negative: "left",
+///         positive: "right",
+///         conflict: AxisConflict::Newest,
+///     }),
+/// );
+///
+/// input_handler.update(vec!['a']);
+/// assert_eq!(input_handler.value("walk"), -1.0);
+///
+/// // Pressing D while A is still held turns around.
+/// input_handler.update(vec!['a', 'd']);
+/// assert_eq!(input_handler.value("walk"), 1.0);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VirtualAxis<C> {
+    /// The control that pushes the axis to -1.
+    pub negative: C,
+    /// The control that pushes the axis to 1.
+    pub positive: C,
+    /// What the axis does when both controls are down.
+    pub conflict: AxisConflict,
+}
+
+/// What a [`VirtualAxis`] does when both of its controls are down at once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AxisConflict {
+    /// They cancel out, and the axis is 0.
+    #[default]
+    Neutral,
+    /// The control pressed most recently wins. If they were pressed on the same frame, the axis is 0.
+    Newest,
+    /// The control pressed first wins. If they were pressed on the same frame, the axis is 0.
+    Oldest,
+}
+
+/// Two axis controls read together as one 2D control, like a movement vector from WASD or a stick.
+///
+/// Set this up with `set_dual_axis` on the handlers, and read it with `vector`. Each axis can be anything
+/// an axis control can be: analog inputs, a [`VirtualAxis`] made from two buttons, or both, so one control
+/// can take the left stick and WASD at once.
+///
+/// ```rust
+/// # use puppetmaster::{DualAxis, PollingInputHandler, VirtualAxis};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![
+///     ('w', "up"),
+///     ('a', "left"),
+///     ('s', "down"),
+///     ('d', "right"),
+/// ]);
+/// let axis = |negative, positive| Some(VirtualAxis { negative, positive, conflict: Default::default() });
+/// input_handler.set_virtual_axis("walk_x", axis("left", "right"));
+/// input_handler.set_virtual_axis("walk_y", axis("down", "up"));
+/// input_handler.axis_config_mut().insert('x', "walk_x");
+/// input_handler.axis_config_mut().insert('y', "walk_y");
+/// input_handler.set_dual_axis(
+///     "walk",
+///     Some(DualAxis {
+///         x: "walk_x",
+///         y: "walk_y",
+///         normalize: true,
+///     }),
+/// );
+///
+/// input_handler.update(vec!['d']);
+/// assert_eq!(input_handler.vector("walk"), (1.0, 0.0));
+///
+/// // Diagonals aren't any faster.
+/// input_handler.update(vec!['w', 'd']);
+/// let (x, y) = input_handler.vector("walk");
+/// assert!((x - y).abs() < 1e-6 && (x.hypot(y) - 1.0).abs() < 1e-6);
+///
+/// // The stick works too.
+/// input_handler.set_axis_inputs(vec![('x', -0.5)]);
+/// input_handler.update(vec![]);
+/// assert_eq!(input_handler.vector("walk"), (-0.5, 0.0));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DualAxis<C> {
+    /// The axis control for the horizontal part.
+    pub x: C,
+    /// The axis control for the vertical part. Which way is positive is up to how it's bound.
+    pub y: C,
+    /// If vectors longer than 1 get scaled down to 1, so moving diagonally isn't faster than moving
+    /// straight.
+    pub normalize: bool,
+}
+
+/// Changes to an axis control's settings while an axis context is active, like a bigger deadzone in menus
+/// or inverted look in a flying section.
+///
+/// Set this up with `set_axis_override` on the handlers, and switch contexts with `set_axis_context`. Each
+/// setting that's `Some` replaces the axis control's own one while its context is active, and each one
+/// that's `None` keeps it.
+///
+/// An axis context is only a name for a set of overrides; it doesn't change anything else about the handler.
+/// It's named with the same type as the controls, so switching and looking it up each frame is as cheap as
+/// looking up a control. Give your control type a variant for each context, or use `&'static str`s.
+///
+/// ```rust
+/// # use puppetmaster::{AxisOverride, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::<&str, &str>::new();
+/// input_handler.axis_config_mut().insert("right_stick_y", "look_y");
+/// input_handler.set_axis_override(
+///     "flying",
+///     "look_y",
+///     Some(AxisOverride {
+///         deadzone: Some(0.5),
+///         inverted: Some(true),
+///         ..Default::default()
+///     }),
+/// );
+///
+/// input_handler.set_axis_inputs(vec![("right_stick_y", 0.75)]);
+/// input_handler.update(vec![]);
+/// assert_eq!(input_handler.value("look_y"), 0.75);
+///
+/// input_handler.set_axis_context(Some("flying"));
+/// assert_eq!(input_handler.value("look_y"), -0.5);
+///
+/// input_handler.set_axis_context(None);
+/// assert_eq!(input_handler.value("look_y"), 0.75);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisOverride {
+    /// How far the axis control has to move before it counts, instead of its own deadzone. This is kept from
+    /// 0 to 1, and NaN counts as 0.
+    pub deadzone: Option<f32>,
+    /// What the axis control's value is scaled by, instead of its own sensitivity. Anything below 0, and NaN,
+    /// counts as 0.
+    pub sensitivity: Option<f32>,
+    /// If the axis control is flipped, instead of its own setting.
+    pub inverted: Option<bool>,
+}
+
+/// The analog side of a handler: axis inputs, like sticks and triggers, bound to axis controls.
+#[derive(Clone, Debug)]
+pub(crate) struct Axes<I, C> {
+    /// Maps axis inputs to the axis controls they move
+    config: AHashMap<I, C>,
+    /// The latest value of each axis input, waiting for the next update
+    pending: AHashMap<I, f32>,
+    /// The value of each axis input as of the last update
+    values: AHashMap<I, f32>,
+    /// How far each axis control has to move before it counts
+    deadzones: AHashMap<C, f32>,
+    /// What each axis control's value is scaled by
+    sensitivities: AHashMap<C, f32>,
+    /// Axis controls that are flipped
+    inverted: AHashSet<C>,
+    /// Changes to the axis controls' settings in each axis context
+    overrides: AHashMap<C, AHashMap<C, AxisOverride>>,
+    /// The axis context whose overrides are used, if any
+    context: Option<C>,
+    /// Axis controls made from two digital controls
+    virtual_axes: AHashMap<C, VirtualAxis<C>>,
+    /// 2D controls made from two axis controls
+    dual_axes: AHashMap<C, DualAxis<C>>,
+}
+
+impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> Axes<I, C> {
+    pub fn config(&self) -> &AHashMap<I, C> {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut AHashMap<I, C> {
+        &mut self.config
+    }
+
+    /// Set one axis input's value, keeping it until it's set again.
+    pub fn set_input(&mut self, input: I, value: f32) {
+        self.pending.insert(input, value);
+    }
+
+    /// Set the value of every axis input. The ones that aren't given are at 0.
+    pub fn set_inputs(&mut self, values: impl IntoIterator<Item = (I, f32)>) {
+        self.pending.clear();
+        self.pending.extend(values);
+    }
+
+    /// Set the value of every bound axis input by asking for it.
+    pub fn query(&mut self, mut value: impl FnMut(&I) -> f32) {
+        self.pending.clear();
+        for input in self.config.keys() {
+            self.pending.insert(input.clone(), value(input));
+        }
+    }
+
+    /// Make the values given since the last update the current ones.
+    pub fn update(&mut self) {
+        self.values.clone_from(&self.pending);
+    }
+
+    pub fn set_deadzone(&mut self, ctrl: C, deadzone: f32) {
+        self.deadzones.insert(ctrl, sanitize_deadzone(deadzone));
+    }
+
+    pub fn deadzone(&self, ctrl: &C) -> f32 {
+        self.deadzones.get(ctrl).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_sensitivity(&mut self, ctrl: C, sensitivity: f32) {
+        self.sensitivities.insert(ctrl, sensitivity.max(0.0));
+    }
+
+    pub fn sensitivity(&self, ctrl: &C) -> f32 {
+        self.sensitivities.get(ctrl).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_inverted(&mut self, ctrl: C, inverted: bool) {
+        if inverted {
+            self.inverted.insert(ctrl);
+        } else {
+            self.inverted.remove(&ctrl);
+        }
+    }
+
+    pub fn inverted(&self, ctrl: &C) -> bool {
+        self.inverted.contains(ctrl)
+    }
+
+    pub fn set_override(&mut self, context: C, ctrl: C, settings: Option<AxisOverride>) {
+        match settings {
+            Some(settings) => {
+                let settings = AxisOverride {
+                    deadzone: settings.deadzone.map(sanitize_deadzone),
+                    sensitivity: settings.sensitivity.map(|sensitivity| sensitivity.max(0.0)),
+                    inverted: settings.inverted,
+                };
+                self.overrides
+                    .entry(context)
+                    .or_default()
+                    .insert(ctrl, settings);
+            }
+            None => {
+                if let Some(overrides) = self.overrides.get_mut(&context) {
+                    overrides.remove(&ctrl);
+                    if overrides.is_empty() {
+                        self.overrides.remove(&context);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn axis_override(&self, context: &C, ctrl: &C) -> Option<&AxisOverride> {
+        self.overrides.get(context)?.get(ctrl)
+    }
+
+    pub fn set_context(&mut self, context: Option<C>) {
+        self.context = context;
+    }
+
+    pub fn context(&self) -> Option<&C> {
+        self.context.as_ref()
+    }
+
+    pub fn set_virtual_axis(&mut self, ctrl: C, axis: Option<VirtualAxis<C>>) {
+        match axis {
+            Some(axis) => {
+                self.virtual_axes.insert(ctrl, axis);
+            }
+            None => {
+                self.virtual_axes.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn virtual_axis(&self, ctrl: &C) -> Option<&VirtualAxis<C>> {
+        self.virtual_axes.get(ctrl)
+    }
+
+    pub fn set_dual_axis(&mut self, ctrl: C, axis: Option<DualAxis<C>>) {
+        match axis {
+            Some(axis) => {
+                self.dual_axes.insert(ctrl, axis);
+            }
+            None => {
+                self.dual_axes.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn dual_axis(&self, ctrl: &C) -> Option<&DualAxis<C>> {
+        self.dual_axes.get(ctrl)
+    }
+
+    pub fn save(&self) -> SavedAxes<I, C> {
+        SavedAxes {
+            bindings: clone_pairs(&self.config),
+            deadzones: clone_pairs(&self.deadzones),
+            sensitivities: clone_pairs(&self.sensitivities),
+            inverted: self.inverted.iter().cloned().collect(),
+            overrides: self
+                .overrides
+                .iter()
+                .flat_map(|(context, overrides)| {
+                    overrides
+                        .iter()
+                        .map(|(ctrl, settings)| (context.clone(), ctrl.clone(), *settings))
+                })
+                .collect(),
+            context: self.context.clone(),
+            virtual_axes: clone_pairs(&self.virtual_axes),
+            dual_axes: clone_pairs(&self.dual_axes),
+        }
+    }
+
+    /// Replace the bindings and axis controls with saved ones. The inputs' values are kept.
+    pub fn restore(&mut self, saved: SavedAxes<I, C>) {
+        self.config = saved.bindings.into_iter().collect();
+        self.deadzones.clear();
+        for (ctrl, deadzone) in saved.deadzones {
+            self.set_deadzone(ctrl, deadzone);
+        }
+        self.sensitivities.clear();
+        for (ctrl, sensitivity) in saved.sensitivities {
+            self.set_sensitivity(ctrl, sensitivity);
+        }
+        self.inverted = saved.inverted.into_iter().collect();
+        self.overrides.clear();
+        for (context, ctrl, settings) in saved.overrides {
+            self.set_override(context, ctrl, Some(settings));
+        }
+        self.context = saved.context;
+        self.virtual_axes = saved.virtual_axes.into_iter().collect();
+        self.dual_axes = saved.dual_axes.into_iter().collect();
+    }
+
+    /// The value of every axis control that has something bound to it.
+    pub fn values(&self, press_time: impl Fn(&C) -> u32) -> AHashMap<C, f32> {
+        self.config
+            .values()
+            .chain(self.virtual_axes.keys())
+            .map(|ctrl| (ctrl.clone(), self.value(ctrl, &press_time)))
+            .collect()
+    }
+
+    /// The value of every 2D control.
+    pub fn vectors(&self, press_time: impl Fn(&C) -> u32) -> AHashMap<C, (f32, f32)> {
+        self.dual_axes
+            .keys()
+            .map(|ctrl| (ctrl.clone(), self.vector(ctrl, &press_time)))
+            .collect()
+    }
+
+    /// The 2D control's value, from its two axis controls.
+    pub fn vector(&self, ctrl: &C, press_time: impl Fn(&C) -> u32) -> (f32, f32) {
+        let Some(axis) = self.dual_axes.get(ctrl) else {
+            return (0.0, 0.0);
+        };
+        let x = self.value(&axis.x, &press_time);
+        let y = self.value(&axis.y, &press_time);
+        let length = x.hypot(y);
+        if axis.normalize && length > 1.0 {
+            (x / length, y / length)
+        } else {
+            (x, y)
+        }
+    }
+
+    /// The axis control's value, from whatever is bound to it that's furthest from the middle. Virtual axes
+    /// look up how long their controls have been down with `press_time`. The axis context's overrides are
+    /// used over the control's own settings.
+    pub fn value(&self, ctrl: &C, press_time: impl Fn(&C) -> u32) -> f32 {
+        let analog = self
+            .config
+            .iter()
+            .filter(|(_, bound)| *bound == ctrl)
+            .filter_map(|(input, _)| self.values.get(input).copied());
+        let digital = self.virtual_axes.get(ctrl).map(|axis| {
+            let negative = press_time(&axis.negative);
+            let positive = press_time(&axis.positive);
+            let newest_positive = match (negative, positive) {
+                (0, 0) => return 0.0,
+                (_, 0) => return -1.0,
+                (0, _) => return 1.0,
+                (negative, positive) => positive < negative,
+            };
+            match axis.conflict {
+                _ if negative == positive => 0.0,
+                AxisConflict::Neutral => 0.0,
+                AxisConflict::Newest if newest_positive => 1.0,
+                AxisConflict::Oldest if !newest_positive => 1.0,
+                AxisConflict::Newest | AxisConflict::Oldest => -1.0,
+            }
+        });
+        let raw = analog
+            .chain(digital)
+            .fold(0.0f32, |furthest, value| {
+                if value.abs() > furthest.abs() {
+                    value
+                } else {
+                    furthest
+                }
+            })
+            .clamp(-1.0, 1.0);
+        let settings = self
+            .context
+            .as_ref()
+            .and_then(|context| self.axis_override(context, ctrl))
+            .copied()
+            .unwrap_or_default();
+        // Overrides were sanitized when they were set.
+        let deadzone = settings.deadzone.unwrap_or_else(|| self.deadzone(ctrl));
+        let sensitivity = settings
+            .sensitivity
+            .unwrap_or_else(|| self.sensitivity(ctrl));
+        let inverted = settings.inverted.unwrap_or_else(|| self.inverted(ctrl));
+        let value = if raw.abs() <= deadzone {
+            0.0
+        } else {
+            // Rescale so the value still starts from 0 right outside the deadzone.
+            raw.signum() * (raw.abs() - deadzone) / (1.0 - deadzone)
+        };
+        let value = (value * sensitivity).clamp(-1.0, 1.0);
+        if inverted {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+/// Keep a deadzone from 0 to 1, with NaN counting as 0, so it can't turn axis values into NaN.
+fn sanitize_deadzone(deadzone: f32) -> f32 {
+    if deadzone.is_nan() {
+        0.0
+    } else {
+        deadzone.clamp(0.0, 1.0)
+    }
+}
+
+fn clone_pairs<K: Clone, V: Clone>(map: &AHashMap<K, V>) -> Vec<(K, V)> {
+    map.iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+impl<I, C> Default for Axes<I, C> {
+    fn default() -> Self {
+        Self {
+            config: AHashMap::new(),
+            pending: AHashMap::new(),
+            values: AHashMap::new(),
+            deadzones: AHashMap::new(),
+            sensitivities: AHashMap::new(),
+            inverted: AHashSet::new(),
+            overrides: AHashMap::new(),
+            context: None,
+            virtual_axes: AHashMap::new(),
+            dual_axes: AHashMap::new(),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{Recording, Replay};
+
+/// Settings for an arcade-style attract mode: once nobody has touched the controls for a while, the handler
+/// plays a recorded demo on its own, and hands control back the moment someone does.
+///
+/// While the demo plays, the handler's controls go up and down exactly as recorded, so your game runs the
+/// demo just like it runs a player. Any bound input held down ends the demo right away. That frame, every
+/// control the demo was holding is released, and the inputs that ended it are ignored until they're let go,
+/// so nothing is left stuck down and the button that woke the game up doesn't also do something in it.
+///
+/// Set this up with `set_attract_mode` on the handlers. The demo is a [`Recording`] of controls, like one
+/// from `record_controls`. You can also start it yourself with `start_attract_mode`, like from a title
+/// screen timer.
+///
+/// ```rust
+/// # use puppetmaster::{AttractMode, PollingInputHandler, Recorder};
+/// let mut recorder = Recorder::new();
+/// for held in [vec!["right"], vec!["right", "jump"], vec![]] {
+///     recorder.record(held);
+/// }
+///
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("d", "right"), ("space", "jump")]);
+/// input_handler.set_attract_mode(Some(AttractMode {
+///     demo: recorder.finish().unwrap(),
+///     idle_frames: 60,
+///     looping: true,
+/// }));
+///
+/// for _ in 0..61 {
+///     input_handler.update(vec![]);
+/// }
+/// assert!(input_handler.in_attract_mode());
+/// assert!(input_handler.down("right"));
+///
+/// // Someone walks up and presses jump.
+/// input_handler.update(vec!["space"]);
+/// assert!(!input_handler.in_attract_mode());
+/// assert!(input_handler.up("right"));
+/// assert!(!input_handler.clicked("jump"));
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttractMode<C> {
+    /// The demo to play.
+    pub demo: Recording<C>,
+    /// How many frames nothing has to be held for before the demo starts on its own. 0 means it never
+    /// starts on its own.
+    pub idle_frames: u32,
+    /// If the demo starts over when it's done. Otherwise, attract mode ends when the demo does.
+    pub looping: bool,
+}
+
+/// Whether the attract mode demo is playing, and where it's got to.
+#[derive(Clone, Debug)]
+pub(crate) struct AttractState<C> {
+    pub settings: AttractMode<C>,
+    /// The demo, if it's playing
+    replay: Option<Replay<C>>,
+}
+
+impl<C: std::hash::Hash + Eq + Clone> AttractState<C> {
+    pub fn new(settings: AttractMode<C>) -> Self {
+        Self {
+            settings,
+            replay: None,
+        }
+    }
+
+    pub fn playing(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    pub fn start(&mut self) {
+        self.replay = Some(Replay::new(self.settings.demo.clone()));
+    }
+
+    pub fn stop(&mut self) {
+        self.replay = None;
+    }
+
+    /// Move the demo forward a frame, and get what it holds down, if it's playing. On the frame it ends, this
+    /// holds nothing down so everything gets let go.
+    pub fn next_frame(&mut self) -> Option<Vec<C>> {
+        let replay = self.replay.as_mut()?;
+        if let Some(held) = replay.next_frame() {
+            return Some(held);
+        }
+        if self.settings.looping {
+            let mut replay = Replay::new(self.settings.demo.clone());
+            if let Some(held) = replay.next_frame() {
+                self.replay = Some(replay);
+                return Some(held);
+            }
+        }
+        self.replay = None;
+        Some(Vec::new())
+    }
+}
@@ -0,0 +1,149 @@
+//! A [Bevy](https://bevyengine.org) plugin for puppetmaster.
+//!
+//! Enable this with the `bevy` feature.
+//!
+//! [`PuppetmasterPlugin`] inserts a [`Controls`] resource, which holds a [`PollingInputHandler`]
+//! that's fed from Bevy's keyboard, mouse, and gamepad state each frame in [`PreUpdate`]
+//! (after Bevy's own [`InputSystems`]). Your systems read it with `Res<Controls<C>>`.
+//!
+//! ```rust
+//! # use bevy_app::{App, Update};
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_input::{keyboard::KeyCode, ButtonInput, InputPlugin};
+//! # use puppetmaster::bevy::{BevyInput, Controls, PuppetmasterPlugin};
+//! #[derive(Clone, Copy, Hash, Eq, PartialEq)]
+//! enum Control {
+//!     Jump,
+//! }
+//!
+//! fn jump(controls: Res<Controls<Control>>) {
+//!     if controls.clicked(Control::Jump) {
+//!         // boing
+//!     }
+//! }
+//!
+//! let mut app = App::new();
+//! app.add_plugins((
+//!     InputPlugin,
+//!     PuppetmasterPlugin::new(vec![(BevyInput::Key(KeyCode::Space), Control::Jump)]),
+//! ))
+//! .add_systems(Update, jump);
+//!
+//! # app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+//! app.update();
+//! # assert!(app.world().resource::<Controls<Control>>().clicked(Control::Jump));
+//! ```
+
+use std::{
+    hash::Hash,
+    ops::{Deref, DerefMut},
+};
+
+use ::bevy_app::{App, Plugin, PreUpdate};
+use ::bevy_ecs::prelude::*;
+use ::bevy_input::{
+    gamepad::{Gamepad, GamepadButton},
+    keyboard::KeyCode,
+    mouse::MouseButton,
+    ButtonInput, InputSystems,
+};
+
+use crate::PollingInputHandler;
+
+/// Anything Bevy treats as a button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BevyInput {
+    /// A key on the keyboard.
+    Key(KeyCode),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// A button on any gamepad.
+    Gamepad(GamepadButton),
+}
+
+impl From<KeyCode> for BevyInput {
+    fn from(key: KeyCode) -> Self {
+        BevyInput::Key(key)
+    }
+}
+
+impl From<MouseButton> for BevyInput {
+    fn from(button: MouseButton) -> Self {
+        BevyInput::Mouse(button)
+    }
+}
+
+impl From<GamepadButton> for BevyInput {
+    fn from(button: GamepadButton) -> Self {
+        BevyInput::Gamepad(button)
+    }
+}
+
+/// The resource holding the state of your controls.
+///
+/// This derefs to a [`PollingInputHandler`], so you can query controls and rebind them right on it.
+#[derive(Resource, Clone, Debug)]
+pub struct Controls<C>(pub PollingInputHandler<BevyInput, C>);
+
+impl<C> Deref for Controls<C> {
+    type Target = PollingInputHandler<BevyInput, C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C> DerefMut for Controls<C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Inserts a [`Controls`] resource with the given bindings, and keeps it updated.
+///
+/// Add Bevy's `InputPlugin` (or `DefaultPlugins`) too, or there won't be any input to read.
+pub struct PuppetmasterPlugin<C> {
+    bindings: Vec<(BevyInput, C)>,
+}
+
+impl<C> PuppetmasterPlugin<C> {
+    /// Create the plugin with the given mapping of inputs to controls.
+    pub fn new(bindings: impl IntoIterator<Item = (BevyInput, C)>) -> Self {
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl<C: Hash + Eq + Clone + Send + Sync + 'static> Plugin for PuppetmasterPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Controls(PollingInputHandler::new_with_controls(
+            self.bindings.iter().cloned(),
+        )))
+        .add_systems(PreUpdate, update_controls::<C>.after(InputSystems));
+    }
+}
+
+/// Feed Bevy's input state into the [`Controls`] resource.
+///
+/// [`PuppetmasterPlugin`] already runs this in `PreUpdate`; you only need it if you're wiring things up yourself.
+pub fn update_controls<C: Hash + Eq + Clone + Send + Sync + 'static>(
+    mut controls: ResMut<Controls<C>>,
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    mouse_buttons: Option<Res<ButtonInput<MouseButton>>>,
+    gamepads: Query<&Gamepad>,
+) {
+    let keys = keys
+        .iter()
+        .flat_map(|keys| keys.get_pressed())
+        .map(|&key| BevyInput::Key(key));
+    let mouse_buttons = mouse_buttons
+        .iter()
+        .flat_map(|buttons| buttons.get_pressed())
+        .map(|&button| BevyInput::Mouse(button));
+    let gamepad_buttons = gamepads
+        .iter()
+        .flat_map(|pad| pad.get_pressed())
+        .map(|&button| BevyInput::Gamepad(button));
+    controls.update(keys.chain(mouse_buttons).chain(gamepad_buttons));
+}
@@ -0,0 +1,111 @@
+/// A charge move, like in fighting games: hold one control for a while, then let go and press another
+/// control soon after to fire the move.
+///
+/// Set this up on the control for the move with `set_charge` on the handlers. Once `charge` has been held for
+/// at least `frames` frames, it's charged. When it's let go, `trigger` has to be pressed within `window`
+/// frames (on the same frame counts), and then the move's control clicks. Pressing `trigger` while still
+/// holding `charge`, or after the window is up, doesn't fire it.
+///
+/// The move's control doesn't need any inputs bound to it, and `charge` and `trigger` still work as usual.
+///
+/// ```rust
+/// # use puppetmaster::{Charge, PollingInputHandler};
+/// let mut input_handler =
+///     PollingInputHandler::new_with_controls(vec![("a", "back"), ("d", "forward"), ("j", "punch")]);
+/// input_handler.set_charge(
+///     "sonic_boom",
+///     Some(Charge {
+///         charge: "back",
+///         trigger: "punch",
+///         frames: 30,
+///         window: 5,
+///     }),
+/// );
+///
+/// for _ in 0..30 {
+///     input_handler.update(vec!["a"]);
+/// }
+/// input_handler.update(vec!["d"]);
+/// input_handler.update(vec!["d", "j"]);
+/// assert!(input_handler.clicked("sonic_boom"));
+///
+/// // Without charging first, it's just a punch.
+/// input_handler.update(vec![]);
+/// input_handler.update(vec!["a"]);
+/// input_handler.update(vec!["d", "j"]);
+/// assert!(input_handler.clicked("punch"));
+/// assert!(!input_handler.clicked("sonic_boom"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Charge<C> {
+    /// The control held down to charge.
+    pub charge: C,
+    /// The control pressed after letting go of `charge` to fire the move.
+    pub trigger: C,
+    /// How many frames `charge` has to be held for. 0 counts as 1.
+    pub frames: u32,
+    /// How many frames after `charge` is let go that `trigger` can still fire the move.
+    pub window: u32,
+}
+
+/// How far along a charge move is.
+#[derive(Clone, Debug)]
+pub(crate) struct ChargeState<C> {
+    pub settings: Charge<C>,
+    /// How many frames the charge control has been held for
+    held_for: u32,
+    /// How many frames it's been since a charged control was let go, while the window is open
+    since_release: Option<u32>,
+    /// If the trigger was held last frame
+    trigger_held: bool,
+}
+
+impl<C> ChargeState<C> {
+    pub fn new(settings: Charge<C>) -> Self {
+        Self {
+            settings,
+            held_for: 0,
+            since_release: None,
+            trigger_held: false,
+        }
+    }
+
+    /// Move forward a frame, given if the charge and trigger controls are held, and how many frames longer
+    /// the window is from [timing assists](crate::Controls::set_timing_assist). Returns if the move
+    /// fires.
+    pub fn update(&mut self, charge_held: bool, trigger_held: bool, assist: u32) -> bool {
+        let trigger_pressed = trigger_held && !self.trigger_held;
+        self.trigger_held = trigger_held;
+
+        if charge_held {
+            self.held_for = self.held_for.saturating_add(1);
+            self.since_release = None;
+            return false;
+        }
+        if self.held_for >= self.settings.frames.max(1) {
+            self.since_release = Some(0);
+        } else if let Some(since) = &mut self.since_release {
+            *since += 1;
+        }
+        self.held_for = 0;
+
+        match self.since_release {
+            Some(since) if since > self.settings.window.saturating_add(assist) => {
+                self.since_release = None;
+                false
+            }
+            Some(_) if trigger_pressed => {
+                self.since_release = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.held_for = 0;
+        self.since_release = None;
+        self.trigger_held = false;
+    }
+}
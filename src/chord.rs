@@ -0,0 +1,187 @@
+/// A control pressed by holding several other controls at once, like L + R to swap weapons.
+///
+/// Set this up on the chord's control with `set_chord` on the handlers. It's down while every control in
+/// `controls` is down, and while it is, those controls are let go, and stay let go until they're released.
+/// The chord's control doesn't need any inputs bound to it.
+///
+/// Players never press a chord's buttons on exactly the same frame, so the first one would click on its own
+/// before the chord does. The handler's [`TimingSettings::chord_delay`](crate::TimingSettings::chord_delay)
+/// holds presses of the controls in a chord back for that many frames, waiting for the rest of it. If the
+/// rest doesn't come in time, the press goes through late; a quick tap that's let go while waiting still
+/// clicks, on the frame it's let go.
+///
+/// ```rust
+/// # use puppetmaster::{Chord, PollingInputHandler, TimingSettings};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("l", "left"), ("r", "right")]);
+/// input_handler.set_chord("swap", Some(Chord { controls: vec!["left", "right"] }));
+/// input_handler.set_timing(TimingSettings {
+///     chord_delay: 3,
+///     ..TimingSettings::default()
+/// });
+///
+/// // Pressing both a frame apart swaps, without a stray left click first.
+/// input_handler.update(vec!["l"]);
+/// assert!(input_handler.up("left"));
+/// input_handler.update(vec!["l", "r"]);
+/// assert!(input_handler.clicked("swap"));
+/// assert!(input_handler.up("left") && input_handler.up("right"));
+///
+/// // Left on its own still works, just a little late.
+/// input_handler.update(vec![]);
+/// for _ in 0..3 {
+///     input_handler.update(vec!["l"]);
+///     assert!(input_handler.up("left"));
+/// }
+/// input_handler.update(vec!["l"]);
+/// assert!(input_handler.clicked("left"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chord<C> {
+    /// The controls that have to be held together. A chord without any controls is never down.
+    pub controls: Vec<C>,
+}
+
+/// What a chord does to the controls this frame.
+#[derive(Clone, Debug)]
+pub(crate) struct ChordUpdate<C> {
+    /// If the whole chord is held
+    pub held: bool,
+    /// Controls in the chord that were just pressed, held back while waiting for the rest of it
+    pub hold_back: Vec<C>,
+    /// Controls that were held back and then let go, which should click now instead
+    pub tapped: Vec<C>,
+}
+
+/// How long a chord has been waiting for the rest of its controls.
+#[derive(Clone, Debug)]
+pub(crate) struct ChordState<C> {
+    pub settings: Chord<C>,
+    /// How many frames presses of the chord's controls have been held back for
+    waiting: u32,
+    /// The controls being held back
+    held_back: Vec<C>,
+}
+
+impl<C: PartialEq + Clone> ChordState<C> {
+    pub fn new(settings: Chord<C>) -> Self {
+        Self {
+            settings,
+            waiting: 0,
+            held_back: Vec::new(),
+        }
+    }
+
+    /// Move forward a frame, given which controls are held this frame, which were already down (so they
+    /// aren't new presses), and how many frames to hold presses back for.
+    pub fn update(
+        &mut self,
+        held: impl Fn(&C) -> bool,
+        down: impl Fn(&C) -> bool,
+        delay: u32,
+    ) -> ChordUpdate<C> {
+        let controls = &self.settings.controls;
+        if !controls.is_empty() && controls.iter().all(&held) {
+            self.reset();
+            return ChordUpdate {
+                held: true,
+                hold_back: Vec::new(),
+                tapped: Vec::new(),
+            };
+        }
+
+        let tapped = std::mem::take(&mut self.held_back)
+            .into_iter()
+            .filter(|ctrl| !held(ctrl))
+            .collect();
+        let pressed = controls
+            .iter()
+            .filter(|ctrl| held(ctrl) && !down(ctrl))
+            .cloned()
+            .collect::<Vec<_>>();
+        let hold_back = if pressed.is_empty() {
+            self.waiting = 0;
+            Vec::new()
+        } else if self.waiting < delay {
+            self.waiting += 1;
+            self.held_back.clone_from(&pressed);
+            pressed
+        } else {
+            // The rest of the chord didn't come in time, so let them through
+            Vec::new()
+        };
+        ChordUpdate {
+            held: false,
+            hold_back,
+            tapped,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.waiting = 0;
+        self.held_back.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn left_right() -> ChordState<char> {
+        ChordState::new(Chord {
+            controls: vec!['l', 'r'],
+        })
+    }
+
+    #[test]
+    fn empty_chord_is_never_held() {
+        let mut state = ChordState::<char>::new(Chord {
+            controls: Vec::new(),
+        });
+        assert!(!state.update(|_| true, |_| false, 3).held);
+    }
+
+    #[test]
+    fn whole_chord_is_held() {
+        let mut state = left_right();
+        let update = state.update(|_| true, |_| false, 3);
+        assert!(update.held);
+        assert!(update.hold_back.is_empty());
+    }
+
+    #[test]
+    fn tap_while_waiting_goes_through_on_release() {
+        let mut state = left_right();
+        assert_eq!(
+            state.update(|c| *c == 'l', |_| false, 3).hold_back,
+            vec!['l']
+        );
+        let update = state.update(|_| false, |_| false, 3);
+        assert_eq!(update.tapped, vec!['l']);
+        assert!(update.hold_back.is_empty());
+    }
+
+    #[test]
+    fn press_goes_through_after_the_delay() {
+        let mut state = left_right();
+        for _ in 0..2 {
+            assert_eq!(
+                state.update(|c| *c == 'l', |_| false, 2).hold_back,
+                vec!['l']
+            );
+        }
+        assert!(state
+            .update(|c| *c == 'l', |_| false, 2)
+            .hold_back
+            .is_empty());
+    }
+
+    #[test]
+    fn no_delay_never_holds_back() {
+        let mut state = left_right();
+        assert!(state
+            .update(|c| *c == 'l', |_| false, 0)
+            .hold_back
+            .is_empty());
+    }
+}
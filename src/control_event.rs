@@ -0,0 +1,160 @@
+use std::{fmt, sync::Arc};
+
+/// Something that happened to a control during an update.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ControlEvent<C> {
+    /// The control was pressed down this frame.
+    Pressed(C),
+    /// The control was released this frame.
+    Released(C),
+    /// The control is being held down, and the OS sent a key-repeat for one of its inputs this frame.
+    ///
+    /// These only show up if the handler's [`RepeatPolicy`] is [`Surface`](RepeatPolicy::Surface).
+    Repeated(C),
+}
+
+type ListenerFn<C> = dyn Fn(&ControlEvent<C>) + Send + Sync;
+
+/// Identifies a listener added with `add_listener` on the handlers, so it can be removed later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ListenerId(pub(crate) u64);
+
+/// A callback for a control's events.
+#[derive(Clone)]
+pub(crate) struct Listener<C> {
+    pub id: ListenerId,
+    pub control: C,
+    callback: Arc<ListenerFn<C>>,
+}
+
+impl<C> Listener<C> {
+    pub fn new(
+        id: ListenerId,
+        control: C,
+        callback: impl Fn(&ControlEvent<C>) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            id,
+            control,
+            callback: Arc::new(callback),
+        }
+    }
+
+    pub fn call(&self, event: &ControlEvent<C>) {
+        (self.callback)(event);
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Listener<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listener")
+            .field("id", &self.id)
+            .field("control", &self.control)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What changed during an update.
+///
+/// Every handler's `update` returns one of these. Menus and terminal games can use it to skip redrawing, or
+/// go to sleep, on frames where the player didn't do anything.
+///
+/// Controls that are simply still held down don't count as a change, even though their
+/// [`press_time`](crate::Controls::press_time) went up.
+///
+/// ```rust
+/// # use puppetmaster::PollingInputHandler;
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("down", "menu_down")]);
+///
+/// let summary = input_handler.update(vec!["down"]);
+/// assert_eq!(summary.pressed, 1);
+/// assert!(summary.changed());
+///
+/// // Nothing new happened, so the menu doesn't need redrawing
+/// assert!(!input_handler.update(vec!["down"]).changed());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct UpdateSummary {
+    /// How many controls were pressed (or clicked again by turbo).
+    pub pressed: usize,
+    /// How many controls were released.
+    pub released: usize,
+    /// How many controls got a key-repeat. See [`ControlEvent::Repeated`].
+    pub repeated: usize,
+    /// If the pointer moved, appeared, or left.
+    pub pointer_moved: bool,
+    /// If there was any scrolling.
+    pub scrolled: bool,
+    /// If anything was typed or edited during text entry.
+    pub typed: bool,
+}
+
+impl UpdateSummary {
+    /// Return if anything changed at all.
+    pub fn changed(&self) -> bool {
+        self.pressed > 0
+            || self.released > 0
+            || self.repeated > 0
+            || self.pointer_moved
+            || self.scrolled
+            || self.typed
+    }
+}
+
+/// What an [`EventInputHandler`](crate::EventInputHandler) does when it gets a key-down event
+/// for an input that's already held down.
+///
+/// Most engines forward the OS's key auto-repeat this way.
+///
+/// ```rust
+/// # use puppetmaster::{ControlEvent, EventInputHandler, RepeatPolicy};
+/// let mut input_handler = EventInputHandler::new_with_controls(vec![('j', "scroll down")]);
+/// input_handler.set_repeat_policy(RepeatPolicy::Surface);
+///
+/// input_handler.on_input_down('j');
+/// input_handler.update();
+/// assert_eq!(input_handler.events(), &[ControlEvent::Pressed("scroll down")]);
+///
+/// // The OS starts repeating the key ...
+/// input_handler.on_input_down('j');
+/// input_handler.update();
+/// assert!(input_handler.repeated("scroll down"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RepeatPolicy {
+    /// Throw the repeats away. This is the default.
+    #[default]
+    Ignore,
+    /// Report repeats as [`ControlEvent::Repeated`]. This is useful for text boxes and menus
+    /// that should scroll when a key is held down.
+    Surface,
+}
+
+/// What happens when another input bound to a control goes down while the control is already held, like
+/// pressing the up arrow while W is held and both are bound to `Up`.
+///
+/// Set this per control with `set_retrigger_policy` on the handlers. Every handler treats it the same way.
+///
+/// ```rust
+/// # use puppetmaster::{PollingInputHandler, RetriggerPolicy};
+/// let mut input_handler =
+///     PollingInputHandler::new_with_controls(vec![("w", "up"), ("up", "up"), ("j", "fire"), ("k", "fire")]);
+/// input_handler.set_retrigger_policy("fire", RetriggerPolicy::Click);
+///
+/// input_handler.update(vec!["w", "j"]);
+/// input_handler.update(vec!["w", "up", "j", "k"]);
+/// assert!(!input_handler.clicked("up"));
+/// // Two buttons for rapid fire
+/// assert!(input_handler.clicked("fire"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RetriggerPolicy {
+    /// The control just stays down, without clicking again. This is the default.
+    #[default]
+    Ignore,
+    /// The control clicks again, as if it had been pressed anew, but keeps counting its press time.
+    Click,
+}
@@ -0,0 +1,2340 @@
+use std::{collections::VecDeque, hash::Hash, time::Instant};
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::{
+    analog::Axes,
+    attract::AttractState,
+    charge::ChargeState,
+    chord::ChordState,
+    control_event::Listener,
+    dwell::DwellState,
+    flick::StickFlickState,
+    gesture::MouseGestureState,
+    long_press::{LongPressState, TapHoldState},
+    macros::Playback,
+    pointer::{DragState, PointerState},
+    scanning::ScanState,
+    scroll::ScrollState,
+    stick_navigation::StickNavigationState,
+    text::TextState,
+    AttractMode, AutoRepeat, Charge, Chord, Clock, Confirmation, ControlEvent, ControlSettings,
+    Debounce, Dwell, FrameNumber, HoldConfirm, InputSnapshot, ListenerId, LongPress, Macro,
+    MinHold, MouseGestures, RateLimit, RetriggerPolicy, Rumble, RumbleRequest, Scanning,
+    StickFlicks, StickNavigation, TapOrHold, TimerClocks, TimingSettings, TriggerEffect,
+    TriggerEffectRequest, TriggerSide, UpdateSummary,
+};
+
+/// The fastest game time can run compared to real time.
+const MAX_TIME_SCALE: f32 = 1000.0;
+
+/// The time on both clocks, for one tick.
+#[derive(Clone, Copy, Debug)]
+struct Now {
+    real: Instant,
+    game: Instant,
+}
+
+impl Now {
+    fn on(self, clock: Clock) -> Instant {
+        match clock {
+            Clock::Game => self.game,
+            Clock::Real => self.real,
+        }
+    }
+}
+
+/// The state of one control.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ControlState {
+    /// How many frames the control has been down for
+    pub time: u32,
+    /// If the control was clicked this frame
+    pub clicked: bool,
+}
+
+/// What a debounced control has been doing.
+#[derive(Clone, Copy, Debug, Default)]
+struct DebounceState {
+    /// If the control counts as held after debouncing
+    held: bool,
+    /// When the control was last released
+    released_at: Option<(u64, Instant)>,
+}
+
+impl DebounceState {
+    /// Move forward a frame, given if the control is held. Returns if it should count as held.
+    fn update(&mut self, debounce: Debounce, held: bool, frame: u64, now: Instant) -> bool {
+        if held && !self.held {
+            let too_soon = self
+                .released_at
+                .is_some_and(|(released_frame, released_time)| match debounce {
+                    Debounce::Frames(frames) => frame - released_frame <= frames as u64,
+                    Debounce::Duration(duration) => now - released_time < duration,
+                });
+            if !too_soon {
+                self.held = true;
+            }
+        } else if !held && self.held {
+            self.held = false;
+            self.released_at = Some((frame, now));
+        }
+        self.held
+    }
+}
+
+/// What a control waiting for confirmation has been doing.
+#[derive(Clone, Copy, Debug, Default)]
+struct ConfirmationState {
+    /// If the control counts as held after confirmation
+    held: bool,
+    /// How many frames in a row the inputs have disagreed with `held`
+    streak: u32,
+}
+
+impl ConfirmationState {
+    /// Move forward a frame, given if the control is held. Returns if it should count as held.
+    fn update(&mut self, confirmation: Confirmation, held: bool) -> bool {
+        if held == self.held {
+            self.streak = 0;
+        } else {
+            self.streak += 1;
+            let needed = if held {
+                confirmation.press
+            } else {
+                confirmation.release
+            };
+            if self.streak >= needed {
+                self.held = held;
+                self.streak = 0;
+            }
+        }
+        self.held
+    }
+}
+
+/// Move a control with a minimum hold time forward a frame, given if it's held and when it was pressed.
+/// Returns if it should count as held.
+fn update_min_hold(
+    pressed_at: &mut Option<(u64, Instant)>,
+    min_hold: MinHold,
+    held: bool,
+    frame: u64,
+    now: Instant,
+) -> bool {
+    if !held {
+        *pressed_at = None;
+        return false;
+    }
+    let (pressed_frame, pressed_time) = *pressed_at.get_or_insert((frame, now));
+    match min_hold {
+        MinHold::Frames(frames) => frame - pressed_frame + 1 >= frames as u64,
+        MinHold::Duration(duration) => now - pressed_time >= duration,
+    }
+}
+
+/// Check a rate-limited control's click against the times of its recent clicks, recording it if it's
+/// allowed. Returns if it's allowed.
+fn allow_click(clicks: &mut VecDeque<Instant>, limit: RateLimit, now: Instant) -> bool {
+    while clicks
+        .front()
+        .is_some_and(|&clicked| now - clicked >= limit.period)
+    {
+        clicks.pop_front();
+    }
+    if clicks.len() < limit.clicks as usize {
+        clicks.push_back(now);
+        true
+    } else {
+        false
+    }
+}
+
+/// How far a hold-to-confirm control is toward confirming.
+#[derive(Clone, Copy, Debug, Default)]
+struct HoldConfirmState {
+    /// When the control was pressed, if it's down
+    pressed_at: Option<Instant>,
+    /// From 0 to 1
+    progress: f32,
+    /// If the hold was confirmed this frame
+    confirmed: bool,
+}
+
+impl HoldConfirmState {
+    /// Move forward a frame, given how long the control has been down for.
+    fn update(&mut self, hold: HoldConfirm, time: u32, now: Instant) {
+        let was_done = self.progress >= 1.0;
+        if time == 0 {
+            *self = HoldConfirmState::default();
+            return;
+        }
+        let pressed_at = *self.pressed_at.get_or_insert(now);
+        self.progress = match hold {
+            HoldConfirm::Frames(0) => 1.0,
+            HoldConfirm::Frames(frames) => time as f32 / frames as f32,
+            HoldConfirm::Duration(duration) if duration.is_zero() => 1.0,
+            HoldConfirm::Duration(duration) => {
+                (now - pressed_at).as_secs_f32() / duration.as_secs_f32()
+            }
+        }
+        .min(1.0);
+        self.confirmed = !was_done && self.progress >= 1.0;
+    }
+}
+
+/// Which stage a staged control has been held to.
+#[derive(Clone, Debug, Default)]
+struct StageState {
+    /// How many frames the control has to be held for to reach each stage, in order
+    thresholds: Vec<u32>,
+    /// The stage the control is at, if it's down
+    stage: Option<usize>,
+    /// The stage reached this frame
+    reached: Option<usize>,
+    /// The stage the control was at when it was released this frame
+    released: Option<usize>,
+}
+
+impl StageState {
+    /// Move forward a frame, given how long the control has been down for.
+    fn update(&mut self, time: u32) {
+        self.reached = None;
+        self.released = None;
+        if time == 0 {
+            self.released = self.stage.take();
+            return;
+        }
+        let stage = self
+            .thresholds
+            .iter()
+            .filter(|&&threshold| time >= threshold)
+            .count();
+        if stage > self.stage.unwrap_or(0) {
+            self.reached = Some(stage);
+        }
+        self.stage = Some(stage);
+    }
+}
+
+/// A follow-up window, and the controls clicked during it.
+#[derive(Clone, Debug)]
+struct FollowUpWindow<C> {
+    /// The frame it was opened on. It covers the frames after this one.
+    opened: FrameNumber,
+    /// How many frames it's open for
+    frames: u32,
+    /// The controls clicked while it was open, and how many frames in they were first clicked
+    pressed: AHashMap<C, u32>,
+}
+
+impl<C> FollowUpWindow<C> {
+    /// If the window, stretched by `extra` frames, covers the given frame.
+    fn covers(&self, frame: FrameNumber, extra: u32) -> bool {
+        frame > self.opened
+            && frame - self.opened <= self.frames as FrameNumber + extra as FrameNumber
+    }
+}
+
+/// How many frames longer a control's timing windows are, if assists are on.
+fn assist_frames<C: Hash + Eq>(assists: &AHashMap<C, u32>, on: bool, ctrl: &C) -> u32 {
+    if on {
+        assists.get(ctrl).copied().unwrap_or(0)
+    } else {
+        0
+    }
+}
+
+/// Where a sticky control is in its cycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Sticky {
+    /// Not pressed at all.
+    #[default]
+    Idle,
+    /// Physically held down. If `chorded`, another control was pressed along with it, so it was used
+    /// like a normal held modifier and won't stick when it's let go.
+    Held { chorded: bool },
+    /// Tapped, so it stays down by itself. Once another control has been pressed (`engaged`) and then
+    /// released, it lets go.
+    Armed { engaged: bool },
+}
+
+impl Sticky {
+    /// Move forward a frame, given if the control is held and if any non-sticky control is held.
+    /// Returns if the control should count as held.
+    fn update(&mut self, held: bool, other_held: bool) -> bool {
+        *self = match (*self, held) {
+            (Sticky::Idle, false) => Sticky::Idle,
+            (Sticky::Idle, true) => Sticky::Held {
+                chorded: other_held,
+            },
+            (Sticky::Held { chorded }, true) => Sticky::Held {
+                chorded: chorded || other_held,
+            },
+            (Sticky::Held { chorded: false }, false) => Sticky::Armed {
+                engaged: other_held,
+            },
+            (Sticky::Held { chorded: true }, false) => Sticky::Idle,
+            // Tapping it again while it's armed cancels it
+            (Sticky::Armed { .. }, true) => Sticky::Held { chorded: true },
+            (Sticky::Armed { engaged: true }, false) if !other_held => Sticky::Idle,
+            (Sticky::Armed { engaged }, false) => Sticky::Armed {
+                engaged: engaged || other_held,
+            },
+        };
+        *self != Sticky::Idle
+    }
+}
+
+/// What a handler knows about its controls, once its inputs have been resolved to them.
+///
+/// Every handler keeps one of these, so per-control behavior works the same no matter how the inputs come in.
+#[derive(Clone, Debug)]
+pub(crate) struct ControlStates<C> {
+    /// The state of each control
+    states: AHashMap<C, ControlState>,
+    /// Controls that had an input held down as of the last tick
+    held: AHashSet<C>,
+    /// Controls that toggle on and off with each press instead of being held
+    toggles: AHashSet<C>,
+    /// Toggle controls that are switched on
+    toggled_on: AHashSet<C>,
+    /// The timing settings for controls that don't have their own
+    timing: TimingSettings,
+    /// Controls with their own debounce setting
+    debounce: AHashMap<C, Option<Debounce>>,
+    /// What each debounced control has been doing
+    debounce_states: AHashMap<C, DebounceState>,
+    /// Controls with their own confirmation setting
+    confirmation: AHashMap<C, Option<Confirmation>>,
+    /// What each control waiting for confirmation has been doing
+    confirmation_states: AHashMap<C, ConfirmationState>,
+    /// Controls with their own minimum hold time
+    min_hold: AHashMap<C, Option<MinHold>>,
+    /// When each control with a minimum hold time was pressed, if it's held
+    min_hold_states: AHashMap<C, Option<(u64, Instant)>>,
+    /// Controls that are held to confirm something, and how far along they are
+    hold_confirms: AHashMap<C, (HoldConfirm, HoldConfirmState)>,
+    stick_navigation: Option<StickNavigationState<C>>,
+    mouse_gestures: Option<MouseGestureState<C>>,
+    stick_flicks: Option<StickFlickState<C>>,
+    /// Controls split into short and long presses, by the control that's split
+    long_presses: AHashMap<C, LongPressState<C>>,
+    /// Each control's own hold threshold, or `None` if it's turned off for the control
+    hold_thresholds: AHashMap<C, Option<u32>>,
+    /// Controls that tell taps from holds, and how their presses turned out
+    tap_holds: AHashMap<C, TapHoldState>,
+    /// How many frames longer each control's timing windows are when assists are on
+    assists: AHashMap<C, u32>,
+    /// If timing assists are on
+    assist_mode: bool,
+    /// Follow-up windows, by name
+    follow_ups: AHashMap<String, FollowUpWindow<C>>,
+    /// Charge moves, by the control they click
+    charges: AHashMap<C, ChargeState<C>>,
+    /// Chords, by the control they press
+    chords: AHashMap<C, ChordState<C>>,
+    /// Controls with staged presses, and which stage they're at
+    stages: AHashMap<C, StageState>,
+    /// Controls with their own rate limit
+    rate_limit: AHashMap<C, Option<RateLimit>>,
+    /// When each rate-limited control recently clicked
+    rate_limit_clicks: AHashMap<C, VecDeque<Instant>>,
+    /// Controls that stay down after being tapped, until another control is pressed and released
+    sticky: AHashMap<C, Sticky>,
+    /// The one-switch scan, if it's on
+    scan: Option<ScanState<C>>,
+    /// Dwell activation, if it's on
+    dwell: Option<DwellState<C>>,
+    /// Where the pointer is
+    pointer: PointerState,
+    /// Scrolling, and the controls it clicks
+    scroll: ScrollState<C>,
+    /// Controls that can be dragged, and how far the pointer has to move for a press to be a drag
+    drag_thresholds: AHashMap<C, f32>,
+    /// Where each draggable control is in telling clicks from drags
+    drags: AHashMap<C, DragState>,
+    /// Macros to play when their trigger control is pressed
+    macros: AHashMap<C, Macro<C>>,
+    /// Macros that are playing right now
+    playbacks: Vec<Playback<C>>,
+    /// Controls with their own turbo setting
+    turbo: AHashMap<C, Option<u32>>,
+    /// Controls with their own auto-repeat setting
+    auto_repeat: AHashMap<C, Option<AutoRepeat>>,
+    /// If the game is paused, so only exempt controls change
+    paused: bool,
+    /// Controls that keep working while the game is paused
+    pause_exempt: AHashSet<C>,
+    /// The controls that were down when the state was stashed, and how long they'd been down for
+    stashed: Option<AHashMap<C, u32>>,
+    /// How many frames after clicking each control ignores new presses
+    lockouts: AHashMap<C, u32>,
+    /// The last frame each locked-out control ignores new presses on
+    locked_until: AHashMap<C, FrameNumber>,
+    /// Controls that are only down for the frame they're pressed on
+    click_only: AHashSet<C>,
+    /// Controls pressed during a pause, which are ignored until they're released
+    swallowed: AHashSet<C>,
+    /// Text entry, if it's on
+    text: TextState,
+    /// Controls that keep working during text entry
+    text_passthrough: AHashSet<C>,
+    /// If the first tick after the window regains focus should ignore every input
+    refocus_suppression: bool,
+    /// If the next tick should ignore every input
+    suppress_next: bool,
+    /// If waiting for any control, the first one pressed since it started waiting
+    waiting: Option<Option<C>>,
+    /// Callbacks for controls' events, in the order they were added
+    listeners: Vec<Listener<C>>,
+    /// The ID the next listener gets
+    next_listener: u64,
+    /// Rumble the game has asked for that hasn't been played yet
+    rumble: Vec<RumbleRequest>,
+    /// Trigger effects the game has asked for that haven't been applied yet
+    trigger_effects: Vec<TriggerEffectRequest>,
+    /// Controls that click again when another of their inputs goes down while they're held
+    retriggers: AHashSet<C>,
+    /// How many inputs each control that retriggers had held down last tick
+    input_counts: AHashMap<C, usize>,
+    /// Controls that had another input go down this tick while they were held
+    retriggered: AHashSet<C>,
+    /// How many frames apart each control's taps can be to count as one burst, if not the default
+    tap_windows: AHashMap<C, u32>,
+    /// How many taps are in each control's latest burst, and the frame of the last one
+    taps: AHashMap<C, (u32, FrameNumber)>,
+    /// How many ticks in a row nothing has been held for
+    idle_frames: u64,
+    /// The attract mode demo, if there is one
+    attract: Option<AttractState<C>>,
+    /// How fast game time runs compared to real time
+    time_scale: f32,
+    /// Which clock each time-based timer runs on
+    timer_clocks: TimerClocks,
+    /// Where game time has got to, as of the last tick
+    game_clock: Instant,
+    /// When the last tick happened, in real time
+    last_tick: Option<Instant>,
+    /// How many seconds of game time each tick counts for, if the game has said
+    delta_time: Option<f32>,
+    /// How many seconds of game time each control that's down has been down for
+    press_seconds: AHashMap<C, f32>,
+    /// When each control that's down was pressed
+    pressed_at: AHashMap<C, Instant>,
+    /// When controls' inputs were actually pressed, if the handler knows better than the tick does
+    press_stamps: AHashMap<C, Instant>,
+    /// The frames each control was last clicked and released on
+    last_frames: AHashMap<C, (Option<FrameNumber>, Option<FrameNumber>)>,
+    /// What happened to the controls during the last tick
+    events: Vec<ControlEvent<C>>,
+    /// How many times `tick` has been called
+    frame: FrameNumber,
+}
+
+impl<C: Hash + Eq + Clone> ControlStates<C> {
+    /// Move the controls forward a frame. `controls` should list each control once, and `is_held` says
+    /// if any of the control's inputs are held down.
+    pub fn tick<'a>(
+        &mut self,
+        controls: impl IntoIterator<Item = &'a C>,
+        mut is_held: impl FnMut(&C) -> bool,
+    ) where
+        C: 'a,
+    {
+        self.events.clear();
+        // A restored frame can be anything, so don't count past the end.
+        self.frame = self.frame.saturating_add(1);
+        let now = self.now();
+        let suppressed = std::mem::take(&mut self.suppress_next);
+        self.pointer.update();
+        let mut controls = controls
+            .into_iter()
+            .map(|control| (control.clone(), !suppressed && is_held(control)))
+            .collect::<Vec<_>>();
+
+        let live = controls.iter().any(|(_, held)| *held);
+        self.idle_frames = if live { 0 } else { self.idle_frames + 1 };
+        if let Some(attract) = &mut self.attract {
+            if attract.playing() && live {
+                // Hand control back: let go of everything the demo held, and ignore whatever woke it up until
+                // it's released.
+                attract.stop();
+                self.swallowed.extend(
+                    controls
+                        .into_iter()
+                        .filter(|(_, held)| *held)
+                        .map(|(control, _)| control),
+                );
+                self.replay_frame(Vec::new(), now);
+                return;
+            }
+            let idle_frames = attract.settings.idle_frames as u64;
+            if !attract.playing() && idle_frames > 0 && self.idle_frames >= idle_frames {
+                attract.start();
+            }
+            if let Some(held) = attract.next_frame() {
+                self.replay_frame(held, now);
+                return;
+            }
+        }
+
+        if let Some(scan) = &self.scan {
+            // Scan targets don't need inputs, so make sure they get ticked too
+            for target in scan.settings.targets.iter() {
+                if !controls.iter().any(|(control, _)| control == target) {
+                    controls.push((target.clone(), false));
+                }
+            }
+        }
+        if let Some(dwell) = &self.dwell {
+            // Same for dwell regions
+            for region in dwell.settings.regions.iter() {
+                if !controls
+                    .iter()
+                    .any(|(control, _)| *control == region.control)
+                {
+                    controls.push((region.control.clone(), false));
+                }
+            }
+        }
+        // And scroll controls
+        for ctrl in self.scroll.controls() {
+            if !controls.iter().any(|(control, _)| control == ctrl) {
+                controls.push((ctrl.clone(), false));
+            }
+        }
+        // And stick navigation controls
+        for ctrl in self
+            .stick_navigation
+            .iter()
+            .flat_map(StickNavigationState::controls)
+        {
+            if !controls.iter().any(|(control, _)| control == ctrl) {
+                controls.push((ctrl.clone(), false));
+            }
+        }
+        // And mouse gesture controls
+        for ctrl in self
+            .mouse_gestures
+            .iter()
+            .flat_map(MouseGestureState::controls)
+        {
+            if !controls.iter().any(|(control, _)| control == ctrl) {
+                controls.push((ctrl.clone(), false));
+            }
+        }
+        // And stick flick controls
+        for ctrl in self.stick_flicks.iter().flat_map(StickFlickState::controls) {
+            if !controls.iter().any(|(control, _)| control == ctrl) {
+                controls.push((ctrl.clone(), false));
+            }
+        }
+        // And the halves of long-press controls
+        for long_press in self.long_presses.values() {
+            for ctrl in [&long_press.settings.short, &long_press.settings.long] {
+                if !controls.iter().any(|(control, _)| control == ctrl) {
+                    controls.push((ctrl.clone(), false));
+                }
+            }
+        }
+        // And charge moves, and the controls they watch
+        for (ctrl, charge) in self.charges.iter() {
+            for ctrl in [ctrl, &charge.settings.charge, &charge.settings.trigger] {
+                if !controls.iter().any(|(control, _)| control == ctrl) {
+                    controls.push((ctrl.clone(), false));
+                }
+            }
+        }
+        // And chords
+        for ctrl in self.chords.keys() {
+            if !controls.iter().any(|(control, _)| control == ctrl) {
+                controls.push((ctrl.clone(), false));
+            }
+        }
+        // And for controls macros were holding, so they get released
+        for ctrl in self.playbacks.iter().flat_map(Playback::held) {
+            if !controls.iter().any(|(control, _)| control == ctrl) {
+                controls.push((ctrl.clone(), false));
+            }
+        }
+
+        for (control, held) in controls.iter_mut() {
+            if let Some(debounce) = self.debounce(control) {
+                let state = self.debounce_states.entry(control.clone()).or_default();
+                *held = state.update(
+                    debounce,
+                    *held,
+                    self.frame,
+                    now.on(self.timer_clocks.debounce),
+                );
+            }
+            if let Some(confirmation) = self.confirmation(control) {
+                let state = self.confirmation_states.entry(control.clone()).or_default();
+                *held = state.update(confirmation, *held);
+            }
+            if let Some(min_hold) = self.min_hold(control) {
+                let pressed_at = self.min_hold_states.entry(control.clone()).or_default();
+                *held = update_min_hold(
+                    pressed_at,
+                    min_hold,
+                    *held,
+                    self.frame,
+                    now.on(self.timer_clocks.min_hold),
+                );
+            }
+        }
+
+        if let Some(scan) = &mut self.scan {
+            let switch_held = controls
+                .iter()
+                .any(|(control, held)| *held && *control == scan.settings.switch);
+            if let Some(target) = scan.update(switch_held) {
+                for (control, held) in controls.iter_mut() {
+                    if control == target {
+                        *held = true;
+                    }
+                }
+            }
+        }
+        if let Some(dwell) = &mut self.dwell {
+            if let Some(target) = dwell.update(self.pointer.position()) {
+                for (control, held) in controls.iter_mut() {
+                    if control == target {
+                        *held = true;
+                    }
+                }
+            }
+        }
+        for target in self.scroll.update() {
+            for (control, held) in controls.iter_mut() {
+                if control == target {
+                    *held = true;
+                }
+            }
+        }
+        if let Some(target) = self.stick_navigation.as_mut().and_then(|nav| nav.update()) {
+            for (control, held) in controls.iter_mut() {
+                if control == target {
+                    *held = true;
+                }
+            }
+        }
+        if let Some(target) = self
+            .stick_flicks
+            .as_mut()
+            .and_then(|flicks| flicks.update())
+        {
+            for (control, held) in controls.iter_mut() {
+                if control == target {
+                    *held = true;
+                }
+            }
+        }
+        if let Some(gestures) = &mut self.mouse_gestures {
+            let hold_held = controls
+                .iter()
+                .any(|(control, held)| *held && Some(control) == gestures.settings.hold.as_ref());
+            if let Some(target) = gestures.update(self.pointer.position(), hold_held) {
+                for (control, held) in controls.iter_mut() {
+                    if control == target {
+                        *held = true;
+                    }
+                }
+            }
+        }
+        for (source, long_press) in self.long_presses.iter_mut() {
+            let source_held = controls
+                .iter()
+                .any(|(control, held)| *held && control == source);
+            if let Some(target) = long_press.update(source_held) {
+                for (control, held) in controls.iter_mut() {
+                    if control == target {
+                        *held = true;
+                    }
+                }
+            }
+        }
+        for (target, charge) in self.charges.iter_mut() {
+            let is_held = |ctrl: &C| {
+                controls
+                    .iter()
+                    .any(|(control, held)| *held && control == ctrl)
+            };
+            let fired = charge.update(
+                is_held(&charge.settings.charge),
+                is_held(&charge.settings.trigger),
+                assist_frames(&self.assists, self.assist_mode, target),
+            );
+            if fired {
+                for (control, held) in controls.iter_mut() {
+                    if control == target {
+                        *held = true;
+                    }
+                }
+            }
+        }
+        for (target, chord) in self.chords.iter_mut() {
+            let update = chord.update(
+                |ctrl| {
+                    controls
+                        .iter()
+                        .any(|(control, held)| *held && control == ctrl)
+                },
+                // Controls ignored until they're released can't be held back, or they'd look released
+                |ctrl| self.held.contains(ctrl) || self.swallowed.contains(ctrl),
+                self.timing.chord_delay,
+            );
+            for (control, held) in controls.iter_mut() {
+                if (update.held && control == target) || update.tapped.contains(control) {
+                    *held = true;
+                } else if update.hold_back.contains(control) {
+                    *held = false;
+                }
+            }
+            if update.held {
+                // Let go of the controls in the chord until they're released, so they don't click on their own
+                self.swallowed
+                    .extend(chord.settings.controls.iter().cloned());
+            }
+        }
+
+        for (control, held) in controls.iter() {
+            if *held && !self.held.contains(control) {
+                if let Some(steps) = self.macros.get(control) {
+                    self.playbacks.push(Playback::new(steps.clone()));
+                }
+            }
+        }
+        self.playbacks.retain_mut(|playback| !playback.update());
+        for ctrl in self.playbacks.iter().flat_map(Playback::held) {
+            match controls.iter_mut().find(|(control, _)| control == ctrl) {
+                Some((_, held)) => *held = true,
+                None => controls.push((ctrl.clone(), true)),
+            }
+        }
+
+        self.text.update();
+        if self.text.entry().is_some() {
+            // Typing shouldn't do anything in the game, and keys held when typing started shouldn't either.
+            // Keys the IME is using are off limits entirely.
+            let composing = self.text.composing();
+            for (control, held) in controls.iter() {
+                if *held && (composing || !self.text_passthrough.contains(control)) {
+                    self.swallowed.insert(control.clone());
+                }
+            }
+        }
+
+        let other_held = controls
+            .iter()
+            .any(|(control, held)| *held && !self.sticky.contains_key(control));
+
+        for (control, mut held) in controls {
+            let released =
+                !held && self.held.contains(&control) && !self.toggles.contains(&control);
+            if self.paused && !self.pause_exempt.contains(&control) && !released {
+                // Freeze the control where it is. If it gets pressed now, it shouldn't click on unpause either.
+                // Releases still go through, so a control let go during the pause isn't down after it.
+                match self.states.get_mut(&control) {
+                    Some(state) if state.time >= 1 => state.clicked = false,
+                    _ if held => {
+                        self.swallowed.insert(control);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            if self.swallowed.contains(&control) {
+                if held {
+                    held = false;
+                } else {
+                    self.swallowed.remove(&control);
+                }
+            }
+            let locked = self
+                .locked_until
+                .get(&control)
+                .is_some_and(|&until| self.frame <= until);
+            if held && locked && !self.held.contains(&control) {
+                // Throw the press away entirely, rather than letting it through once the lockout is over
+                self.swallowed.insert(control.clone());
+                held = false;
+            }
+
+            if let Some(sticky) = self.sticky.get_mut(&control) {
+                held = sticky.update(held, other_held);
+            }
+            let was_held = if held {
+                !self.held.insert(control.clone())
+            } else {
+                self.held.remove(&control)
+            };
+
+            let down = if self.toggles.contains(&control) {
+                if held && !was_held && !self.toggled_on.remove(&control) {
+                    self.toggled_on.insert(control.clone());
+                }
+                self.toggled_on.contains(&control)
+            } else {
+                held
+            };
+
+            if down && self.click_only.contains(&control) {
+                // Let go next frame, and stay let go until it's released
+                self.swallowed.insert(control.clone());
+            }
+            let lockout = self.lockouts.get(&control).copied();
+            self.advance(control.clone(), down, now);
+            if let Some(lockout) = lockout.filter(|_| self.clicked(&control)) {
+                self.locked_until
+                    .insert(control, self.frame.saturating_add(lockout as FrameNumber));
+            }
+        }
+        self.finish_tick(now);
+    }
+
+    /// Move the controls forward a frame, holding down exactly the given controls. This skips everything
+    /// that turns inputs into controls, since the controls were already worked out when they were recorded.
+    pub fn tick_replay(&mut self, held: impl IntoIterator<Item = C>) {
+        self.events.clear();
+        // A restored frame can be anything, so don't count past the end.
+        self.frame = self.frame.saturating_add(1);
+        let now = self.now();
+        self.pointer.update();
+        self.replay_frame(held, now);
+    }
+
+    /// Move every control forward, holding down exactly the given controls.
+    fn replay_frame(&mut self, held: impl IntoIterator<Item = C>, now: Now) {
+        let held = held.into_iter().collect::<AHashSet<_>>();
+        let mut controls = self.states.keys().cloned().collect::<AHashSet<_>>();
+        controls.extend(held.iter().cloned());
+        for control in controls {
+            let down = held.contains(&control);
+            if down {
+                self.held.insert(control.clone());
+            } else {
+                self.held.remove(&control);
+            }
+            self.advance(control, down, now);
+        }
+        self.finish_tick(now);
+    }
+
+    /// Count how many inputs each control has held down, given one control for each held input, so controls
+    /// that retrigger know when another of their inputs goes down. Call this before `tick`.
+    pub fn count_inputs<'a>(&mut self, held: impl IntoIterator<Item = &'a C>)
+    where
+        C: 'a,
+    {
+        if self.retriggers.is_empty() {
+            return;
+        }
+        let mut counts = AHashMap::new();
+        for ctrl in held {
+            if self.retriggers.contains(ctrl) {
+                *counts.entry(ctrl.clone()).or_insert(0) += 1;
+            }
+        }
+        for (ctrl, &count) in counts.iter() {
+            if self
+                .input_counts
+                .get(ctrl)
+                .is_some_and(|&last| count > last)
+            {
+                self.retriggered.insert(ctrl.clone());
+            }
+        }
+        self.input_counts = counts;
+    }
+
+    pub fn retriggers(&self, ctrl: &C) -> bool {
+        self.retriggers.contains(ctrl)
+    }
+
+    pub fn set_retrigger_policy(&mut self, ctrl: C, policy: RetriggerPolicy) {
+        match policy {
+            RetriggerPolicy::Ignore => {
+                self.retriggers.remove(&ctrl);
+                self.input_counts.remove(&ctrl);
+            }
+            RetriggerPolicy::Click => {
+                self.retriggers.insert(ctrl);
+            }
+        }
+    }
+
+    pub fn retrigger_policy(&self, ctrl: &C) -> RetriggerPolicy {
+        if self.retriggers.contains(ctrl) {
+            RetriggerPolicy::Click
+        } else {
+            RetriggerPolicy::Ignore
+        }
+    }
+
+    /// Move game time forward to this tick, and get the time on both clocks.
+    fn now(&mut self) -> Now {
+        let real = Instant::now();
+        if let Some(last_tick) = self.last_tick {
+            self.game_clock += (real - last_tick).mul_f32(self.time_scale);
+        }
+        self.last_tick = Some(real);
+        Now {
+            real,
+            game: self.game_clock,
+        }
+    }
+
+    /// Move one control forward a frame, given if it's down after all the processing.
+    fn advance(&mut self, control: C, down: bool, now: Now) {
+        if down {
+            let turbo = self.turbo(&control);
+            let auto_repeat = self.auto_repeat(&control);
+            let rate_limit = self.rate_limit(&control);
+            let tap_window = self.tap_window(&control) as FrameNumber;
+            let state = self.states.entry(control.clone()).or_default();
+            state.time += 1;
+            if let Some(dt) = self.delta_time {
+                *self.press_seconds.entry(control.clone()).or_default() += dt;
+            }
+            if state.time == 1 {
+                let frame = self.frame;
+                self.taps
+                    .entry(control.clone())
+                    .and_modify(|(count, last)| {
+                        *count = if frame - *last <= tap_window {
+                            *count + 1
+                        } else {
+                            1
+                        };
+                        *last = frame;
+                    })
+                    .or_insert((1, frame));
+                let stamp = self.press_stamps.get(&control).copied();
+                self.pressed_at
+                    .insert(control.clone(), stamp.unwrap_or(now.real));
+            }
+            if let (1, Some(first @ None)) = (state.time, &mut self.waiting) {
+                *first = Some(control.clone());
+            }
+            state.clicked = state.time == 1
+                || turbo.is_some_and(|period| (state.time - 1).is_multiple_of(period))
+                || auto_repeat.is_some_and(|repeat| repeat.repeats_on(state.time))
+                || self.retriggered.contains(&control);
+            if let (true, Some(limit)) = (state.clicked, rate_limit) {
+                let clicks = self.rate_limit_clicks.entry(control.clone()).or_default();
+                state.clicked = allow_click(clicks, limit, now.on(self.timer_clocks.rate_limit));
+            }
+            if state.clicked {
+                self.last_frames.entry(control.clone()).or_default().0 = Some(self.frame);
+                self.events.push(ControlEvent::Pressed(control));
+            }
+        } else if self
+            .states
+            .insert(control.clone(), ControlState::default())
+            .unwrap_or_default()
+            .time
+            >= 1
+        {
+            self.pressed_at.remove(&control);
+            self.press_seconds.remove(&control);
+            self.last_frames.entry(control.clone()).or_default().1 = Some(self.frame);
+            self.events.push(ControlEvent::Released(control));
+        }
+    }
+
+    /// Bring everything that depends on the controls up to date, once they've all moved forward.
+    fn finish_tick(&mut self, now: Now) {
+        self.press_stamps.clear();
+        self.retriggered.clear();
+        self.update_drags();
+        for (control, (hold, state)) in self.hold_confirms.iter_mut() {
+            let time = self.states.get(control).map_or(0, |state| state.time);
+            state.update(*hold, time, now.on(self.timer_clocks.hold_confirm));
+        }
+        for (control, state) in self.stages.iter_mut() {
+            state.update(self.states.get(control).map_or(0, |state| state.time));
+        }
+        for (control, state) in self.states.iter() {
+            let threshold = self
+                .hold_thresholds
+                .get(control)
+                .copied()
+                .unwrap_or(self.timing.hold_threshold);
+            match threshold {
+                Some(threshold) => self
+                    .tap_holds
+                    .entry(control.clone())
+                    .or_default()
+                    .update(threshold, state.time),
+                None => {
+                    self.tap_holds.remove(control);
+                }
+            }
+        }
+        for window in self.follow_ups.values_mut() {
+            for event in self.events.iter() {
+                if let ControlEvent::Pressed(control) = event {
+                    let extra = assist_frames(&self.assists, self.assist_mode, control);
+                    if window.covers(self.frame, extra) {
+                        let offset = u32::try_from(self.frame.saturating_sub(window.opened))
+                            .unwrap_or(u32::MAX);
+                        window.pressed.entry(control.clone()).or_insert(offset);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Move the drags forward a frame, once the controls are up to date.
+    fn update_drags(&mut self) {
+        for (control, &threshold) in self.drag_thresholds.iter() {
+            let down = self
+                .states
+                .get(control)
+                .is_some_and(|state| state.time >= 1);
+            self.drags.entry(control.clone()).or_default().update(
+                down,
+                self.pointer.position(),
+                threshold,
+            );
+        }
+    }
+
+    /// Let go of everything, including switched-on toggles. The controls are released at the next tick.
+    pub fn release_all(&mut self) {
+        self.held.clear();
+        self.taps.clear();
+        self.toggled_on.clear();
+        self.debounce_states.clear();
+        self.confirmation_states.clear();
+        self.min_hold_states.clear();
+        self.swallowed.clear();
+        self.drags.clear();
+        for (_, state) in self.hold_confirms.values_mut() {
+            *state = HoldConfirmState::default();
+        }
+        for state in self.stages.values_mut() {
+            state.stage = None;
+        }
+        for long_press in self.long_presses.values_mut() {
+            long_press.reset();
+        }
+        for tap_hold in self.tap_holds.values_mut() {
+            tap_hold.reset();
+        }
+        for charge in self.charges.values_mut() {
+            charge.reset();
+        }
+        for chord in self.chords.values_mut() {
+            chord.reset();
+        }
+        if let Some(nav) = &mut self.stick_navigation {
+            nav.reset();
+        }
+        if let Some(gestures) = &mut self.mouse_gestures {
+            gestures.reset();
+        }
+        if let Some(flicks) = &mut self.stick_flicks {
+            flicks.reset();
+        }
+        self.scroll.reset();
+        for sticky in self.sticky.values_mut() {
+            *sticky = Sticky::Idle;
+        }
+        if let Some(scan) = &mut self.scan {
+            scan.reset();
+        }
+        if let Some(dwell) = &mut self.dwell {
+            dwell.reset();
+        }
+        self.playbacks.clear();
+    }
+
+    /// Let go of one control right now, and ignore it until all its inputs have been released.
+    pub fn release_control(&mut self, ctrl: &C) {
+        self.swallowed.insert(ctrl.clone());
+        self.states.remove(ctrl);
+        self.held.remove(ctrl);
+        self.toggled_on.remove(ctrl);
+        self.debounce_states.remove(ctrl);
+        self.confirmation_states.remove(ctrl);
+        self.min_hold_states.remove(ctrl);
+        self.drags.remove(ctrl);
+        self.pressed_at.remove(ctrl);
+        self.press_seconds.remove(ctrl);
+        if let Some((_, state)) = self.hold_confirms.get_mut(ctrl) {
+            *state = HoldConfirmState::default();
+        }
+        if let Some(state) = self.stages.get_mut(ctrl) {
+            state.stage = None;
+        }
+        if let Some(long_press) = self.long_presses.get_mut(ctrl) {
+            long_press.reset();
+        }
+        if let Some(tap_hold) = self.tap_holds.get_mut(ctrl) {
+            tap_hold.reset();
+        }
+        if let Some(charge) = self.charges.get_mut(ctrl) {
+            charge.reset();
+        }
+        if let Some(chord) = self.chords.get_mut(ctrl) {
+            chord.reset();
+        }
+        if let Some(sticky) = self.sticky.get_mut(ctrl) {
+            *sticky = Sticky::Idle;
+        }
+    }
+
+    /// Remember how long each control has been down for, to pick back up from with `unstash`.
+    pub fn stash(&mut self) {
+        self.stashed = Some(self.pressed_times().into_iter().collect());
+    }
+
+    /// Go back to the stashed press times. Controls held the whole time pick up where they were, and
+    /// controls pressed since the stash are let go until they're released.
+    pub fn unstash(&mut self) -> bool {
+        let Some(stashed) = self.stashed.take() else {
+            return false;
+        };
+        for (ctrl, _) in self.pressed_times() {
+            match stashed.get(&ctrl) {
+                Some(&time) => {
+                    self.states.insert(
+                        ctrl,
+                        ControlState {
+                            time,
+                            clicked: false,
+                        },
+                    );
+                }
+                None => self.release_control(&ctrl),
+            }
+        }
+        true
+    }
+
+    pub fn is_stashed(&self) -> bool {
+        self.stashed.is_some()
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_pause_exempt(&mut self, ctrl: C, exempt: bool) {
+        if exempt {
+            self.pause_exempt.insert(ctrl);
+        } else {
+            self.pause_exempt.remove(&ctrl);
+        }
+    }
+
+    pub fn is_pause_exempt(&self, ctrl: &C) -> bool {
+        self.pause_exempt.contains(ctrl)
+    }
+
+    pub fn text(&self) -> &TextState {
+        &self.text
+    }
+
+    pub fn text_mut(&mut self) -> &mut TextState {
+        &mut self.text
+    }
+
+    pub fn set_text_passthrough(&mut self, ctrl: C, passthrough: bool) {
+        if passthrough {
+            self.text_passthrough.insert(ctrl);
+        } else {
+            self.text_passthrough.remove(&ctrl);
+        }
+    }
+
+    pub fn is_text_passthrough(&self, ctrl: &C) -> bool {
+        self.text_passthrough.contains(ctrl)
+    }
+
+    pub fn focus_lost(&mut self) {
+        self.release_all();
+    }
+
+    pub fn focus_gained(&mut self) {
+        self.suppress_next = self.refocus_suppression;
+    }
+
+    /// If the next tick is going to ignore every input.
+    pub fn suppressing(&self) -> bool {
+        self.suppress_next
+    }
+
+    pub fn set_refocus_suppression(&mut self, suppress: bool) {
+        self.refocus_suppression = suppress;
+    }
+
+    pub fn refocus_suppression(&self) -> bool {
+        self.refocus_suppression
+    }
+
+    /// Let go of everything right now.
+    pub fn clear(&mut self) {
+        self.release_all();
+        self.states.clear();
+        self.pressed_at.clear();
+        self.press_seconds.clear();
+    }
+
+    pub fn frame(&self) -> FrameNumber {
+        self.frame
+    }
+
+    pub fn wait_for_any(&mut self) {
+        self.waiting = Some(None);
+    }
+
+    pub fn stop_waiting(&mut self) {
+        self.waiting = None;
+    }
+
+    pub fn any_pressed(&self) -> Option<&C> {
+        self.waiting.as_ref().and_then(Option::as_ref)
+    }
+
+    pub fn request_rumble(&mut self, device: Option<u32>, rumble: Rumble) {
+        self.rumble.push(RumbleRequest { device, rumble });
+    }
+
+    pub fn drain_rumble(&mut self) -> impl Iterator<Item = RumbleRequest> + '_ {
+        self.rumble.drain(..)
+    }
+
+    pub fn request_trigger_effect(
+        &mut self,
+        device: Option<u32>,
+        trigger: TriggerSide,
+        effect: TriggerEffect,
+    ) {
+        self.trigger_effects.push(TriggerEffectRequest {
+            device,
+            trigger,
+            effect,
+        });
+    }
+
+    pub fn drain_trigger_effects(&mut self) -> impl Iterator<Item = TriggerEffectRequest> + '_ {
+        self.trigger_effects.drain(..)
+    }
+
+    pub fn push_event(&mut self, event: ControlEvent<C>) {
+        self.events.push(event);
+    }
+
+    pub fn events(&self) -> &[ControlEvent<C>] {
+        &self.events
+    }
+
+    pub fn add_listener(
+        &mut self,
+        ctrl: C,
+        listener: impl Fn(&ControlEvent<C>) + Send + Sync + 'static,
+    ) -> ListenerId {
+        let id = ListenerId(self.next_listener);
+        self.next_listener += 1;
+        self.listeners.push(Listener::new(id, ctrl, listener));
+        id
+    }
+
+    pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+        let len = self.listeners.len();
+        self.listeners.retain(|listener| listener.id != id);
+        self.listeners.len() != len
+    }
+
+    pub fn clear_listeners(&mut self) {
+        self.listeners.clear();
+    }
+
+    /// Call the listeners for the last tick's events, including events pushed after it.
+    pub fn notify_listeners(&self) {
+        for event in self.events.iter() {
+            let (ControlEvent::Pressed(ctrl)
+            | ControlEvent::Released(ctrl)
+            | ControlEvent::Repeated(ctrl)) = event;
+            for listener in self.listeners.iter() {
+                if listener.control == *ctrl {
+                    listener.call(event);
+                }
+            }
+        }
+    }
+
+    /// Sum up what changed during the last tick, including events pushed after it.
+    pub fn summary(&self) -> UpdateSummary {
+        let mut summary = UpdateSummary {
+            pointer_moved: self.pointer.moved(),
+            scrolled: self.scroll.delta() != (0.0, 0.0),
+            typed: self.text.changed(),
+            ..UpdateSummary::default()
+        };
+        for event in self.events.iter() {
+            match event {
+                ControlEvent::Pressed(_) => summary.pressed += 1,
+                ControlEvent::Released(_) => summary.released += 1,
+                ControlEvent::Repeated(_) => summary.repeated += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn set_toggle(&mut self, ctrl: C, toggle: bool) {
+        if toggle {
+            // Don't drop a control that's being held when it becomes a toggle
+            if self.held.contains(&ctrl) {
+                self.toggled_on.insert(ctrl.clone());
+            }
+            self.toggles.insert(ctrl);
+        } else {
+            self.toggled_on.remove(&ctrl);
+            self.toggles.remove(&ctrl);
+        }
+    }
+
+    pub fn is_toggle(&self, ctrl: &C) -> bool {
+        self.toggles.contains(ctrl)
+    }
+
+    pub fn set_lockout(&mut self, ctrl: C, frames: Option<u32>) {
+        match frames {
+            Some(frames) => {
+                self.lockouts.insert(ctrl, frames);
+            }
+            None => {
+                self.locked_until.remove(&ctrl);
+                self.lockouts.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn lockout(&self, ctrl: &C) -> Option<u32> {
+        self.lockouts.get(ctrl).copied()
+    }
+
+    pub fn set_click_only(&mut self, ctrl: C, click_only: bool) {
+        if click_only {
+            self.click_only.insert(ctrl);
+        } else {
+            self.click_only.remove(&ctrl);
+        }
+    }
+
+    pub fn is_click_only(&self, ctrl: &C) -> bool {
+        self.click_only.contains(ctrl)
+    }
+
+    pub fn set_timing(&mut self, timing: TimingSettings) {
+        self.timing = timing;
+    }
+
+    pub fn timing(&self) -> &TimingSettings {
+        &self.timing
+    }
+
+    pub fn set_debounce(&mut self, ctrl: C, debounce: Option<Debounce>) {
+        self.debounce.insert(ctrl, debounce);
+    }
+
+    pub fn debounce(&self, ctrl: &C) -> Option<Debounce> {
+        self.debounce
+            .get(ctrl)
+            .copied()
+            .unwrap_or(self.timing.debounce)
+    }
+
+    pub fn clear_debounce(&mut self, ctrl: &C) {
+        self.debounce.remove(ctrl);
+    }
+
+    pub fn set_confirmation(&mut self, ctrl: C, confirmation: Option<Confirmation>) {
+        self.confirmation.insert(ctrl, confirmation);
+    }
+
+    pub fn confirmation(&self, ctrl: &C) -> Option<Confirmation> {
+        self.confirmation
+            .get(ctrl)
+            .copied()
+            .unwrap_or(self.timing.confirmation)
+    }
+
+    pub fn clear_confirmation(&mut self, ctrl: &C) {
+        self.confirmation.remove(ctrl);
+    }
+
+    pub fn set_min_hold(&mut self, ctrl: C, min_hold: Option<MinHold>) {
+        self.min_hold.insert(ctrl, min_hold);
+    }
+
+    pub fn min_hold(&self, ctrl: &C) -> Option<MinHold> {
+        self.min_hold
+            .get(ctrl)
+            .copied()
+            .unwrap_or(self.timing.min_hold)
+    }
+
+    pub fn clear_min_hold(&mut self, ctrl: &C) {
+        self.min_hold.remove(ctrl);
+    }
+
+    pub fn set_hold_confirm(&mut self, ctrl: C, hold: Option<HoldConfirm>) {
+        match hold {
+            Some(hold) => {
+                self.hold_confirms
+                    .insert(ctrl, (hold, HoldConfirmState::default()));
+            }
+            None => {
+                self.hold_confirms.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn hold_confirm(&self, ctrl: &C) -> Option<HoldConfirm> {
+        self.hold_confirms.get(ctrl).map(|(hold, _)| *hold)
+    }
+
+    pub fn hold_progress(&self, ctrl: &C) -> f32 {
+        self.hold_confirms
+            .get(ctrl)
+            .map_or(0.0, |(_, state)| state.progress)
+    }
+
+    pub fn hold_confirmed(&self, ctrl: &C) -> bool {
+        self.hold_confirms
+            .get(ctrl)
+            .is_some_and(|(_, state)| state.confirmed)
+    }
+
+    pub fn set_stick_navigation(&mut self, navigation: Option<StickNavigation<C>>) {
+        self.stick_navigation = navigation.map(StickNavigationState::new);
+    }
+
+    pub fn stick_navigation(&self) -> Option<&StickNavigation<C>> {
+        self.stick_navigation.as_ref().map(|nav| &nav.settings)
+    }
+
+    pub fn set_mouse_gestures(&mut self, gestures: Option<MouseGestures<C>>) {
+        self.mouse_gestures = gestures.map(MouseGestureState::new);
+    }
+
+    pub fn mouse_gestures(&self) -> Option<&MouseGestures<C>> {
+        self.mouse_gestures
+            .as_ref()
+            .map(|gestures| &gestures.settings)
+    }
+
+    pub fn set_stick_flicks(&mut self, flicks: Option<StickFlicks<C>>) {
+        self.stick_flicks = flicks.map(StickFlickState::new);
+    }
+
+    pub fn stick_flicks(&self) -> Option<&StickFlicks<C>> {
+        self.stick_flicks.as_ref().map(|flicks| &flicks.settings)
+    }
+
+    pub fn set_flick_stick(&mut self, stick: (f32, f32)) {
+        if let Some(flicks) = &mut self.stick_flicks {
+            flicks.set_stick(stick);
+        }
+    }
+
+    pub fn set_navigation_stick(&mut self, stick: (f32, f32)) {
+        if let Some(nav) = &mut self.stick_navigation {
+            nav.set_stick(stick);
+        }
+    }
+
+    pub fn set_long_press(&mut self, ctrl: C, long_press: Option<LongPress<C>>) {
+        match long_press {
+            Some(long_press) => {
+                self.long_presses
+                    .insert(ctrl, LongPressState::new(long_press));
+            }
+            None => {
+                self.long_presses.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn long_press(&self, ctrl: &C) -> Option<&LongPress<C>> {
+        self.long_presses.get(ctrl).map(|state| &state.settings)
+    }
+
+    pub fn set_hold_threshold(&mut self, ctrl: C, frames: Option<u32>) {
+        self.hold_thresholds.insert(ctrl, frames);
+    }
+
+    pub fn hold_threshold(&self, ctrl: &C) -> Option<u32> {
+        self.hold_thresholds
+            .get(ctrl)
+            .copied()
+            .unwrap_or(self.timing.hold_threshold)
+    }
+
+    pub fn clear_hold_threshold(&mut self, ctrl: &C) {
+        self.hold_thresholds.remove(ctrl);
+    }
+
+    pub fn tap_or_hold(&self, ctrl: &C) -> Option<TapOrHold> {
+        self.tap_holds.get(ctrl).and_then(|state| state.outcome)
+    }
+
+    pub fn set_charge(&mut self, ctrl: C, charge: Option<Charge<C>>) {
+        match charge {
+            Some(charge) => {
+                self.charges.insert(ctrl, ChargeState::new(charge));
+            }
+            None => {
+                self.charges.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn charge(&self, ctrl: &C) -> Option<&Charge<C>> {
+        self.charges.get(ctrl).map(|state| &state.settings)
+    }
+
+    pub fn set_chord(&mut self, ctrl: C, chord: Option<Chord<C>>) {
+        match chord {
+            Some(chord) => {
+                self.chords.insert(ctrl, ChordState::new(chord));
+            }
+            None => {
+                self.chords.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn chord(&self, ctrl: &C) -> Option<&Chord<C>> {
+        self.chords.get(ctrl).map(|state| &state.settings)
+    }
+
+    pub fn set_stages(&mut self, ctrl: C, thresholds: Option<Vec<u32>>) {
+        match thresholds {
+            Some(mut thresholds) => {
+                thresholds.sort_unstable();
+                self.stages.insert(
+                    ctrl,
+                    StageState {
+                        thresholds,
+                        ..StageState::default()
+                    },
+                );
+            }
+            None => {
+                self.stages.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn stages(&self, ctrl: &C) -> Option<&[u32]> {
+        self.stages
+            .get(ctrl)
+            .map(|state| state.thresholds.as_slice())
+    }
+
+    pub fn stage(&self, ctrl: &C) -> usize {
+        self.stages
+            .get(ctrl)
+            .and_then(|state| state.stage)
+            .unwrap_or(0)
+    }
+
+    pub fn stage_reached(&self, ctrl: &C) -> Option<usize> {
+        self.stages.get(ctrl).and_then(|state| state.reached)
+    }
+
+    pub fn released_at_stage(&self, ctrl: &C) -> Option<usize> {
+        self.stages.get(ctrl).and_then(|state| state.released)
+    }
+
+    pub fn set_assist_mode(&mut self, on: bool) {
+        self.assist_mode = on;
+    }
+
+    pub fn assist_mode(&self) -> bool {
+        self.assist_mode
+    }
+
+    pub fn set_timing_assist(&mut self, ctrl: C, frames: Option<u32>) {
+        match frames {
+            Some(frames) => {
+                self.assists.insert(ctrl, frames);
+            }
+            None => {
+                self.assists.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn timing_assist(&self, ctrl: &C) -> Option<u32> {
+        self.assists.get(ctrl).copied()
+    }
+
+    pub fn open_follow_up(&mut self, name: &str, frames: u32) {
+        self.follow_ups.insert(
+            name.to_owned(),
+            FollowUpWindow {
+                opened: self.frame,
+                frames,
+                pressed: AHashMap::new(),
+            },
+        );
+    }
+
+    pub fn close_follow_up(&mut self, name: &str) -> bool {
+        self.follow_ups.remove(name).is_some()
+    }
+
+    pub fn follow_up_open(&self, name: &str) -> bool {
+        self.follow_ups
+            .get(name)
+            .is_some_and(|window| window.covers(self.frame.saturating_add(1), 0))
+    }
+
+    pub fn follow_up_press(&self, name: &str, ctrl: &C) -> Option<u32> {
+        self.follow_ups
+            .get(name)
+            .and_then(|window| window.pressed.get(ctrl).copied())
+    }
+
+    pub fn set_rate_limit(&mut self, ctrl: C, rate_limit: Option<RateLimit>) {
+        self.rate_limit.insert(ctrl, rate_limit);
+    }
+
+    pub fn rate_limit(&self, ctrl: &C) -> Option<RateLimit> {
+        self.rate_limit
+            .get(ctrl)
+            .copied()
+            .unwrap_or(self.timing.rate_limit)
+    }
+
+    pub fn clear_rate_limit(&mut self, ctrl: &C) {
+        self.rate_limit.remove(ctrl);
+    }
+
+    pub fn set_sticky(&mut self, ctrl: C, sticky: bool) {
+        if sticky {
+            self.sticky.entry(ctrl).or_default();
+        } else {
+            self.sticky.remove(&ctrl);
+        }
+    }
+
+    pub fn is_sticky(&self, ctrl: &C) -> bool {
+        self.sticky.contains_key(ctrl)
+    }
+
+    pub fn set_scanning(&mut self, scanning: Option<Scanning<C>>) {
+        self.scan = scanning.map(ScanState::new);
+    }
+
+    pub fn scanning(&self) -> Option<&Scanning<C>> {
+        self.scan.as_ref().map(|scan| &scan.settings)
+    }
+
+    pub fn scan_focus(&self) -> Option<&C> {
+        self.scan.as_ref().and_then(ScanState::focus)
+    }
+
+    pub fn set_dwell(&mut self, dwell: Option<Dwell<C>>) {
+        self.dwell = dwell.map(DwellState::new);
+    }
+
+    pub fn dwell(&self) -> Option<&Dwell<C>> {
+        self.dwell.as_ref().map(|dwell| &dwell.settings)
+    }
+
+    pub fn pointer(&self) -> &PointerState {
+        &self.pointer
+    }
+
+    pub fn pointer_mut(&mut self) -> &mut PointerState {
+        &mut self.pointer
+    }
+
+    pub fn scroll(&self) -> &ScrollState<C> {
+        &self.scroll
+    }
+
+    pub fn scroll_mut(&mut self) -> &mut ScrollState<C> {
+        &mut self.scroll
+    }
+
+    pub fn set_drag_threshold(&mut self, ctrl: C, threshold: Option<f32>) {
+        match threshold {
+            Some(threshold) => {
+                self.drag_thresholds.insert(ctrl, threshold);
+            }
+            None => {
+                self.drag_thresholds.remove(&ctrl);
+                self.drags.remove(&ctrl);
+            }
+        }
+    }
+
+    pub fn drag_threshold(&self, ctrl: &C) -> Option<f32> {
+        self.drag_thresholds.get(ctrl).copied()
+    }
+
+    pub fn drag_state(&self, ctrl: &C) -> DragState {
+        self.drags.get(ctrl).copied().unwrap_or_default()
+    }
+
+    pub fn dwell_progress(&self) -> Option<(&C, f32)> {
+        self.dwell.as_ref().and_then(DwellState::progress)
+    }
+
+    pub fn set_macro(&mut self, ctrl: C, steps: Option<Macro<C>>) {
+        match steps {
+            Some(steps) => self.macros.insert(ctrl, steps),
+            None => self.macros.remove(&ctrl),
+        };
+    }
+
+    pub fn macro_for(&self, ctrl: &C) -> Option<&Macro<C>> {
+        self.macros.get(ctrl)
+    }
+
+    pub fn play_macro(&mut self, steps: Macro<C>) {
+        self.playbacks.push(Playback::new(steps));
+    }
+
+    pub fn stop_macros(&mut self) {
+        self.playbacks.clear();
+    }
+
+    /// Set how many frames apart a held control clicks, or `None` to only click when it's pressed.
+    pub fn set_turbo(&mut self, ctrl: C, period: Option<u32>) {
+        self.turbo.insert(ctrl, period);
+    }
+
+    pub fn turbo(&self, ctrl: &C) -> Option<u32> {
+        self.turbo
+            .get(ctrl)
+            .copied()
+            .unwrap_or(self.timing.turbo)
+            .map(|period| period.max(1))
+    }
+
+    pub fn clear_turbo(&mut self, ctrl: &C) {
+        self.turbo.remove(ctrl);
+    }
+
+    pub fn set_auto_repeat(&mut self, ctrl: C, auto_repeat: Option<AutoRepeat>) {
+        self.auto_repeat.insert(ctrl, auto_repeat);
+    }
+
+    pub fn auto_repeat(&self, ctrl: &C) -> Option<AutoRepeat> {
+        self.auto_repeat
+            .get(ctrl)
+            .copied()
+            .unwrap_or(self.timing.auto_repeat)
+    }
+
+    pub fn clear_auto_repeat(&mut self, ctrl: &C) {
+        self.auto_repeat.remove(ctrl);
+    }
+
+    pub fn last_clicked_frame(&self, ctrl: &C) -> Option<FrameNumber> {
+        self.last_frames.get(ctrl).and_then(|(clicked, _)| *clicked)
+    }
+
+    pub fn last_released_frame(&self, ctrl: &C) -> Option<FrameNumber> {
+        self.last_frames
+            .get(ctrl)
+            .and_then(|(_, released)| *released)
+    }
+
+    /// Note when a control's inputs were really pressed, for the next tick to use if the control goes down.
+    /// The earliest time wins.
+    pub fn stamp_press(&mut self, ctrl: C, at: Instant) {
+        self.press_stamps
+            .entry(ctrl)
+            .and_modify(|stamp| *stamp = (*stamp).min(at))
+            .or_insert(at);
+    }
+
+    pub fn pressed_at(&self, ctrl: &C) -> Option<Instant> {
+        self.pressed_at.get(ctrl).copied()
+    }
+
+    pub fn set_tap_window(&mut self, ctrl: C, frames: Option<u32>) {
+        match frames {
+            Some(frames) => self.tap_windows.insert(ctrl, frames),
+            None => self.tap_windows.remove(&ctrl),
+        };
+    }
+
+    pub fn tap_window(&self, ctrl: &C) -> u32 {
+        self.tap_windows
+            .get(ctrl)
+            .copied()
+            .unwrap_or(self.timing.tap_window)
+    }
+
+    pub fn tap_count(&self, ctrl: &C) -> u32 {
+        match self.taps.get(ctrl) {
+            Some(&(count, last)) if self.frame - last <= self.tap_window(ctrl) as FrameNumber => {
+                count
+            }
+            _ => 0,
+        }
+    }
+
+    pub fn idle_frames(&self) -> u64 {
+        self.idle_frames
+    }
+
+    pub fn set_attract_mode(&mut self, attract: Option<AttractMode<C>>) {
+        if self.in_attract_mode() {
+            for ctrl in self.held.clone() {
+                self.release_control(&ctrl);
+            }
+        }
+        self.attract = attract.map(AttractState::new);
+    }
+
+    pub fn attract_mode(&self) -> Option<&AttractMode<C>> {
+        self.attract.as_ref().map(|attract| &attract.settings)
+    }
+
+    pub fn start_attract_mode(&mut self) -> bool {
+        match &mut self.attract {
+            Some(attract) => {
+                attract.start();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn in_attract_mode(&self) -> bool {
+        self.attract.as_ref().is_some_and(AttractState::playing)
+    }
+
+    pub fn set_time_scale(&mut self, scale: f32) {
+        // Infinity would panic when it's applied to the time since the last tick.
+        self.time_scale = if scale.is_nan() {
+            0.0
+        } else {
+            scale.clamp(0.0, MAX_TIME_SCALE)
+        };
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_timer_clocks(&mut self, clocks: TimerClocks) {
+        self.timer_clocks = clocks;
+    }
+
+    pub fn timer_clocks(&self) -> TimerClocks {
+        self.timer_clocks
+    }
+
+    pub fn set_delta_time(&mut self, dt: Option<f32>) {
+        self.delta_time = dt;
+    }
+
+    pub fn delta_time(&self) -> Option<f32> {
+        self.delta_time
+    }
+
+    pub fn press_seconds(&self, ctrl: &C) -> f32 {
+        self.press_seconds.get(ctrl).copied().unwrap_or(0.0)
+    }
+
+    pub fn press_time(&self, ctrl: &C) -> u32 {
+        self.states.get(ctrl).map_or(0, |state| state.time)
+    }
+
+    pub fn clicked(&self, ctrl: &C) -> bool {
+        self.states.get(ctrl).is_some_and(|state| state.clicked)
+    }
+
+    /// How many frames the control has been up for since it was released, counting the frame it was
+    /// released on. This is 0 if it's down, or has never been released.
+    pub fn release_time(&self, ctrl: &C) -> u32 {
+        if self.press_time(ctrl) >= 1 {
+            return 0;
+        }
+        self.last_released_frame(ctrl).map_or(0, |released| {
+            u32::try_from(self.frame.saturating_sub(released) + 1).unwrap_or(u32::MAX)
+        })
+    }
+
+    pub fn all_pressed(&self) -> impl Iterator<Item = &C> + '_ {
+        self.states
+            .iter()
+            .filter_map(|(ctrl, state)| if state.time >= 1 { Some(ctrl) } else { None })
+    }
+
+    /// Every control that's down, and how many frames it's been down for.
+    pub fn pressed_times(&self) -> Vec<(C, u32)> {
+        self.states
+            .iter()
+            .filter(|(_, state)| state.time >= 1)
+            .map(|(ctrl, state)| (ctrl.clone(), state.time))
+            .collect()
+    }
+
+    pub fn control_settings(&self) -> ControlSettings<C> {
+        fn pairs<C: Clone, T: Copy>(map: &AHashMap<C, T>) -> Vec<(C, T)> {
+            map.iter()
+                .map(|(ctrl, &value)| (ctrl.clone(), value))
+                .collect()
+        }
+        ControlSettings {
+            debounce: pairs(&self.debounce),
+            confirmation: pairs(&self.confirmation),
+            min_hold: pairs(&self.min_hold),
+            turbo: pairs(&self.turbo),
+            auto_repeat: pairs(&self.auto_repeat),
+            rate_limit: pairs(&self.rate_limit),
+            tap_window: pairs(&self.tap_windows),
+            hold_threshold: pairs(&self.hold_thresholds),
+            toggles: self.toggles.iter().cloned().collect(),
+            sticky: self.sticky.keys().cloned().collect(),
+            click_only: self.click_only.iter().cloned().collect(),
+            lockout: pairs(&self.lockouts),
+            pause_exempt: self.pause_exempt.iter().cloned().collect(),
+            long_press: self
+                .long_presses
+                .iter()
+                .map(|(ctrl, state)| (ctrl.clone(), state.settings.clone()))
+                .collect(),
+            charge: self
+                .charges
+                .iter()
+                .map(|(ctrl, state)| (ctrl.clone(), state.settings.clone()))
+                .collect(),
+            chord: self
+                .chords
+                .iter()
+                .map(|(ctrl, state)| (ctrl.clone(), state.settings.clone()))
+                .collect(),
+        }
+    }
+
+    /// Replace every control's own settings with saved ones.
+    pub fn restore_control_settings(&mut self, settings: ControlSettings<C>) {
+        self.debounce = settings.debounce.into_iter().collect();
+        self.confirmation = settings.confirmation.into_iter().collect();
+        self.min_hold = settings.min_hold.into_iter().collect();
+        self.turbo = settings.turbo.into_iter().collect();
+        self.auto_repeat = settings.auto_repeat.into_iter().collect();
+        self.rate_limit = settings.rate_limit.into_iter().collect();
+        self.tap_windows = settings.tap_window.into_iter().collect();
+        self.hold_thresholds = settings.hold_threshold.into_iter().collect();
+        self.toggles = settings.toggles.into_iter().collect();
+        self.sticky = settings
+            .sticky
+            .into_iter()
+            .map(|ctrl| (ctrl, Sticky::default()))
+            .collect();
+        self.click_only = settings.click_only.into_iter().collect();
+        self.lockouts = settings.lockout.into_iter().collect();
+        self.pause_exempt = settings.pause_exempt.into_iter().collect();
+        self.long_presses = settings
+            .long_press
+            .into_iter()
+            .map(|(ctrl, long_press)| (ctrl, LongPressState::new(long_press)))
+            .collect();
+        self.charges = settings
+            .charge
+            .into_iter()
+            .map(|(ctrl, charge)| (ctrl, ChargeState::new(charge)))
+            .collect();
+        self.chords = settings
+            .chord
+            .into_iter()
+            .map(|(ctrl, chord)| (ctrl, ChordState::new(chord)))
+            .collect();
+    }
+
+    /// The toggle controls that are switched on, and if each one's inputs are held.
+    pub fn toggled_on(&self) -> Vec<(C, bool)> {
+        self.toggled_on
+            .iter()
+            .map(|ctrl| (ctrl.clone(), self.held.contains(ctrl)))
+            .collect()
+    }
+
+    /// Start over from a saved frame, with the given controls held down.
+    pub fn restore(
+        &mut self,
+        frame: FrameNumber,
+        pressed: Vec<(C, u32)>,
+        toggled_on: Vec<(C, bool)>,
+    ) {
+        self.clear();
+        // Everything stamped with a frame number might be from after the frame being restored to.
+        self.last_frames.clear();
+        self.locked_until.clear();
+        self.follow_ups.clear();
+        self.frame = frame;
+        for (ctrl, time) in pressed {
+            self.held.insert(ctrl.clone());
+            self.states.insert(
+                ctrl,
+                ControlState {
+                    time,
+                    clicked: false,
+                },
+            );
+        }
+        for (ctrl, held) in toggled_on {
+            // A toggle stays down after it's let go, so being down doesn't mean its inputs are held.
+            if !held {
+                self.held.remove(&ctrl);
+            }
+            self.toggled_on.insert(ctrl);
+        }
+    }
+
+    /// The value of an axis control, with its virtual axis reading the controls here.
+    pub fn axis_value<I: Hash + Eq + Clone>(&self, axes: &Axes<I, C>, ctrl: &C) -> f32 {
+        axes.value(ctrl, |ctrl| self.press_time(ctrl))
+    }
+
+    /// The value of a 2D control, with its virtual axes reading the controls here.
+    pub fn axis_vector<I: Hash + Eq + Clone>(&self, axes: &Axes<I, C>, ctrl: &C) -> (f32, f32) {
+        axes.vector(ctrl, |ctrl| self.press_time(ctrl))
+    }
+
+    /// A snapshot of the controls here and the axis controls in `axes`.
+    pub fn snapshot<I: Hash + Eq + Clone>(&self, axes: &Axes<I, C>) -> InputSnapshot<C> {
+        let release_times = self
+            .last_frames
+            .keys()
+            .map(|ctrl| (ctrl.clone(), self.release_time(ctrl)))
+            .filter(|(_, time)| *time >= 1)
+            .collect();
+        InputSnapshot::new(self.states.clone())
+            .with_values(axes.values(|ctrl| self.press_time(ctrl)))
+            .with_release_times(release_times)
+            .with_vectors(axes.vectors(|ctrl| self.press_time(ctrl)))
+    }
+}
+
+impl<C> Default for ControlStates<C> {
+    fn default() -> Self {
+        Self {
+            states: AHashMap::new(),
+            held: AHashSet::new(),
+            toggles: AHashSet::new(),
+            toggled_on: AHashSet::new(),
+            timing: TimingSettings::default(),
+            debounce: AHashMap::new(),
+            debounce_states: AHashMap::new(),
+            confirmation: AHashMap::new(),
+            confirmation_states: AHashMap::new(),
+            min_hold: AHashMap::new(),
+            min_hold_states: AHashMap::new(),
+            hold_confirms: AHashMap::new(),
+            stick_navigation: None,
+            mouse_gestures: None,
+            stick_flicks: None,
+            long_presses: AHashMap::new(),
+            hold_thresholds: AHashMap::new(),
+            tap_holds: AHashMap::new(),
+            charges: AHashMap::new(),
+            chords: AHashMap::new(),
+            assists: AHashMap::new(),
+            assist_mode: false,
+            follow_ups: AHashMap::new(),
+            stages: AHashMap::new(),
+            rate_limit: AHashMap::new(),
+            rate_limit_clicks: AHashMap::new(),
+            sticky: AHashMap::new(),
+            scan: None,
+            dwell: None,
+            pointer: PointerState::default(),
+            scroll: ScrollState::default(),
+            drag_thresholds: AHashMap::new(),
+            drags: AHashMap::new(),
+            macros: AHashMap::new(),
+            playbacks: Vec::new(),
+            turbo: AHashMap::new(),
+            auto_repeat: AHashMap::new(),
+            paused: false,
+            pause_exempt: AHashSet::new(),
+            stashed: None,
+            lockouts: AHashMap::new(),
+            locked_until: AHashMap::new(),
+            click_only: AHashSet::new(),
+            swallowed: AHashSet::new(),
+            text: TextState::default(),
+            text_passthrough: AHashSet::new(),
+            refocus_suppression: false,
+            suppress_next: false,
+            waiting: None,
+            listeners: Vec::new(),
+            next_listener: 0,
+            rumble: Vec::new(),
+            trigger_effects: Vec::new(),
+            retriggers: AHashSet::new(),
+            input_counts: AHashMap::new(),
+            retriggered: AHashSet::new(),
+            tap_windows: AHashMap::new(),
+            taps: AHashMap::new(),
+            idle_frames: 0,
+            attract: None,
+            time_scale: 1.0,
+            timer_clocks: TimerClocks::default(),
+            game_clock: Instant::now(),
+            last_tick: None,
+            delta_time: None,
+            press_seconds: AHashMap::new(),
+            pressed_at: AHashMap::new(),
+            press_stamps: AHashMap::new(),
+            last_frames: AHashMap::new(),
+            events: Vec::new(),
+            frame: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+    use crate::MacroStep;
+
+    /// Tick the states with `held` being the controls that are held, out of `controls`.
+    fn tick(states: &mut ControlStates<char>, controls: &[char], held: &[char]) {
+        states.tick(controls, |ctrl| held.contains(ctrl));
+    }
+
+    /// Tick the states once for each entry of `held`, and return the frames (counting from 1) the control
+    /// clicked on.
+    fn clicks(states: &mut ControlStates<char>, ctrl: char, held: &[bool]) -> Vec<usize> {
+        (1..=held.len())
+            .filter(|&frame| {
+                let down = if held[frame - 1] {
+                    vec![ctrl]
+                } else {
+                    Vec::new()
+                };
+                tick(states, &[ctrl], &down);
+                states.clicked(&ctrl)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn debounce_ignores_presses_right_after_release() {
+        let mut states = ControlStates::default();
+        states.set_debounce('a', Some(Debounce::Frames(2)));
+        let held = [true, false, true, true, true];
+        assert_eq!(clicks(&mut states, 'a', &held), vec![1, 5]);
+
+        // No frames of debounce lets a press right after the release through.
+        let mut states = ControlStates::default();
+        states.set_debounce('a', Some(Debounce::Frames(0)));
+        assert_eq!(clicks(&mut states, 'a', &[true, false, true]), vec![1, 3]);
+    }
+
+    #[test]
+    fn confirmation_needs_a_streak() {
+        let mut states = ControlStates::default();
+        states.set_confirmation(
+            'a',
+            Some(Confirmation {
+                press: 2,
+                release: 2,
+            }),
+        );
+        let held = [true, false, true, true, false, true, false, false];
+        assert_eq!(clicks(&mut states, 'a', &held), vec![4]);
+        assert_eq!(states.press_time(&'a'), 0);
+    }
+
+    #[test]
+    fn min_hold_of_zero_or_one_counts_right_away() {
+        for frames in [0, 1] {
+            let mut states = ControlStates::default();
+            states.set_min_hold('a', Some(MinHold::Frames(frames)));
+            assert_eq!(clicks(&mut states, 'a', &[true]), vec![1]);
+        }
+
+        // A tap shorter than the minimum never counts.
+        let mut states = ControlStates::default();
+        states.set_min_hold('a', Some(MinHold::Frames(3)));
+        let held = [true, true, false, true, true, true];
+        assert_eq!(clicks(&mut states, 'a', &held), vec![6]);
+    }
+
+    #[test]
+    fn lockout_throws_whole_presses_away() {
+        let mut states = ControlStates::default();
+        states.set_lockout('a', Some(2));
+        // Held through the end of the lockout on frame 3, and still thrown away until it's let go.
+        let held = [true, false, true, true, true, false, true];
+        assert_eq!(clicks(&mut states, 'a', &held), vec![1, 7]);
+    }
+
+    #[test]
+    fn turbo_period_of_zero_counts_as_one() {
+        let mut states = ControlStates::default();
+        states.set_turbo('a', Some(0));
+        assert_eq!(states.turbo(&'a'), Some(1));
+        assert_eq!(clicks(&mut states, 'a', &[true; 3]), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn sticky_stays_down_until_another_control_is_used() {
+        let mut states = ControlStates::default();
+        states.set_sticky('s', true);
+        let controls = ['s', 'x'];
+        for (held, s_down) in [
+            (&[][..], false),
+            (&['s'][..], true),
+            (&[][..], true),
+            (&[][..], true),
+            (&['x'][..], true),
+            (&[][..], false),
+        ] {
+            tick(&mut states, &controls, held);
+            assert_eq!(states.press_time(&'s') >= 1, s_down);
+        }
+
+        // Tapping it again while it's stuck lets go.
+        tick(&mut states, &controls, &['s']);
+        tick(&mut states, &controls, &[]);
+        tick(&mut states, &controls, &['s']);
+        tick(&mut states, &controls, &[]);
+        assert_eq!(states.press_time(&'s'), 0);
+    }
+
+    #[test]
+    fn macro_holds_for_its_waits() {
+        let mut states = ControlStates::default();
+        states.play_macro(Macro::new([
+            MacroStep::Press('a'),
+            MacroStep::Wait(2),
+            MacroStep::Release('a'),
+            MacroStep::Press('b'),
+            MacroStep::Release('b'),
+            MacroStep::Wait(0),
+            MacroStep::Press('c'),
+        ]));
+        let mut down = Vec::new();
+        for _ in 0..4 {
+            tick(&mut states, &[], &[]);
+            down.push([
+                states.press_time(&'a'),
+                states.press_time(&'b'),
+                states.press_time(&'c'),
+            ]);
+        }
+        // B is let go on the frame it's pressed, so it never goes down. Once the macro runs out of steps, it
+        // lets go of C too.
+        assert_eq!(down, vec![[1, 0, 0], [2, 0, 0], [0, 0, 0], [0, 0, 0]]);
+    }
+
+    #[test]
+    fn release_while_paused_goes_through() {
+        let mut states = ControlStates::default();
+        tick(&mut states, &['a', 'b'], &['a', 'b']);
+        states.set_paused(true);
+        tick(&mut states, &['a', 'b'], &['b']);
+        assert_eq!(states.press_time(&'a'), 0);
+        assert_eq!(states.release_time(&'a'), 1);
+        // Still held, so still frozen.
+        assert_eq!(states.press_time(&'b'), 1);
+
+        tick(&mut states, &['a', 'b'], &['b']);
+        states.set_paused(false);
+        tick(&mut states, &['a', 'b'], &['b']);
+        assert_eq!(states.press_time(&'a'), 0);
+        assert!(!states.clicked(&'a'));
+        assert_eq!(states.press_time(&'b'), 2);
+    }
+
+    #[test]
+    fn time_scale_stays_finite() {
+        let mut states = ControlStates::default();
+        states.set_min_hold('a', Some(MinHold::Duration(Duration::from_millis(1))));
+
+        states.set_time_scale(f32::INFINITY);
+        assert_eq!(states.time_scale(), MAX_TIME_SCALE);
+        tick(&mut states, &['a'], &['a']);
+        thread::sleep(Duration::from_millis(1));
+        tick(&mut states, &['a'], &['a']);
+        assert!(states.clicked(&'a'));
+
+        states.set_time_scale(f32::NAN);
+        assert_eq!(states.time_scale(), 0.0);
+        states.set_time_scale(-2.0);
+        assert_eq!(states.time_scale(), 0.0);
+        tick(&mut states, &['a'], &[]);
+    }
+
+    #[test]
+    fn restore_to_an_earlier_frame_forgets_later_ones() {
+        let mut states = ControlStates::default();
+        states.set_lockout('a', Some(10));
+        states.set_debounce('b', Some(Debounce::Frames(5)));
+        for _ in 0..4 {
+            tick(&mut states, &['a', 'b'], &[]);
+        }
+        tick(&mut states, &['a', 'b'], &['a', 'b']);
+        tick(&mut states, &['a', 'b'], &[]);
+        states.open_follow_up("combo", 10);
+        assert_eq!(states.frame(), 6);
+
+        states.restore(1, Vec::new(), Vec::new());
+        tick(&mut states, &['a', 'b'], &['a', 'b']);
+        assert_eq!(states.frame(), 2);
+        // Neither the lockout until frame 15 nor the release on frame 6 holds these back.
+        assert!(states.clicked(&'a') && states.clicked(&'b'));
+        assert_eq!(states.last_released_frame(&'a'), None);
+        assert!(!states.follow_up_open("combo"));
+    }
+
+    #[test]
+    fn restore_keeps_held_controls_going() {
+        let mut states = ControlStates::default();
+        states.restore(40, vec![('a', 5)], Vec::new());
+        assert!(!states.clicked(&'a'));
+        tick(&mut states, &['a'], &['a']);
+        assert_eq!(states.press_time(&'a'), 6);
+        assert!(!states.clicked(&'a'));
+        tick(&mut states, &['a'], &[]);
+        assert_eq!(states.release_time(&'a'), 1);
+        assert_eq!(states.last_released_frame(&'a'), Some(42));
+    }
+
+    #[test]
+    fn restore_at_the_last_frame_doesnt_overflow() {
+        let mut states = ControlStates::default();
+        states.set_lockout('a', Some(3));
+        states.restore(FrameNumber::MAX - 1, vec![('a', 2)], Vec::new());
+        states.open_follow_up("combo", 5);
+        for _ in 0..3 {
+            tick(&mut states, &['a'], &['a']);
+        }
+        assert_eq!(states.frame(), FrameNumber::MAX);
+        assert_eq!(states.press_time(&'a'), 5);
+        assert!(states.follow_up_open("combo"));
+
+        tick(&mut states, &['a'], &[]);
+        tick(&mut states, &['a'], &['a']);
+        assert!(states.clicked(&'a'));
+        assert_eq!(states.release_time(&'a'), 0);
+    }
+}
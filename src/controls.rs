@@ -0,0 +1,1713 @@
+use std::{
+    hash::Hash,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use ahash::AHashMap;
+
+use crate::{
+    analog::Axes,
+    control_state::ControlStates,
+    filter::InputFilters,
+    pointer::{DragState, PointerTransform},
+    raw_input::RawInputs,
+    AttractMode, AutoRepeat, AxisOverride, Charge, Chord, Confirmation, ControlEvent,
+    ControlsSummary, Debounce, Drag, DualAxis, Dwell, FrameNumber, HoldConfirm, ImeEvent,
+    InputFilter, InputLabel, InputSnapshot, ListenerId, LongPress, Macro, MinHold, MouseGestures,
+    RateLimit, Recorder, Rect, RetriggerPolicy, Rumble, RumbleRequest, Scanning, ScrollControls,
+    StickFlicks, StickNavigation, TapOrHold, TextEdit, TextEntry, TimerClocks, TimingSettings,
+    TriggerEffect, TriggerEffectRequest, TriggerSide, VirtualAxis,
+};
+
+/// The bindings, settings, and control state every input handler shares.
+///
+/// Each handler owns one of these and derefs to it, so everything here can be called on any handler directly;
+/// the handlers themselves only add the parts that depend on how input gets in, like updating and queueing
+/// events.
+///
+/// ```rust
+/// # use puppetmaster::{Controls, PollingInputHandler};
+/// fn bind_jump(controls: &mut Controls<&'static str, &'static str>) {
+///     controls.control_config_mut().insert("space", "jump");
+///     controls.input_remap_mut().insert("w", "space");
+/// }
+///
+/// let mut input_handler = PollingInputHandler::<&str, &str>::new();
+/// bind_jump(&mut input_handler);
+/// input_handler.update(vec!["w"]);
+/// assert!(input_handler.clicked("jump"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Controls<I, C> {
+    /// Maps inputs to the controls they activate
+    pub(crate) control_config: AHashMap<I, C>,
+    /// Rewrites inputs before they're looked up in `control_config`
+    pub(crate) input_remap: AHashMap<I, I>,
+    /// The state of each control
+    pub(crate) states: ControlStates<C>,
+    /// What the handler has seen of the raw inputs
+    pub(crate) raw_inputs: RawInputs<I>,
+    /// What the inputs go through before they're mapped
+    pub(crate) filters: InputFilters<I>,
+    /// Axis inputs, and the axis controls they're bound to
+    pub(crate) axes: Axes<I, C>,
+    /// The snapshot handed out since the last change, so taking several a frame is cheap
+    snapshot: OnceLock<InputSnapshot<C>>,
+}
+
+impl<I, C> Controls<I, C> {
+    /// Wrap the given mapping of inputs to controls, with everything else at its default.
+    pub(crate) fn with_config(control_config: AHashMap<I, C>) -> Self {
+        Self {
+            control_config,
+            input_remap: AHashMap::new(),
+            states: ControlStates::default(),
+            raw_inputs: RawInputs::default(),
+            filters: InputFilters::default(),
+            axes: Axes::default(),
+            snapshot: OnceLock::new(),
+        }
+    }
+
+    /// Forget the cached snapshot. Call this whenever something that goes into a snapshot might have changed.
+    pub(crate) fn changed(&mut self) {
+        self.snapshot.take();
+    }
+}
+
+impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> Controls<I, C> {
+    /// Park the gameplay controls' state, like when a modal menu opens. Pick it back up with
+    /// [`unstash`](Self::unstash) when the menu closes. Stashing again replaces what was stashed.
+    ///
+    /// The handler keeps working as usual in between, so the menu can use it.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler =
+    ///     PollingInputHandler::new_with_controls(vec![("d", "right"), ("enter", "jump")]);
+    /// for _ in 0..10 {
+    ///     input_handler.update(vec!["d"]);
+    /// }
+    ///
+    /// // The player opens the inventory while running, and picks something with enter
+    /// input_handler.stash();
+    /// for _ in 0..100 {
+    ///     input_handler.update(vec!["d", "enter"]);
+    /// }
+    /// input_handler.unstash();
+    ///
+    /// // Back in the game, still running right, but enter from the menu doesn't jump
+    /// input_handler.update(vec!["d", "enter"]);
+    /// assert_eq!(input_handler.press_time("right"), 11);
+    /// assert!(!input_handler.down("jump"));
+    /// ```
+    pub fn stash(&mut self) {
+        self.changed();
+        self.states.stash();
+    }
+
+    /// Pick the [stashed](Self::stash) state back up, matching it to what's physically held now. Returns if
+    /// anything was stashed.
+    ///
+    /// Controls that were down when the state was stashed and are still down carry on with the press time
+    /// they had, like the menu never happened, without clicking again. Controls pressed in the menu are let
+    /// go until they're released, so the button that closed the menu doesn't do anything in the game.
+    /// Controls released in the menu stay released.
+    pub fn unstash(&mut self) -> bool {
+        self.changed();
+        self.states.unstash()
+    }
+
+    /// Return if there's a [stashed](Self::stash) state waiting to be picked back up.
+    pub fn is_stashed(&self) -> bool {
+        self.states.is_stashed()
+    }
+
+    /// Let go of one control right now, without touching any others.
+    ///
+    /// Its press time goes back to 0 and it stops being pressed, without a release. It stays let go until every
+    /// input bound to it has been physically released, so it takes a fresh press to use it again. This is
+    /// for when the game uses up a press, like a grab that shouldn't turn into a second grab, or when one
+    /// held control should be dropped while the others stay held.
+    pub fn release_control(&mut self, ctrl: C) {
+        self.changed();
+        self.states.release_control(&ctrl);
+    }
+
+    /// Call this when the window regains focus.
+    ///
+    /// If [refocus suppression](Self::set_refocus_suppression) is on, the next update ignores every input,
+    /// so the click or keypress that brought the window back doesn't do anything in the game.
+    pub fn on_focus_gained(&mut self) {
+        self.states.focus_gained();
+    }
+
+    /// Make the first update after [`on_focus_gained`](Self::on_focus_gained) ignore every input. This is off
+    /// by default.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("click", "shoot")]);
+    /// input_handler.set_refocus_suppression(true);
+    ///
+    /// // The player alt-tabs away, then clicks on the window to come back.
+    /// input_handler.on_focus_lost();
+    /// input_handler.on_focus_gained();
+    /// input_handler.update(vec!["click"]);
+    /// assert!(input_handler.up("shoot"));
+    ///
+    /// input_handler.update(vec![]);
+    /// input_handler.update(vec!["click"]);
+    /// assert!(input_handler.clicked("shoot"));
+    /// ```
+    pub fn set_refocus_suppression(&mut self, suppress: bool) {
+        self.states.set_refocus_suppression(suppress);
+    }
+
+    /// Return if the first update after the window regains focus ignores every input.
+    pub fn refocus_suppression(&self) -> bool {
+        self.states.refocus_suppression()
+    }
+
+    /// Make a control toggle on and off with each press of its inputs, instead of only being down while they're
+    /// held. Pass `false` to make it a normal held control again.
+    ///
+    /// This is meant to be exposed as a player setting (toggle-to-aim, toggle-to-sprint, and so on);
+    /// your game keeps querying the control with [`down`](Self::down) either way.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![('c', "crouch")]);
+    /// input_handler.set_toggle("crouch", true);
+    ///
+    /// // Tap to crouch ...
+    /// input_handler.update(vec!['c']);
+    /// input_handler.update(vec![]);
+    /// assert!(input_handler.down("crouch"));
+    ///
+    /// // ... and tap again to stand up.
+    /// input_handler.update(vec!['c']);
+    /// assert!(input_handler.up("crouch"));
+    /// ```
+    pub fn set_toggle(&mut self, ctrl: C, toggle: bool) {
+        self.states.set_toggle(ctrl, toggle);
+    }
+
+    /// Return if the control toggles on and off instead of being held.
+    pub fn is_toggle(&self, ctrl: C) -> bool {
+        self.states.is_toggle(&ctrl)
+    }
+
+    /// Get how many times the control has been tapped in its current burst of rapid taps, or 0 if it isn't
+    /// being tapped. For "mash to escape", read this every frame; for a triple-tap special, check it when the
+    /// control [clicks](Self::clicked).
+    ///
+    /// Each press within the control's [tap window](Self::set_tap_window) of the one before adds to the burst,
+    /// and the burst is over once a window goes by without one.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![('j', "dash")]);
+    ///
+    /// let mut triple_tapped = false;
+    /// for held in [true, false, true, false, false, true] {
+    ///     input_handler.update(if held { vec!['j'] } else { vec![] });
+    ///     triple_tapped |= input_handler.clicked("dash") && input_handler.tap_count("dash") == 3;
+    /// }
+    /// assert!(triple_tapped);
+    ///
+    /// for _ in 0..30 {
+    ///     input_handler.update(vec![]);
+    /// }
+    /// assert_eq!(input_handler.tap_count("dash"), 0);
+    /// ```
+    pub fn tap_count(&self, ctrl: C) -> u32 {
+        self.states.tap_count(&ctrl)
+    }
+
+    /// Set how many frames apart the control's presses can be to count as one burst of taps, or pass `None`
+    /// to go back to the handler's [`TimingSettings`].
+    pub fn set_tap_window(&mut self, ctrl: C, frames: Option<u32>) {
+        self.states.set_tap_window(ctrl, frames);
+    }
+
+    /// Get how many frames apart the control's presses can be to count as one burst of taps.
+    pub fn tap_window(&self, ctrl: C) -> u32 {
+        self.states.tap_window(&ctrl)
+    }
+
+    /// Lock a control out for `frames` frames after it clicks, or pass `None` to turn this off.
+    ///
+    /// During the lockout, new presses of the control are thrown away, not saved for later: the control
+    /// stays up until its inputs are released and pressed again after the lockout is over. Holding the
+    /// press that clicked it isn't affected. This stops bouncy switches and double taps from confirming a
+    /// menu or buying something twice.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("enter", "buy")]);
+    /// input_handler.set_lockout("buy", Some(10));
+    ///
+    /// // A worn-out switch bounces, sending a second press right after the first
+    /// let mut purchases = 0;
+    /// for held in [true, false, true, true, false, false, false, false, false, false, false, false, true] {
+    ///     input_handler.update(if held { vec!["enter"] } else { vec![] });
+    ///     purchases += input_handler.clicked("buy") as u32;
+    /// }
+    /// // The bounce was thrown away, and the press after the lockout went through
+    /// assert_eq!(purchases, 2);
+    /// ```
+    pub fn set_lockout(&mut self, ctrl: C, frames: Option<u32>) {
+        self.states.set_lockout(ctrl, frames);
+    }
+
+    /// Get how many frames the control is locked out for after clicking, if it has a lockout.
+    pub fn lockout(&self, ctrl: C) -> Option<u32> {
+        self.states.lockout(&ctrl)
+    }
+
+    /// Choose what happens when another input bound to the control goes down while the control is already
+    /// held. By default, nothing does. See [`RetriggerPolicy`].
+    pub fn set_retrigger_policy(&mut self, ctrl: C, policy: RetriggerPolicy) {
+        self.states.set_retrigger_policy(ctrl, policy);
+    }
+
+    /// Get what happens when another input bound to the control goes down while the control is already held.
+    pub fn retrigger_policy(&self, ctrl: C) -> RetriggerPolicy {
+        self.states.retrigger_policy(&ctrl)
+    }
+
+    /// Make a control only ever click, without being held, or pass `false` to make it a normal control again.
+    ///
+    /// A click-only control is down for the one frame it's pressed on, so its press time never goes past 1.
+    /// After that it's let go, and stays let go until all its inputs are released, so holding the button
+    /// never does it again. This is for dangerous actions like selling an item, so every system that uses
+    /// the control only does it once, whether it checks [`clicked`](Self::clicked) or [`down`](Self::down).
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("x", "sell_item")]);
+    /// input_handler.set_click_only("sell_item", true);
+    ///
+    /// let mut sold = 0;
+    /// for _ in 0..60 {
+    ///     input_handler.update(vec!["x"]);
+    ///     // Even a system that checks `down` only sells one item
+    ///     if input_handler.down("sell_item") {
+    ///         sold += 1;
+    ///     }
+    /// }
+    /// assert_eq!(sold, 1);
+    /// assert_eq!(input_handler.press_time("sell_item"), 0);
+    /// ```
+    pub fn set_click_only(&mut self, ctrl: C, click_only: bool) {
+        self.states.set_click_only(ctrl, click_only);
+    }
+
+    /// Return if the control is click-only.
+    pub fn is_click_only(&self, ctrl: C) -> bool {
+        self.states.is_click_only(&ctrl)
+    }
+
+    /// Set the timing settings used by every control that doesn't have its own.
+    ///
+    /// Settings given to one control, with [`set_debounce`](Self::set_debounce) and friends, still win over these.
+    pub fn set_timing(&mut self, timing: TimingSettings) {
+        self.states.set_timing(timing);
+    }
+
+    /// Get the timing settings used by every control that doesn't have its own.
+    pub fn timing(&self) -> &TimingSettings {
+        self.states.timing()
+    }
+
+    /// Ignore presses of a control that come too soon after it was released. Pass `None` to turn this off for
+    /// this control even if the handler's [`TimingSettings`] turn it on, or use
+    /// [`clear_debounce`](Self::clear_debounce) to go back to them.
+    ///
+    /// This overrides the handler's [`TimingSettings`] for this control.
+    ///
+    /// The extra presses are filtered out before anything else sees them, so they never show up in
+    /// [`clicked`](Self::clicked) or [`down`](Self::down).
+    pub fn set_debounce(&mut self, ctrl: C, debounce: Option<Debounce>) {
+        self.states.set_debounce(ctrl, debounce);
+    }
+
+    /// Get how soon after being released the control can be pressed again, if that's being filtered.
+    pub fn debounce(&self, ctrl: C) -> Option<Debounce> {
+        self.states.debounce(&ctrl)
+    }
+
+    /// Go back to using the handler's [`TimingSettings`] for this control's debounce.
+    pub fn clear_debounce(&mut self, ctrl: C) {
+        self.states.clear_debounce(&ctrl);
+    }
+
+    /// Make a control wait until its inputs have been pressed (or released) for a few frames in a row before
+    /// it's pressed (or released) too. Pass `None` to turn this off for this control even if the handler's
+    /// [`TimingSettings`] turn it on, or use [`clear_confirmation`](Self::clear_confirmation) to go back to
+    /// them.
+    ///
+    /// This overrides the handler's [`TimingSettings`] for this control.
+    ///
+    /// [`clicked`](Self::clicked) fires on the frame the press is confirmed, not the frame the input went down.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{Confirmation, PollingInputHandler};
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![('q', "quit")]);
+    /// input_handler.set_confirmation("quit", Some(Confirmation { press: 3, release: 1 }));
+    ///
+    /// // A brush against the key doesn't count ...
+    /// input_handler.update(vec!['q']);
+    /// input_handler.update(vec![]);
+    /// assert!(input_handler.up("quit"));
+    ///
+    /// // ... but holding it does.
+    /// input_handler.update(vec!['q']);
+    /// input_handler.update(vec!['q']);
+    /// assert!(input_handler.up("quit"));
+    /// input_handler.update(vec!['q']);
+    /// assert!(input_handler.clicked("quit"));
+    /// ```
+    pub fn set_confirmation(&mut self, ctrl: C, confirmation: Option<Confirmation>) {
+        self.states.set_confirmation(ctrl, confirmation);
+    }
+
+    /// Get how many frames in a row the control's inputs have to agree before it changes, if that's turned on.
+    pub fn confirmation(&self, ctrl: C) -> Option<Confirmation> {
+        self.states.confirmation(&ctrl)
+    }
+
+    /// Go back to using the handler's [`TimingSettings`] for this control's confirmation.
+    pub fn clear_confirmation(&mut self, ctrl: C) {
+        self.states.clear_confirmation(&ctrl);
+    }
+
+    /// Make a control ignore presses shorter than the given time. Pass `None` to turn this off for this
+    /// control even if the handler's [`TimingSettings`] turn it on, or use
+    /// [`clear_min_hold`](Self::clear_min_hold) to go back to them.
+    ///
+    /// [`clicked`](Self::clicked) fires when the threshold is crossed, and [`press_time`](Self::press_time)
+    /// starts counting from there. This overrides the handler's [`TimingSettings`] for this control.
+    pub fn set_min_hold(&mut self, ctrl: C, min_hold: Option<MinHold>) {
+        self.states.set_min_hold(ctrl, min_hold);
+    }
+
+    /// Get how long the control's inputs have to be held before the press counts, if that's turned on.
+    pub fn min_hold(&self, ctrl: C) -> Option<MinHold> {
+        self.states.min_hold(&ctrl)
+    }
+
+    /// Go back to using the handler's [`TimingSettings`] for this control's min hold.
+    pub fn clear_min_hold(&mut self, ctrl: C) {
+        self.states.clear_min_hold(&ctrl);
+    }
+
+    /// Make a control confirm something when it's held long enough, or pass `None` to turn this off.
+    /// See [`HoldConfirm`].
+    ///
+    /// Check how far along the player is with [`hold_progress`](Self::hold_progress), and act on
+    /// [`hold_confirmed`](Self::hold_confirmed). The control still works normally otherwise.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{HoldConfirm, PollingInputHandler};
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("x", "delete")]);
+    /// input_handler.set_hold_confirm("delete", Some(HoldConfirm::Frames(4)));
+    ///
+    /// input_handler.update(vec!["x"]);
+    /// input_handler.update(vec!["x"]);
+    /// assert_eq!(input_handler.hold_progress("delete"), 0.5);
+    ///
+    /// // Letting go cancels it
+    /// input_handler.update(vec![]);
+    /// assert_eq!(input_handler.hold_progress("delete"), 0.0);
+    ///
+    /// for _ in 0..4 {
+    ///     input_handler.update(vec!["x"]);
+    /// }
+    /// assert!(input_handler.hold_confirmed("delete"));
+    /// input_handler.update(vec!["x"]);
+    /// assert!(!input_handler.hold_confirmed("delete"));
+    /// assert_eq!(input_handler.hold_progress("delete"), 1.0);
+    /// ```
+    pub fn set_hold_confirm(&mut self, ctrl: C, hold: Option<HoldConfirm>) {
+        self.states.set_hold_confirm(ctrl, hold);
+    }
+
+    /// Get how long the control has to be held to confirm, if it's a hold-to-confirm control.
+    pub fn hold_confirm(&self, ctrl: C) -> Option<HoldConfirm> {
+        self.states.hold_confirm(&ctrl)
+    }
+
+    /// Get how far the control is toward confirming, from 0 to 1. This goes back to 0 when the control is
+    /// released, and is always 0 for controls that aren't [hold-to-confirm](Self::set_hold_confirm).
+    pub fn hold_progress(&self, ctrl: C) -> f32 {
+        self.states.hold_progress(&ctrl)
+    }
+
+    /// Return if the control was held long enough to confirm this frame. This is only true for the one
+    /// frame, even if the control stays held.
+    pub fn hold_confirmed(&self, ctrl: C) -> bool {
+        self.states.hold_confirmed(&ctrl)
+    }
+
+    /// Turn on navigating menus with an analog stick, or pass `None` to turn it off. See [`StickNavigation`].
+    pub fn set_stick_navigation(&mut self, navigation: Option<StickNavigation<C>>) {
+        self.states.set_stick_navigation(navigation);
+    }
+
+    /// Get the stick navigation settings, if it's on.
+    pub fn stick_navigation(&self) -> Option<&StickNavigation<C>> {
+        self.states.stick_navigation()
+    }
+
+    /// Tell the handler where the stick used for [menu navigation](Self::set_stick_navigation) is, from
+    /// -1 to 1 on each axis. Call this each frame before updating; it's ignored if stick navigation is off.
+    pub fn set_navigation_stick(&mut self, stick: (f32, f32)) {
+        self.states.set_navigation_stick(stick);
+    }
+
+    /// Turn on stick flicks, or pass `None` to turn them off. See [`StickFlicks`].
+    pub fn set_stick_flicks(&mut self, flicks: Option<StickFlicks<C>>) {
+        self.states.set_stick_flicks(flicks);
+    }
+
+    /// Get the stick flick settings, if they're on.
+    pub fn stick_flicks(&self) -> Option<&StickFlicks<C>> {
+        self.states.stick_flicks()
+    }
+
+    /// Tell the handler where the stick used for [flicks](Self::set_stick_flicks) is, from -1 to 1 on each
+    /// axis. Call this each frame before updating; it's ignored if stick flicks are off.
+    pub fn set_flick_stick(&mut self, stick: (f32, f32)) {
+        self.states.set_flick_stick(stick);
+    }
+
+    /// Turn on mouse gestures, or pass `None` to turn them off. See [`MouseGestures`].
+    pub fn set_mouse_gestures(&mut self, gestures: Option<MouseGestures<C>>) {
+        self.states.set_mouse_gestures(gestures);
+    }
+
+    /// Get the mouse gesture settings, if they're on.
+    pub fn mouse_gestures(&self) -> Option<&MouseGestures<C>> {
+        self.states.mouse_gestures()
+    }
+
+    /// Split a control into a short press and a long press, or pass `None` to stop splitting it.
+    /// See [`LongPress`] for how it works.
+    pub fn set_long_press(&mut self, ctrl: C, long_press: Option<LongPress<C>>) {
+        self.states.set_long_press(ctrl, long_press);
+    }
+
+    /// Get how the control is split into a short press and a long press, if it is.
+    pub fn long_press(&self, ctrl: C) -> Option<&LongPress<C>> {
+        self.states.long_press(&ctrl)
+    }
+
+    /// Tell taps of the control from holds: a press let go of before `frames` frames is a tap, and one held
+    /// for `frames` frames is a hold. 0 counts as 1, so every press is a hold. Pass `None` to stop for this
+    /// control even if the handler's [`TimingSettings`] turn it on, or use
+    /// [`clear_hold_threshold`](Self::clear_hold_threshold) to go back to them.
+    ///
+    /// Read how each press turned out with [`tap_or_hold`](Self::tap_or_hold). Unlike a
+    /// [long press](Self::set_long_press), this doesn't need any extra controls, and the control itself
+    /// works as usual.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{PollingInputHandler, TapOrHold};
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("b", "b_button")]);
+    /// input_handler.set_hold_threshold("b_button", Some(10));
+    ///
+    /// // Tap B to dodge ...
+    /// input_handler.update(vec!["b"]);
+    /// input_handler.update(vec![]);
+    /// assert_eq!(input_handler.tap_or_hold("b_button"), Some(TapOrHold::Tap));
+    ///
+    /// // ... and hold it to sprint.
+    /// for _ in 0..10 {
+    ///     input_handler.update(vec!["b"]);
+    /// }
+    /// assert_eq!(input_handler.tap_or_hold("b_button"), Some(TapOrHold::Hold));
+    /// input_handler.update(vec!["b"]);
+    /// assert_eq!(input_handler.tap_or_hold("b_button"), None);
+    /// ```
+    pub fn set_hold_threshold(&mut self, ctrl: C, frames: Option<u32>) {
+        self.states.set_hold_threshold(ctrl, frames);
+    }
+
+    /// Get how many frames the control has to be held for to count as a hold, if it's telling taps from holds.
+    pub fn hold_threshold(&self, ctrl: C) -> Option<u32> {
+        self.states.hold_threshold(&ctrl)
+    }
+
+    /// Go back to using the handler's [`TimingSettings`] for this control's hold threshold.
+    pub fn clear_hold_threshold(&mut self, ctrl: C) {
+        self.states.clear_hold_threshold(&ctrl);
+    }
+
+    /// Return how the control's press turned out, on the one frame it did: [`TapOrHold::Tap`] on the frame
+    /// a tap is let go, and [`TapOrHold::Hold`] on the frame it's been held for the
+    /// [threshold](Self::set_hold_threshold). Every other frame, this is `None`.
+    pub fn tap_or_hold(&self, ctrl: C) -> Option<TapOrHold> {
+        self.states.tap_or_hold(&ctrl)
+    }
+
+    /// Make a control a charge move, clicked by holding one control and then pressing another, or pass `None`
+    /// to turn it off. See [`Charge`] for how it works.
+    pub fn set_charge(&mut self, ctrl: C, charge: Option<Charge<C>>) {
+        self.states.set_charge(ctrl, charge);
+    }
+
+    /// Get how the control is charged, if it's a charge move.
+    pub fn charge(&self, ctrl: C) -> Option<&Charge<C>> {
+        self.states.charge(&ctrl)
+    }
+
+    /// Make a control a chord, down while several other controls are held together, or pass `None` to turn
+    /// it off. See [`Chord`] for how it works.
+    pub fn set_chord(&mut self, ctrl: C, chord: Option<Chord<C>>) {
+        self.states.set_chord(ctrl, chord);
+    }
+
+    /// Get the controls that make up the chord, if the control is one.
+    pub fn chord(&self, ctrl: C) -> Option<&Chord<C>> {
+        self.states.chord(&ctrl)
+    }
+
+    /// Give a control stages that it reaches by being held longer, like the levels of a charge attack.
+    /// Pass `None` to turn this off.
+    ///
+    /// `thresholds` is how many frames the control has to be held for to reach each stage; stage 1 is the
+    /// first threshold, stage 2 the second, and so on. Stage 0 is before the first.
+    ///
+    /// Check which stage the control is at with [`stage`](Self::stage), when it gets to a new one with
+    /// [`stage_reached`](Self::stage_reached), and which one it was let go at with
+    /// [`released_at_stage`](Self::released_at_stage).
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("j", "fireball")]);
+    /// input_handler.set_stages("fireball", Some(vec![3, 6]));
+    ///
+    /// for _ in 0..3 {
+    ///     input_handler.update(vec!["j"]);
+    /// }
+    /// assert_eq!(input_handler.stage_reached("fireball"), Some(1));
+    /// input_handler.update(vec!["j"]);
+    /// assert_eq!(input_handler.stage_reached("fireball"), None);
+    /// assert_eq!(input_handler.stage("fireball"), 1);
+    ///
+    /// // Let go before the second stage, for a medium fireball
+    /// input_handler.update(vec![]);
+    /// assert_eq!(input_handler.released_at_stage("fireball"), Some(1));
+    /// assert_eq!(input_handler.stage("fireball"), 0);
+    /// ```
+    pub fn set_stages(&mut self, ctrl: C, thresholds: Option<Vec<u32>>) {
+        self.states.set_stages(ctrl, thresholds);
+    }
+
+    /// Get the control's stage thresholds, sorted, if it has stages.
+    pub fn stages(&self, ctrl: C) -> Option<&[u32]> {
+        self.states.stages(&ctrl)
+    }
+
+    /// Get which stage the control has been held to. This is 0 if it's up or doesn't have stages.
+    pub fn stage(&self, ctrl: C) -> usize {
+        self.states.stage(&ctrl)
+    }
+
+    /// Get the stage the control got to this frame, if it got to a new one.
+    pub fn stage_reached(&self, ctrl: C) -> Option<usize> {
+        self.states.stage_reached(&ctrl)
+    }
+
+    /// Get the stage the control was at when it was released, if it was released this frame. Letting go
+    /// before the first stage gives `Some(0)`.
+    pub fn released_at_stage(&self, ctrl: C) -> Option<usize> {
+        self.states.released_at_stage(&ctrl)
+    }
+
+    /// Give a control's timing windows `frames` extra frames while [assist mode](Self::set_assist_mode) is on,
+    /// or pass `None` to stop assisting it.
+    ///
+    /// This stretches the [follow-up windows](Self::open_follow_up) the control is pressed in, and the
+    /// window for pressing the trigger of a [charge move](Self::set_charge) if the control is the move.
+    /// Set these from your difficulty settings, so easier difficulties can be more forgiving with specific
+    /// moves.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler =
+    ///     PollingInputHandler::new_with_controls(vec![("j", "attack"), ("k", "dodge")]);
+    /// // On easy difficulty, dodge-cancels get 5 more frames
+    /// input_handler.set_timing_assist("dodge", Some(5));
+    /// input_handler.set_assist_mode(true);
+    ///
+    /// input_handler.update(vec!["j"]);
+    /// input_handler.open_follow_up("dodge_cancel", 4);
+    /// for _ in 0..6 {
+    ///     input_handler.update(vec![]);
+    /// }
+    /// assert!(!input_handler.follow_up_open("dodge_cancel"));
+    /// input_handler.update(vec!["k"]);
+    /// // Too late normally, but within the assisted window
+    /// assert!(input_handler.followed_up("dodge_cancel", "dodge"));
+    /// ```
+    pub fn set_timing_assist(&mut self, ctrl: C, frames: Option<u32>) {
+        self.states.set_timing_assist(ctrl, frames);
+    }
+
+    /// Get how many extra frames the control's timing windows get in assist mode, if it has any.
+    pub fn timing_assist(&self, ctrl: C) -> Option<u32> {
+        self.states.timing_assist(&ctrl)
+    }
+
+    /// Turn [timing assists](Self::set_timing_assist) on or off. They're off by default.
+    pub fn set_assist_mode(&mut self, on: bool) {
+        self.states.set_assist_mode(on);
+    }
+
+    /// Return if timing assists are on.
+    pub fn assist_mode(&self) -> bool {
+        self.states.assist_mode()
+    }
+
+    /// Open a follow-up window called `name` for the next `frames` updates, replacing any window that
+    /// already had that name.
+    ///
+    /// The handler remembers which controls are clicked while the window is open, so you can ask later if a
+    /// control [followed up](Self::followed_up) within it, even after it's closed. This is for combos and
+    /// cancels: open a window when a move fires, then check for the next move whenever the game is ready
+    /// for it, and presses made early are buffered.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler =
+    ///     PollingInputHandler::new_with_controls(vec![("j", "attack"), ("k", "dodge")]);
+    ///
+    /// input_handler.update(vec!["j"]);
+    /// if input_handler.clicked("attack") {
+    ///     // The attack can be dodge-cancelled for the next 10 frames
+    ///     input_handler.open_follow_up("dodge_cancel", 10);
+    /// }
+    /// input_handler.update(vec![]);
+    /// input_handler.update(vec!["k"]);
+    ///
+    /// // Later, once the attack's animation gets to a point where it can be cancelled ...
+    /// assert!(input_handler.followed_up("dodge_cancel", "dodge"));
+    /// assert_eq!(input_handler.follow_up_press("dodge_cancel", "dodge"), Some(2));
+    /// ```
+    pub fn open_follow_up(&mut self, name: &str, frames: u32) {
+        self.states.open_follow_up(name, frames);
+    }
+
+    /// Forget the follow-up window called `name`, and what was pressed in it. Returns if there was one.
+    pub fn close_follow_up(&mut self, name: &str) -> bool {
+        self.states.close_follow_up(name)
+    }
+
+    /// Return if the follow-up window called `name` is still open, so presses in the next update still count.
+    pub fn follow_up_open(&self, name: &str) -> bool {
+        self.states.follow_up_open(name)
+    }
+
+    /// Return if the control was clicked during the follow-up window called `name`.
+    pub fn followed_up(&self, name: &str, ctrl: C) -> bool {
+        self.follow_up_press(name, ctrl).is_some()
+    }
+
+    /// Get how many frames into the follow-up window called `name` the control was first clicked, counting
+    /// the first update after it opened as 1. Returns `None` if it wasn't clicked during the window.
+    pub fn follow_up_press(&self, name: &str, ctrl: C) -> Option<u32> {
+        self.states.follow_up_press(name, &ctrl)
+    }
+
+    /// Limit how often a control can click. Pass `None` to turn this off for this control even if the
+    /// handler's [`TimingSettings`] turn it on, or use [`clear_rate_limit`](Self::clear_rate_limit) to go
+    /// back to them.
+    ///
+    /// Clicks beyond the limit are dropped, though the control still counts as [`down`](Self::down). This
+    /// overrides the handler's [`TimingSettings`] for this control.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{PollingInputHandler, RateLimit};
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![('e', "emote")]);
+    /// input_handler.set_rate_limit("emote", Some(RateLimit::per_second(2)));
+    ///
+    /// let mut emotes = 0;
+    /// for _ in 0..5 {
+    ///     input_handler.update(vec!['e']);
+    ///     emotes += input_handler.clicked("emote") as u32;
+    ///     input_handler.update(vec![]);
+    /// }
+    /// assert_eq!(emotes, 2);
+    /// ```
+    pub fn set_rate_limit(&mut self, ctrl: C, rate_limit: Option<RateLimit>) {
+        self.states.set_rate_limit(ctrl, rate_limit);
+    }
+
+    /// Get how often the control is allowed to click, if it's limited.
+    pub fn rate_limit(&self, ctrl: C) -> Option<RateLimit> {
+        self.states.rate_limit(&ctrl)
+    }
+
+    /// Go back to using the handler's [`TimingSettings`] for this control's rate limit.
+    pub fn clear_rate_limit(&mut self, ctrl: C) {
+        self.states.clear_rate_limit(&ctrl);
+    }
+
+    /// Make a control sticky, like the sticky keys of desktop operating systems. Pass `false` to make it
+    /// a normal control again.
+    ///
+    /// Tapping a sticky control keeps it down until another control has been pressed and released, so
+    /// players don't have to hold modifiers like sprint or crouch while pressing something else.
+    /// Holding it along with another control works as usual, and tapping it again un-sticks it.
+    pub fn set_sticky(&mut self, ctrl: C, sticky: bool) {
+        self.states.set_sticky(ctrl, sticky);
+    }
+
+    /// Return if the control is sticky.
+    pub fn is_sticky(&self, ctrl: C) -> bool {
+        self.states.is_sticky(&ctrl)
+    }
+
+    /// Turn on one-switch scanning with the given settings, or pass `None` to turn it off.
+    /// See [`Scanning`] for how it works.
+    pub fn set_scanning(&mut self, scanning: Option<Scanning<C>>) {
+        self.states.set_scanning(scanning);
+    }
+
+    /// Get the one-switch scanning settings, if scanning is on.
+    pub fn scanning(&self) -> Option<&Scanning<C>> {
+        self.states.scanning()
+    }
+
+    /// Get the control the scan is focused on, so you can highlight it.
+    pub fn scan_focus(&self) -> Option<&C> {
+        self.states.scan_focus()
+    }
+
+    /// Turn on dwell activation with the given settings, or pass `None` to turn it off.
+    /// See [`Dwell`] for how it works.
+    pub fn set_dwell(&mut self, dwell: Option<Dwell<C>>) {
+        self.states.set_dwell(dwell);
+    }
+
+    /// Get the dwell activation settings, if dwell is on.
+    pub fn dwell(&self) -> Option<&Dwell<C>> {
+        self.states.dwell()
+    }
+
+    /// Get the control the pointer is dwelling on, and how far along the dwell is from 0 to 1, so you can
+    /// draw an indicator. This is `None` once the control's been pressed, until the pointer moves to a new region.
+    pub fn dwell_progress(&self) -> Option<(&C, f32)> {
+        self.states.dwell_progress()
+    }
+
+    /// Tell the handler where the pointer is in window coordinates, or `None` if it's left the window.
+    ///
+    /// The position sticks until you set it again, so you only need to call this when the pointer moves.
+    /// Like inputs, it takes effect at the next update.
+    pub fn set_pointer(&mut self, pointer: Option<(f32, f32)>) {
+        self.states.pointer_mut().report(pointer);
+    }
+
+    /// Convert pointer positions from window coordinates to your own before anything sees them, like
+    /// turning them into world coordinates with your camera, or into UI coordinates when the UI is scaled.
+    ///
+    /// The transform is applied at each update, so it's fine if it changes between frames (say, because
+    /// it reads the camera position from something shared).
+    ///
+    /// ```rust
+    /// # use puppetmaster::{PollingInputHandler, Rect};
+    /// let mut input_handler = PollingInputHandler::<(), ()>::new();
+    /// // The UI is drawn at twice the size
+    /// input_handler.set_pointer_transform(|(x, y)| (x / 2.0, y / 2.0));
+    /// let button = Rect::new(10.0, 10.0, 40.0, 20.0);
+    ///
+    /// input_handler.set_pointer(Some((0.0, 0.0)));
+    /// input_handler.update(vec![]);
+    /// input_handler.set_pointer(Some((40.0, 30.0)));
+    /// input_handler.update(vec![]);
+    /// assert_eq!(input_handler.pointer_position(), Some((20.0, 15.0)));
+    /// assert_eq!(input_handler.pointer_delta(), (20.0, 15.0));
+    /// assert!(input_handler.pointer_entered(button));
+    /// ```
+    pub fn set_pointer_transform(
+        &mut self,
+        transform: impl Fn((f32, f32)) -> (f32, f32) + Send + Sync + 'static,
+    ) {
+        self.states
+            .pointer_mut()
+            .set_transform(Some(PointerTransform::new(transform)));
+    }
+
+    /// Stop transforming pointer positions, so they're in window coordinates again.
+    pub fn clear_pointer_transform(&mut self) {
+        self.states.pointer_mut().set_transform(None);
+    }
+
+    /// Get where the pointer is, after the transform, or `None` if it's not over the window.
+    pub fn pointer_position(&self) -> Option<(f32, f32)> {
+        self.states.pointer().position()
+    }
+
+    /// Get how far the pointer moved since the last update, after the transform. This is `(0.0, 0.0)` if it
+    /// just came onto the window or left it.
+    pub fn pointer_delta(&self) -> (f32, f32) {
+        self.states.pointer().delta()
+    }
+
+    /// Return if the pointer is inside the rectangle.
+    pub fn pointer_in(&self, rect: Rect) -> bool {
+        self.states.pointer().is_in(rect)
+    }
+
+    /// Return if the pointer moved into the rectangle this frame.
+    pub fn pointer_entered(&self, rect: Rect) -> bool {
+        self.states.pointer().entered(rect)
+    }
+
+    /// Return if the pointer moved out of the rectangle this frame.
+    pub fn pointer_exited(&self, rect: Rect) -> bool {
+        self.states.pointer().exited(rect)
+    }
+
+    /// Tell the handler the mouse wheel or trackpad scrolled, in lines. Positive `y` is up, and positive `x`
+    /// is to the right.
+    ///
+    /// Call this for every scroll event you get. They're added up and take effect at the next update.
+    pub fn add_scroll(&mut self, delta: (f32, f32)) {
+        self.states.scroll_mut().add(delta);
+    }
+
+    /// Get how far the mouse wheel or trackpad scrolled since the last update, in lines.
+    pub fn scroll(&self) -> (f32, f32) {
+        self.states.scroll().delta()
+    }
+
+    /// Make controls click as the mouse wheel or trackpad scrolls, or pass `None` to stop.
+    /// See [`ScrollControls`] for how it works.
+    pub fn set_scroll_controls(&mut self, controls: Option<ScrollControls<C>>) {
+        self.states.scroll_mut().set_controls(controls);
+    }
+
+    /// Get the controls that click when scrolling, if there are any.
+    pub fn scroll_controls(&self) -> Option<&ScrollControls<C>> {
+        self.states.scroll().settings()
+    }
+
+    /// Make a control tell clicks from drags, or pass `None` to stop. Usually this is a mouse button.
+    ///
+    /// A press of the control becomes a drag once the pointer moves more than `threshold` away from where it
+    /// was pressed (in the same coordinates as [`pointer_position`](Self::pointer_position)). Until then, it
+    /// might still be a click, which [`tapped`](Self::tapped) reports when it's released.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("lmb", "select")]);
+    /// input_handler.set_drag_threshold("select", Some(4.0));
+    ///
+    /// input_handler.set_pointer(Some((10.0, 10.0)));
+    /// input_handler.update(vec!["lmb"]);
+    /// input_handler.set_pointer(Some((30.0, 25.0)));
+    /// input_handler.update(vec!["lmb"]);
+    /// // Draw a selection box ...
+    /// assert_eq!(input_handler.drag("select").unwrap().delta(), (20.0, 15.0));
+    ///
+    /// input_handler.update(vec![]);
+    /// assert!(input_handler.drag_ended("select").is_some());
+    /// assert!(!input_handler.tapped("select"));
+    /// ```
+    pub fn set_drag_threshold(&mut self, ctrl: C, threshold: Option<f32>) {
+        self.states.set_drag_threshold(ctrl, threshold);
+    }
+
+    /// Get how far the pointer has to move for a press of the control to be a drag, if it can be dragged.
+    pub fn drag_threshold(&self, ctrl: C) -> Option<f32> {
+        self.states.drag_threshold(&ctrl)
+    }
+
+    /// Get the control's drag, if it's being dragged.
+    pub fn drag(&self, ctrl: C) -> Option<Drag> {
+        match self.states.drag_state(&ctrl) {
+            DragState::Dragging(drag) => Some(drag),
+            _ => None,
+        }
+    }
+
+    /// Get the control's drag, if it was let go at the end of a drag this frame.
+    pub fn drag_ended(&self, ctrl: C) -> Option<Drag> {
+        match self.states.drag_state(&ctrl) {
+            DragState::Ended(drag) => Some(drag),
+            _ => None,
+        }
+    }
+
+    /// Return if the draggable control was let go this frame without having been dragged, so the press
+    /// was a click.
+    pub fn tapped(&self, ctrl: C) -> bool {
+        self.states.drag_state(&ctrl) == DragState::Tapped
+    }
+
+    /// Play the macro whenever the control is pressed, or pass `None` to stop. See [`Macro`] for how it works.
+    ///
+    /// Map an input to the control as usual to put the macro on that input.
+    pub fn set_macro(&mut self, ctrl: C, steps: Option<Macro<C>>) {
+        self.states.set_macro(ctrl, steps);
+    }
+
+    /// Get the macro the control plays, if it has one.
+    pub fn macro_for(&self, ctrl: C) -> Option<&Macro<C>> {
+        self.states.macro_for(&ctrl)
+    }
+
+    /// Start playing a macro, starting at the next update.
+    pub fn play_macro(&mut self, steps: Macro<C>) {
+        self.states.play_macro(steps);
+    }
+
+    /// Stop every macro that's playing. Whatever they were holding is released at the next update.
+    pub fn stop_macros(&mut self) {
+        self.states.stop_macros();
+    }
+
+    /// Pause or unpause the game's controls.
+    ///
+    /// While paused, controls are frozen: [`press_time`](Self::press_time) stops counting and nothing is
+    /// [`clicked`](Self::clicked). Controls pressed during the pause are ignored until they're released, so
+    /// unpausing never causes a spurious click. Releases still go through, so a control let go during the
+    /// pause isn't down when it ends. Controls made [exempt](Self::set_pause_exempt) keep working, so the
+    /// player can still unpause and move around a pause menu.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler =
+    ///     PollingInputHandler::new_with_controls(vec![("esc", "pause"), ("space", "jump")]);
+    /// input_handler.set_pause_exempt("pause", true);
+    ///
+    /// input_handler.update(vec!["space"]);
+    /// input_handler.set_paused(true);
+    /// input_handler.update(vec!["space"]);
+    /// input_handler.update(vec!["space"]);
+    /// assert_eq!(input_handler.press_time("jump"), 1);
+    ///
+    /// input_handler.update(vec!["esc"]);
+    /// assert!(input_handler.clicked("pause"));
+    /// ```
+    pub fn set_paused(&mut self, paused: bool) {
+        self.states.set_paused(paused);
+    }
+
+    /// Return if the controls are paused.
+    pub fn is_paused(&self) -> bool {
+        self.states.is_paused()
+    }
+
+    /// Make a control keep working while the controls are paused. Pass `false` to make it freeze again.
+    pub fn set_pause_exempt(&mut self, ctrl: C, exempt: bool) {
+        self.states.set_pause_exempt(ctrl, exempt);
+    }
+
+    /// Return if the control keeps working while the controls are paused.
+    pub fn is_pause_exempt(&self, ctrl: C) -> bool {
+        self.states.is_pause_exempt(&ctrl)
+    }
+
+    /// Start text entry, like when the player opens a chat box or a name field.
+    ///
+    /// Until [`end_text_entry`](Self::end_text_entry), typing goes into a [`TextEntry`] instead of the game:
+    /// every control is released and stays up, except the ones you've made
+    /// [pass through](Self::set_text_entry_passthrough) (like one to close the chat box). Anything held when
+    /// text entry ends stays ignored until it's released.
+    ///
+    /// The text comes from [`on_text`](Self::on_text) and [`on_text_edit`](Self::on_text_edit), which the
+    /// engine adapters call for you. Starting text entry when it's already on does nothing.
+    pub fn begin_text_entry(&mut self) {
+        self.states.text_mut().begin();
+    }
+
+    /// Stop text entry, and get what was typed. Returns `None` if text entry wasn't on.
+    pub fn end_text_entry(&mut self) -> Option<String> {
+        self.states.text_mut().end()
+    }
+
+    /// Get the text being typed, if text entry is on.
+    pub fn text_entry(&self) -> Option<&TextEntry> {
+        self.states.text().entry()
+    }
+
+    /// Get mutable access to the text being typed, if text entry is on.
+    pub fn text_entry_mut(&mut self) -> Option<&mut TextEntry> {
+        self.states.text_mut().entry_mut()
+    }
+
+    /// Call this when your game engine gives you typed text. It's added at the cursor at the next update.
+    ///
+    /// This does nothing unless text entry is on.
+    pub fn on_text(&mut self, text: &str) {
+        self.states.text_mut().on_text(text);
+    }
+
+    /// Call this when an editing key like backspace is pressed, or repeated. It's applied at the next update.
+    ///
+    /// This does nothing unless text entry is on.
+    pub fn on_text_edit(&mut self, edit: TextEdit) {
+        self.states.text_mut().on_edit(edit);
+    }
+
+    /// Call this when your game engine reports an input method editor event. It's applied at the next update.
+    /// See [`ImeEvent`] for how composition works.
+    ///
+    /// This does nothing unless text entry is on.
+    pub fn on_ime(&mut self, event: ImeEvent) {
+        self.states.text_mut().on_ime(event);
+    }
+
+    /// Make a control keep working during text entry. Pass `false` to make it ignored again.
+    pub fn set_text_entry_passthrough(&mut self, ctrl: C, passthrough: bool) {
+        self.states.set_text_passthrough(ctrl, passthrough);
+    }
+
+    /// Return if the control keeps working during text entry.
+    pub fn is_text_entry_passthrough(&self, ctrl: C) -> bool {
+        self.states.is_text_passthrough(&ctrl)
+    }
+
+    /// Call `listener` whenever something happens to the control, for things like sound effects that are
+    /// easier to write as reactions to events. Returns an ID to [remove](Self::remove_listener) it with.
+    ///
+    /// Listeners are called at the end of [`update`](crate::PollingInputHandler::update), once every control is up to date, so
+    /// they see the same state your game will. They're called once per [`ControlEvent`], in the order the
+    /// events happened; if a control has several listeners, they're called in the order they
+    /// were added. Listeners can't reach back into the handler while it's updating. If one needs to change
+    /// the handler, have it send a message your game acts on after the update.
+    ///
+    /// ```rust
+    /// # use std::sync::{
+    /// #     atomic::{AtomicU32, Ordering},
+    /// #     Arc,
+    /// # };
+    /// # use puppetmaster::{ControlEvent, PollingInputHandler};
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("space", "jump")]);
+    ///
+    /// // Play a sound whenever the player jumps
+    /// let sounds_played = Arc::new(AtomicU32::new(0));
+    /// let sounds = sounds_played.clone();
+    /// input_handler.add_listener("jump", move |event| {
+    ///     if let ControlEvent::Pressed(_) = event {
+    ///         sounds.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// });
+    ///
+    /// input_handler.update(vec!["space"]);
+    /// input_handler.update(vec!["space"]);
+    /// input_handler.update(vec![]);
+    /// assert_eq!(sounds_played.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn add_listener(
+        &mut self,
+        ctrl: C,
+        listener: impl Fn(&ControlEvent<C>) + Send + Sync + 'static,
+    ) -> ListenerId {
+        self.states.add_listener(ctrl, listener)
+    }
+
+    /// Remove a listener. Returns `false` if it was already removed.
+    pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.states.remove_listener(id)
+    }
+
+    /// Remove every listener.
+    pub fn clear_listeners(&mut self) {
+        self.states.clear_listeners();
+    }
+
+    /// Ask for a game controller to rumble, or every controller if `device` is `None`. See [`Rumble`].
+    ///
+    /// This only queues the request up; it's played when an engine adapter drains the queue.
+    pub fn rumble(&mut self, device: Option<u32>, rumble: Rumble) {
+        self.states.request_rumble(device, rumble);
+    }
+
+    /// Take the rumble requests that haven't been played yet, oldest first.
+    ///
+    /// Requests wait here until they're drained, so if you aren't using an adapter that plays them,
+    /// drain this yourself every frame.
+    pub fn drain_rumble(&mut self) -> impl Iterator<Item = RumbleRequest> + '_ {
+        self.states.drain_rumble()
+    }
+
+    /// Set how a game controller's adaptive trigger feels, or every controller's if `device` is `None`. See
+    /// [`TriggerEffect`].
+    ///
+    /// This only queues the effect up; it's applied when an engine adapter drains the queue.
+    pub fn set_trigger_effect(
+        &mut self,
+        device: Option<u32>,
+        trigger: TriggerSide,
+        effect: TriggerEffect,
+    ) {
+        self.states.request_trigger_effect(device, trigger, effect);
+    }
+
+    /// Take the trigger effects that haven't been applied yet, oldest first.
+    ///
+    /// Like rumble requests, these wait here until they're drained.
+    pub fn drain_trigger_effects(&mut self) -> impl Iterator<Item = TriggerEffectRequest> + '_ {
+        self.states.drain_trigger_effects()
+    }
+
+    /// Make a control click again every `period` frames while it's held, as if it were being mashed. Pass
+    /// `None` to turn this off for this control even if the handler's [`TimingSettings`] turn it on, or use
+    /// [`clear_turbo`](Self::clear_turbo) to go back to them.
+    ///
+    /// This overrides the handler's [`TimingSettings`] for this control.
+    ///
+    /// While turbo is on, [`clicked`](Self::clicked) is `true` on the frame the control is pressed and then
+    /// once every `period` frames after that. [`down`](Self::down) and [`press_time`](Self::press_time)
+    /// aren't affected. A `period` of 0 counts as 1.
+    pub fn set_turbo(&mut self, ctrl: C, period: Option<u32>) {
+        self.states.set_turbo(ctrl, period);
+    }
+
+    /// Get how many frames apart the control clicks while it's held, if turbo is on for it.
+    pub fn turbo(&self, ctrl: C) -> Option<u32> {
+        self.states.turbo(&ctrl)
+    }
+
+    /// Go back to using the handler's [`TimingSettings`] for this control's turbo.
+    pub fn clear_turbo(&mut self, ctrl: C) {
+        self.states.clear_turbo(&ctrl);
+    }
+
+    /// Make a control click again while it's held, once after a delay and then at a steady rate. Pass `None`
+    /// to turn this off for this control even if the handler's [`TimingSettings`] turn it on, or use
+    /// [`clear_auto_repeat`](Self::clear_auto_repeat) to go back to them. See [`AutoRepeat`].
+    pub fn set_auto_repeat(&mut self, ctrl: C, auto_repeat: Option<AutoRepeat>) {
+        self.states.set_auto_repeat(ctrl, auto_repeat);
+    }
+
+    /// Get how the control clicks again while it's held, if it does.
+    pub fn auto_repeat(&self, ctrl: C) -> Option<AutoRepeat> {
+        self.states.auto_repeat(&ctrl)
+    }
+
+    /// Go back to using the handler's [`TimingSettings`] for this control's auto-repeat.
+    pub fn clear_auto_repeat(&mut self, ctrl: C) {
+        self.states.clear_auto_repeat(&ctrl);
+    }
+
+    /// Get which update the handler is on. This is 0 before the first update, then counts up by 1 each update.
+    pub fn frame(&self) -> FrameNumber {
+        self.states.frame()
+    }
+
+    /// Get the [frame](Self::frame) the control last clicked on, or `None` if it never has.
+    ///
+    /// Subtract this from the current frame for "how many frames ago" timing, like input buffering and
+    /// coyote time.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("space", "jump")]);
+    /// input_handler.update(vec!["space"]);
+    /// input_handler.update(vec![]);
+    /// input_handler.update(vec![]);
+    /// input_handler.update(vec![]);
+    ///
+    /// // Coyote time: let the player jump for a few frames after they press the button.
+    /// let frames_ago = input_handler
+    ///     .last_clicked_frame("jump")
+    ///     .map(|frame| input_handler.frame() - frame);
+    /// assert_eq!(frames_ago, Some(3));
+    /// assert_eq!(input_handler.last_released_frame("jump"), Some(2));
+    /// ```
+    pub fn last_clicked_frame(&self, ctrl: C) -> Option<FrameNumber> {
+        self.states.last_clicked_frame(&ctrl)
+    }
+
+    /// Get the [frame](Self::frame) the control was last released on, or `None` if it never has been.
+    pub fn last_released_frame(&self, ctrl: C) -> Option<FrameNumber> {
+        self.states.last_released_frame(&ctrl)
+    }
+
+    /// Get the raw input that was most recently pressed, and the frame it was pressed on.
+    ///
+    /// This is the input itself, before any [remapping](Self::input_remap_mut) or control mapping. It's for
+    /// "press any key" screens, rebinding prompts, and figuring out which device the player is using.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::<_, ()>::new();
+    /// input_handler.update(vec!['k']);
+    /// // "Press any key to start"
+    /// let start = input_handler
+    ///     .last_input()
+    ///     .is_some_and(|(_, frame)| frame == input_handler.frame());
+    /// assert!(start);
+    /// ```
+    pub fn last_input(&self) -> Option<(&I, FrameNumber)> {
+        self.raw_inputs.last()
+    }
+
+    /// Start waiting for the player to press something, for "press any key" screens and "press a button on
+    /// the controller you want to use" prompts.
+    ///
+    /// Anything already held down doesn't count until it's released and pressed again, so the button that
+    /// opened the prompt doesn't answer it too. Check for an answer with
+    /// [`any_input_pressed`](Self::any_input_pressed) and [`any_control_pressed`](Self::any_control_pressed).
+    /// Calling this again starts over.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("enter", "confirm")]);
+    /// // The player is still holding enter from the last menu
+    /// input_handler.update(vec!["enter"]);
+    ///
+    /// // "Press any button to continue"
+    /// input_handler.wait_for_any_input();
+    /// input_handler.update(vec!["enter"]);
+    /// assert_eq!(input_handler.any_input_pressed(), None);
+    ///
+    /// input_handler.update(vec!["enter", "k"]);
+    /// assert_eq!(input_handler.any_input_pressed(), Some(&"k"));
+    /// assert_eq!(input_handler.any_control_pressed(), None);
+    ///
+    /// input_handler.update(vec![]);
+    /// input_handler.update(vec!["enter"]);
+    /// assert_eq!(input_handler.any_input_pressed(), Some(&"k"));
+    /// assert_eq!(input_handler.any_control_pressed(), Some(&"confirm"));
+    /// ```
+    pub fn wait_for_any_input(&mut self) {
+        self.raw_inputs.wait_for_any();
+        self.states.wait_for_any();
+    }
+
+    /// Stop waiting for the player to press something, and forget what they pressed.
+    pub fn stop_waiting_for_input(&mut self) {
+        self.raw_inputs.stop_waiting();
+        self.states.stop_waiting();
+    }
+
+    /// Return if the handler is [waiting](Self::wait_for_any_input) for the player to press something.
+    pub fn is_waiting_for_input(&self) -> bool {
+        self.raw_inputs.is_waiting()
+    }
+
+    /// Get the first raw input pressed since the handler started [waiting](Self::wait_for_any_input), mapped
+    /// or not. This stays the same until the handler starts or stops waiting again.
+    pub fn any_input_pressed(&self) -> Option<&I> {
+        self.raw_inputs.any_pressed()
+    }
+
+    /// Get the first control pressed since the handler started [waiting](Self::wait_for_any_input). This
+    /// stays the same until the handler starts or stops waiting again.
+    pub fn any_control_pressed(&self) -> Option<&C> {
+        self.states.any_pressed()
+    }
+
+    /// Return the number of frames the given control has been pressed for
+    pub fn press_time(&self, ctrl: C) -> u32 {
+        self.states.press_time(&ctrl)
+    }
+
+    /// Set how fast game time runs compared to real time, like 0.5 for slow motion or 0 for hitstop. The
+    /// default is 1. It's kept from 0 to 1000, and NaN counts as 0.
+    ///
+    /// Timers measured in time, like [`MinHold::Duration`](crate::MinHold::Duration) and
+    /// [`RateLimit`](crate::RateLimit), run on game time unless [`set_timer_clocks`](Self::set_timer_clocks)
+    /// says otherwise, so they slow down and stop along with your game. Timers measured in frames, and
+    /// [`press_time`](Self::press_time), count every update no matter what. [`press_seconds`](Self::press_seconds)
+    /// counts exactly the delta time you give it.
+    ///
+    /// ```rust
+    /// # use std::{thread, time::Duration};
+    /// # use puppetmaster::{MinHold, PollingInputHandler};
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("j", "guard")]);
+    /// input_handler.set_min_hold("guard", Some(MinHold::Duration(Duration::from_millis(20))));
+    ///
+    /// // Hitstop freezes game time, so holding through it doesn't count toward the hold.
+    /// input_handler.set_time_scale(0.0);
+    /// for _ in 0..3 {
+    ///     input_handler.update(vec!["j"]);
+    ///     thread::sleep(Duration::from_millis(10));
+    /// }
+    /// assert!(input_handler.up("guard"));
+    ///
+    /// input_handler.set_time_scale(1.0);
+    /// thread::sleep(Duration::from_millis(25));
+    /// input_handler.update(vec!["j"]);
+    /// assert!(input_handler.down("guard"));
+    /// ```
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.states.set_time_scale(scale);
+    }
+
+    /// Get how fast game time runs compared to real time.
+    pub fn time_scale(&self) -> f32 {
+        self.states.time_scale()
+    }
+
+    /// Choose which clock each timer measured in time runs on. See [`Clock`](crate::Clock).
+    pub fn set_timer_clocks(&mut self, clocks: TimerClocks) {
+        self.states.set_timer_clocks(clocks);
+    }
+
+    /// Get which clock each timer measured in time runs on.
+    pub fn timer_clocks(&self) -> TimerClocks {
+        self.states.timer_clocks()
+    }
+
+    /// Tell the handler how many seconds of game time each update counts for, or `None` to stop counting
+    /// seconds. It keeps using this until you change it, so set it once if your game has a fixed timestep, or
+    /// before every update if it doesn't.
+    ///
+    /// Controls that are down count up their [`press_seconds`](Self::press_seconds) by this much each update.
+    /// Because it's game time, it slows down and stops along with your game.
+    pub fn set_delta_time(&mut self, dt: Option<f32>) {
+        self.states.set_delta_time(dt);
+    }
+
+    /// Get how many seconds of game time each update counts for, if it's been set.
+    pub fn delta_time(&self) -> Option<f32> {
+        self.states.delta_time()
+    }
+
+    /// Get how many seconds of game time the control has been down for, counting the update it was pressed
+    /// on, like [`press_time`](Self::press_time) does. This is 0 if it's up, or if no
+    /// [delta time](Self::set_delta_time) has been given.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("j", "charge")]);
+    /// input_handler.set_delta_time(Some(0.25));
+    ///
+    /// for _ in 0..4 {
+    ///     input_handler.update(vec!["j"]);
+    /// }
+    /// // The combo system counts frames, and the charge meter counts seconds.
+    /// assert_eq!(input_handler.press_time("charge"), 4);
+    /// assert_eq!(input_handler.press_seconds("charge"), 1.0);
+    /// ```
+    pub fn press_seconds(&self, ctrl: C) -> f32 {
+        self.states.press_seconds(&ctrl)
+    }
+
+    /// Get when the control was pressed, if it's down. This is the time of the update that saw it go down.
+    pub fn pressed_at(&self, ctrl: C) -> Option<Instant> {
+        self.states.pressed_at(&ctrl)
+    }
+
+    /// Get how long the control has been down for in real time, or zero if it's up.
+    ///
+    /// Unlike [`press_time`](Self::press_time), this doesn't depend on how often you update the handler, so
+    /// it's the one to use in UI and turn-based games that don't have a steady frame loop.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("enter", "end_turn")]);
+    /// input_handler.update(vec!["enter"]);
+    ///
+    /// // A turn-based game might not update again for a while
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// input_handler.update(vec!["enter"]);
+    /// assert_eq!(input_handler.press_time("end_turn"), 2);
+    /// assert!(input_handler.press_duration("end_turn") >= Duration::from_millis(20));
+    /// ```
+    pub fn press_duration(&self, ctrl: C) -> Duration {
+        self.pressed_at(ctrl)
+            .map_or(Duration::ZERO, |pressed_at| pressed_at.elapsed())
+    }
+
+    /// Return if this control is held down (ie, the corresponding input has been pressed for 1 or more frames).
+    pub fn down(&self, ctrl: C) -> bool {
+        self.press_time(ctrl) >= 1
+    }
+
+    /// Return if this control is up.
+    pub fn up(&self, ctrl: C) -> bool {
+        self.press_time(ctrl) == 0
+    }
+
+    /// Return if this control was *clicked* down this frame (ie, the corresponding input was *just* pressed this frame).
+    pub fn clicked(&self, ctrl: C) -> bool {
+        self.states.clicked(&ctrl)
+    }
+
+    /// Return if this control was released this frame (ie, the corresponding input was *just* let go of this
+    /// frame).
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("j", "bow")]);
+    /// input_handler.update(vec!["j"]);
+    /// input_handler.update(vec!["j"]);
+    ///
+    /// // Fire the arrow when the bowstring is let go.
+    /// input_handler.update(vec![]);
+    /// assert!(input_handler.just_released("bow"));
+    /// assert_eq!(input_handler.release_time("bow"), 1);
+    ///
+    /// input_handler.update(vec![]);
+    /// assert!(!input_handler.just_released("bow"));
+    /// assert_eq!(input_handler.release_time("bow"), 2);
+    /// ```
+    pub fn just_released(&self, ctrl: C) -> bool {
+        self.release_time(ctrl) == 1
+    }
+
+    /// Return the number of frames the given control has been up for since it was released, counting the
+    /// frame it was released on, like [`press_time`](Self::press_time) does. This is 0 if the control is
+    /// down, or has never been released.
+    pub fn release_time(&self, ctrl: C) -> u32 {
+        self.states.release_time(&ctrl)
+    }
+
+    /// Return an iterator over all the controls that are currently held down.
+    pub fn all_pressed(&self) -> impl Iterator<Item = &C> + '_ {
+        self.states.all_pressed()
+    }
+
+    /// Take a read-only snapshot of the state of all the controls and the values of the axis controls, which
+    /// can be shared across threads.
+    ///
+    /// The snapshot is only made again once something in it changes, so every snapshot taken in the same
+    /// frame shares one copy of the state.
+    pub fn snapshot(&self) -> InputSnapshot<C> {
+        self.snapshot
+            .get_or_init(|| self.states.snapshot(&self.axes))
+            .clone()
+    }
+
+    /// Record the controls held down this frame. Call this once per frame, after updating. See [`Recorder`]
+    /// for the difference between recording inputs and controls.
+    pub fn record_controls(&self, recorder: &mut Recorder<C>) {
+        recorder.record(self.states.all_pressed().cloned());
+    }
+
+    /// Get how many updates in a row none of the bound inputs have been held for.
+    pub fn idle_frames(&self) -> u64 {
+        self.states.idle_frames()
+    }
+
+    /// Set up a demo to play when nobody's touching the controls, or pass `None` to turn it off. See
+    /// [`AttractMode`].
+    ///
+    /// If the demo is playing, it stops, and every control it was holding is let go.
+    pub fn set_attract_mode(&mut self, attract: Option<AttractMode<C>>) {
+        self.states.set_attract_mode(attract);
+    }
+
+    /// Get the attract mode settings, if there are any.
+    pub fn attract_mode(&self) -> Option<&AttractMode<C>> {
+        self.states.attract_mode()
+    }
+
+    /// Start the attract mode demo from the beginning right now, without waiting for the controls to be idle.
+    /// Returns `false` if there's no attract mode set up.
+    pub fn start_attract_mode(&mut self) -> bool {
+        self.states.start_attract_mode()
+    }
+
+    /// Return if the attract mode demo is playing.
+    pub fn in_attract_mode(&self) -> bool {
+        self.states.in_attract_mode()
+    }
+
+    /// Describe the given controls and what they're bound to right now, for help screens and pause menus.
+    /// Bound controls that aren't asked for are left out.
+    ///
+    /// `describe_control` gives each control's name and category, and `describe_input` says how to show each
+    /// input. See [`ControlsSummary`].
+    pub fn export_controls_summary(
+        &self,
+        controls: impl IntoIterator<Item = C>,
+        describe_control: impl FnMut(&C) -> (String, String),
+        describe_input: impl FnMut(&I) -> InputLabel,
+    ) -> ControlsSummary<C> {
+        ControlsSummary::new(
+            &self.control_config,
+            &self.input_remap,
+            controls,
+            describe_control,
+            describe_input,
+        )
+    }
+
+    /// Get the value of an axis control as of the last update, from -1 to 1. When several axis inputs or a
+    /// [virtual axis](Self::set_virtual_axis) are bound to it, the one furthest from the middle wins. This is
+    /// 0 if nothing is bound to it.
+    pub fn value(&self, ctrl: C) -> f32 {
+        self.states.axis_value(&self.axes, &ctrl)
+    }
+
+    /// Make an axis control out of two digital controls, or pass `None` to stop.
+    ///
+    /// See [`VirtualAxis`] for how it works.
+    pub fn set_virtual_axis(&mut self, ctrl: C, axis: Option<VirtualAxis<C>>) {
+        self.changed();
+        self.axes.set_virtual_axis(ctrl, axis);
+    }
+
+    /// Get the digital controls an axis control is made from, if it's a virtual axis.
+    pub fn virtual_axis(&self, ctrl: C) -> Option<&VirtualAxis<C>> {
+        self.axes.virtual_axis(&ctrl)
+    }
+
+    /// Get the value of a 2D control as of the last update, from its two axis controls. This is `(0.0, 0.0)`
+    /// if the control isn't a [dual axis](Self::set_dual_axis).
+    pub fn vector(&self, ctrl: C) -> (f32, f32) {
+        self.states.axis_vector(&self.axes, &ctrl)
+    }
+
+    /// Make a 2D control out of two axis controls, or pass `None` to stop.
+    ///
+    /// See [`DualAxis`] for how it works.
+    pub fn set_dual_axis(&mut self, ctrl: C, axis: Option<DualAxis<C>>) {
+        self.changed();
+        self.axes.set_dual_axis(ctrl, axis);
+    }
+
+    /// Get the axis controls a 2D control is made from, if it's a dual axis.
+    pub fn dual_axis(&self, ctrl: C) -> Option<&DualAxis<C>> {
+        self.axes.dual_axis(&ctrl)
+    }
+
+    /// Make an axis control ignore small movements, like stick drift. Values closer to the middle than
+    /// `deadzone` count as 0, and the rest are scaled to still start from 0. The default is 0. It's kept from
+    /// 0 to 1, and NaN counts as 0.
+    pub fn set_axis_deadzone(&mut self, ctrl: C, deadzone: f32) {
+        self.changed();
+        self.axes.set_deadzone(ctrl, deadzone);
+    }
+
+    /// Get how far an axis control has to move before it counts, by its own setting. An
+    /// [axis context](Self::set_axis_context)'s override doesn't change this.
+    pub fn axis_deadzone(&self, ctrl: C) -> f32 {
+        self.axes.deadzone(&ctrl)
+    }
+
+    /// Scale an axis control's value after its deadzone, so it reaches the ends sooner (above 1) or moves
+    /// slower (below 1). The value is still kept from -1 to 1. The default is 1.
+    pub fn set_axis_sensitivity(&mut self, ctrl: C, sensitivity: f32) {
+        self.changed();
+        self.axes.set_sensitivity(ctrl, sensitivity);
+    }
+
+    /// Get what an axis control's value is scaled by, by its own setting.
+    pub fn axis_sensitivity(&self, ctrl: C) -> f32 {
+        self.axes.sensitivity(&ctrl)
+    }
+
+    /// Flip an axis control, so pushing one way reads as the other, like an "invert Y" option.
+    pub fn set_axis_inverted(&mut self, ctrl: C, inverted: bool) {
+        self.changed();
+        self.axes.set_inverted(ctrl, inverted);
+    }
+
+    /// Get if an axis control is flipped, by its own setting.
+    pub fn axis_inverted(&self, ctrl: C) -> bool {
+        self.axes.inverted(&ctrl)
+    }
+
+    /// Change an axis control's settings while an [axis context](Self::set_axis_context) is active, or pass
+    /// `None` to stop.
+    ///
+    /// See [`AxisOverride`] for how it works.
+    pub fn set_axis_override(&mut self, context: C, ctrl: C, settings: Option<AxisOverride>) {
+        self.changed();
+        self.axes.set_override(context, ctrl, settings);
+    }
+
+    /// Get the changes to an axis control's settings in an axis context, if it has any.
+    pub fn axis_override(&self, context: C, ctrl: C) -> Option<&AxisOverride> {
+        self.axes.axis_override(&context, &ctrl)
+    }
+
+    /// Switch to an axis context, so its [overrides](Self::set_axis_override) are used over the axis
+    /// controls' own settings, or pass `None` to use just their own. Contexts that nothing overrides are
+    /// fine, and change nothing. An axis context only picks the overrides; see [`AxisOverride`].
+    pub fn set_axis_context(&mut self, context: Option<C>) {
+        self.changed();
+        self.axes.set_context(context);
+    }
+
+    /// Get the axis context in use, if there is one.
+    pub fn axis_context(&self) -> Option<&C> {
+        self.axes.context()
+    }
+
+    /// Get the mapping of axis inputs to axis controls.
+    pub fn axis_config(&self) -> &AHashMap<I, C> {
+        self.axes.config()
+    }
+
+    /// Mutably get the mapping of axis inputs to axis controls.
+    pub fn axis_config_mut(&mut self) -> &mut AHashMap<I, C> {
+        self.changed();
+        self.axes.config_mut()
+    }
+
+    /// Get the mapping of inputs to controls.
+    pub fn control_config(&self) -> &AHashMap<I, C> {
+        &self.control_config
+    }
+
+    /// Get mutable access to the mapping of inputs to controls, for rebinding.
+    pub fn control_config_mut(&mut self) -> &mut AHashMap<I, C> {
+        &mut self.control_config
+    }
+
+    /// Get the table of inputs that stand in for other inputs.
+    pub fn input_remap(&self) -> &AHashMap<I, I> {
+        &self.input_remap
+    }
+
+    /// Get mutable access to the table of inputs that stand in for other inputs.
+    ///
+    /// An entry `(from, to)` makes pressing `from` act exactly like pressing `to`, for every control, before
+    /// `control_config` is looked at. This is for when a player's hardware is the problem rather than the
+    /// bindings: a broken button can be redirected to a working one, or two keys swapped, without touching
+    /// any control mappings. An input that's remapped no longer acts as itself.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler =
+    ///     PollingInputHandler::new_with_controls(vec![("x", "jump"), ("b", "shoot")]);
+    /// // The X button is broken, so swap it with B.
+    /// input_handler.input_remap_mut().insert("b", "x");
+    /// input_handler.input_remap_mut().insert("x", "b");
+    ///
+    /// input_handler.update(vec!["b"]);
+    /// assert!(input_handler.down("jump"));
+    /// assert!(input_handler.up("shoot"));
+    /// ```
+    pub fn input_remap_mut(&mut self) -> &mut AHashMap<I, I> {
+        &mut self.input_remap
+    }
+
+    /// Add a filter to the end of the chain the inputs go through before they're mapped to controls.
+    /// See [`InputFilter`].
+    pub fn add_input_filter(&mut self, filter: impl InputFilter<I> + 'static) {
+        self.filters.push(Box::new(filter));
+    }
+
+    /// Remove every input filter.
+    pub fn clear_input_filters(&mut self) {
+        self.filters.clear();
+    }
+
+    /// Get how many input filters there are.
+    pub fn input_filter_count(&self) -> usize {
+        self.filters.len()
+    }
+}
+
+impl<I, C> Default for Controls<I, C> {
+    fn default() -> Self {
+        Self::with_config(AHashMap::new())
+    }
+}
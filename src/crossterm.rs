@@ -0,0 +1,198 @@
+//! Glue for feeding [`crossterm`] events into an [`EventInputHandler`], for terminal games.
+//!
+//! Enable this with the `crossterm` feature.
+//!
+//! Most terminals only report key presses, never releases. To cope with that, give the handler a
+//! [`ReleaseTimeout`] (such as [`RELEASE_TIMEOUT`]) so keys let go on their own once the terminal stops
+//! repeating them. If the terminal supports the kitty keyboard protocol and you've pushed
+//! `KeyboardEnhancementFlags::REPORT_EVENT_TYPES`, real releases come through instead: the first one [`event`]
+//! sees turns the timeout off, since it isn't needed anymore.
+//!
+//! ```rust,no_run
+//! # use puppetmaster::{EventInputHandler, crossterm::{self as adapter, CrosstermInput}};
+//! # use ::crossterm::event::{self, KeyCode};
+//! #[derive(Clone, Copy, Hash, Eq, PartialEq)]
+//! enum Control {
+//!     Left,
+//!     Right,
+//!     Run,
+//! }
+//!
+//! let mut input_handler = EventInputHandler::new_with_controls(vec![
+//!     (CrosstermInput::Key(KeyCode::Char('h')), Control::Left),
+//!     (CrosstermInput::Key(KeyCode::Char('l')), Control::Right),
+//!     (CrosstermInput::Shift, Control::Run),
+//! ]);
+//! input_handler.set_release_timeout(Some(adapter::RELEASE_TIMEOUT));
+//!
+//! loop {
+//!     while event::poll(std::time::Duration::ZERO)? {
+//!         adapter::event(&mut input_handler, &event::read()?);
+//!     }
+//!     input_handler.update();
+//!
+//!     // Now do game logic ...
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::{hash::Hash, time::Duration};
+
+use ::crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, ModifierKeyCode, MouseButton,
+    MouseEventKind,
+};
+
+use crate::{EventInputHandler, ReleaseTimeout, TextEdit};
+
+/// A release timeout that works for most terminals.
+///
+/// It's a little longer than the usual delay before a held key starts repeating, so held keys don't flicker.
+pub const RELEASE_TIMEOUT: ReleaseTimeout = ReleaseTimeout::Duration(Duration::from_millis(600));
+
+/// Anything crossterm treats as a button.
+///
+/// Keys are whatever the terminal reports, so with shift held, the `a` key shows up as `Char('A')`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CrosstermInput {
+    /// A key on the keyboard.
+    Key(KeyCode),
+    /// Either shift key.
+    Shift,
+    /// Either control key.
+    Control,
+    /// Either alt key.
+    Alt,
+    /// Either super key.
+    Super,
+    /// A mouse button. The terminal needs mouse capture turned on for these.
+    Mouse(MouseButton),
+    /// One tick of the mouse wheel upwards.
+    WheelUp,
+    /// One tick of the mouse wheel downwards.
+    WheelDown,
+    /// One tick of the mouse wheel to the left.
+    WheelLeft,
+    /// One tick of the mouse wheel to the right.
+    WheelRight,
+}
+
+impl From<KeyCode> for CrosstermInput {
+    fn from(key: KeyCode) -> Self {
+        CrosstermInput::Key(key)
+    }
+}
+
+impl From<MouseButton> for CrosstermInput {
+    fn from(button: MouseButton) -> Self {
+        CrosstermInput::Mouse(button)
+    }
+}
+
+/// The modifier inputs, and the flags that report them
+const MODIFIERS: [(CrosstermInput, KeyModifiers); 4] = [
+    (CrosstermInput::Shift, KeyModifiers::SHIFT),
+    (CrosstermInput::Control, KeyModifiers::CONTROL),
+    (CrosstermInput::Alt, KeyModifiers::ALT),
+    (CrosstermInput::Super, KeyModifiers::SUPER),
+];
+
+/// Feed a crossterm event into the handler.
+///
+/// The modifier inputs follow the modifier flags on every key and mouse event, so they're held exactly as long
+/// as the terminal says they are. Modifier keys reported on their own (which only happens with the kitty
+/// protocol) count too.
+///
+/// Typing, and pasting if you've turned on bracketed paste, is passed on to
+/// [`EventInputHandler::on_text`] and [`EventInputHandler::on_text_edit`] for text entry.
+///
+/// When the terminal loses focus, all inputs are released, and regaining it is passed on to
+/// [`EventInputHandler::on_focus_gained`]. Focus events only come through if you've turned on
+/// `EnableFocusChange`.
+pub fn event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<CrosstermInput, C>,
+    event: &Event,
+) {
+    match event {
+        Event::Key(key) => key_event(handler, key),
+        Event::Mouse(mouse) => {
+            sync_modifiers(handler, mouse.modifiers);
+            let input = match mouse.kind {
+                MouseEventKind::Down(button) => {
+                    handler.on_input_down(CrosstermInput::Mouse(button));
+                    return;
+                }
+                MouseEventKind::Up(button) => {
+                    handler.on_input_up(CrosstermInput::Mouse(button));
+                    return;
+                }
+                MouseEventKind::ScrollUp => CrosstermInput::WheelUp,
+                MouseEventKind::ScrollDown => CrosstermInput::WheelDown,
+                MouseEventKind::ScrollLeft => CrosstermInput::WheelLeft,
+                MouseEventKind::ScrollRight => CrosstermInput::WheelRight,
+                MouseEventKind::Drag(_) | MouseEventKind::Moved => return,
+            };
+            handler.on_input_down(input);
+            handler.on_input_up(input);
+        }
+        Event::FocusLost => handler.on_focus_lost(),
+        Event::FocusGained => handler.on_focus_gained(),
+        Event::Paste(text) => handler.on_text(text),
+        _ => {}
+    }
+}
+
+fn key_event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<CrosstermInput, C>,
+    key: &KeyEvent,
+) {
+    if key.kind == KeyEventKind::Release && handler.release_timeout().is_some() {
+        // The terminal reports releases, so there's no need to guess at them
+        handler.set_release_timeout(None);
+    }
+
+    if key.kind != KeyEventKind::Release {
+        match key.code {
+            // Ctrl and alt combinations are shortcuts, not typing
+            KeyCode::Char(ch)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                handler.on_text(ch.encode_utf8(&mut [0; 4]));
+            }
+            KeyCode::Backspace => handler.on_text_edit(TextEdit::Backspace),
+            KeyCode::Delete => handler.on_text_edit(TextEdit::Delete),
+            KeyCode::Left => handler.on_text_edit(TextEdit::Left),
+            KeyCode::Right => handler.on_text_edit(TextEdit::Right),
+            KeyCode::Home => handler.on_text_edit(TextEdit::Home),
+            KeyCode::End => handler.on_text_edit(TextEdit::End),
+            _ => {}
+        }
+    }
+
+    let input = match key.code {
+        KeyCode::Modifier(modifier) => match modifier {
+            ModifierKeyCode::LeftShift | ModifierKeyCode::RightShift => CrosstermInput::Shift,
+            ModifierKeyCode::LeftControl | ModifierKeyCode::RightControl => CrosstermInput::Control,
+            ModifierKeyCode::LeftAlt | ModifierKeyCode::RightAlt => CrosstermInput::Alt,
+            ModifierKeyCode::LeftSuper | ModifierKeyCode::RightSuper => CrosstermInput::Super,
+            _ => CrosstermInput::Key(key.code),
+        },
+        code => {
+            // A modifier key's own event carries stale flags, so only trust them on other keys
+            sync_modifiers(handler, key.modifiers);
+            CrosstermInput::Key(code)
+        }
+    };
+    handler.on_input_event(input, key.kind != KeyEventKind::Release);
+}
+
+fn sync_modifiers<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<CrosstermInput, C>,
+    modifiers: KeyModifiers,
+) {
+    for (input, flag) in MODIFIERS {
+        handler.on_input_event(input, modifiers.contains(flag));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::Rect;
+
+/// Settings for dwell activation, where hovering the pointer over a button presses it.
+///
+/// While dwell is on, the handler watches the pointer position you give it with `set_pointer`. Once the
+/// pointer has stayed inside one of the `regions` for `dwell_time` frames, that region's control is pressed
+/// for one frame. To press it again, move the pointer out of the region and back in.
+///
+/// This is for players using eye trackers, head mice, and other pointers that can't click. Your game draws
+/// the regions and a dwell indicator (see `dwell_progress` on the handlers). The regions' controls don't
+/// need to have any inputs bound to them.
+///
+/// ```rust
+/// # use puppetmaster::{Dwell, DwellRegion, PollingInputHandler, Rect};
+/// let mut input_handler = PollingInputHandler::<(), _>::new();
+/// input_handler.set_dwell(Some(Dwell {
+///     regions: vec![DwellRegion {
+///         control: "jump",
+///         rect: Rect::new(10.0, 10.0, 50.0, 20.0),
+///     }],
+///     dwell_time: 30,
+/// }));
+///
+/// input_handler.set_pointer(Some((20.0, 15.0)));
+/// for _ in 0..15 {
+///     input_handler.update(vec![]);
+/// }
+/// assert_eq!(input_handler.dwell_progress(), Some((&"jump", 0.5)));
+///
+/// for _ in 0..15 {
+///     input_handler.update(vec![]);
+/// }
+/// assert!(input_handler.clicked("jump"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dwell<C> {
+    /// The areas of the screen that can be dwelled on.
+    pub regions: Vec<DwellRegion<C>>,
+    /// How many frames the pointer has to stay in a region to press its control. 0 counts as 1.
+    pub dwell_time: u32,
+}
+
+/// A rectangle that presses a control when the pointer dwells in it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DwellRegion<C> {
+    /// The control to press.
+    pub control: C,
+    /// Where the region is, in the same coordinates as the handler's
+    /// pointer position (so after any pointer transform).
+    pub rect: Rect,
+}
+
+/// Where the pointer is dwelling.
+#[derive(Clone, Debug)]
+pub(crate) struct DwellState<C> {
+    pub settings: Dwell<C>,
+    /// Index of the region the pointer is in
+    hovered: Option<usize>,
+    /// How many frames the pointer has been in the hovered region
+    timer: u32,
+    /// If the hovered region's control has already been pressed
+    fired: bool,
+}
+
+impl<C> DwellState<C> {
+    pub fn new(settings: Dwell<C>) -> Self {
+        Self {
+            settings,
+            hovered: None,
+            timer: 0,
+            fired: false,
+        }
+    }
+
+    /// Move forward a frame, given where the pointer is. Returns the control to press this frame, if any.
+    pub fn update(&mut self, pointer: Option<(f32, f32)>) -> Option<&C> {
+        let hovered = pointer.and_then(|pointer| {
+            self.settings
+                .regions
+                .iter()
+                .position(|region| region.rect.contains(pointer))
+        });
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            self.timer = 0;
+            self.fired = false;
+        }
+
+        let region = self.settings.regions.get(self.hovered?)?;
+        if self.fired {
+            return None;
+        }
+        self.timer += 1;
+        if self.timer >= self.settings.dwell_time.max(1) {
+            self.fired = true;
+            Some(&region.control)
+        } else {
+            None
+        }
+    }
+
+    /// The control being dwelled on and how far along the dwell is, from 0 to 1.
+    pub fn progress(&self) -> Option<(&C, f32)> {
+        if self.fired {
+            return None;
+        }
+        let region = self.settings.regions.get(self.hovered?)?;
+        Some((
+            &region.control,
+            self.timer as f32 / self.settings.dwell_time.max(1) as f32,
+        ))
+    }
+
+    /// Start every dwell over.
+    pub fn reset(&mut self) {
+        self.hovered = None;
+        self.timer = 0;
+        self.fired = false;
+    }
+}
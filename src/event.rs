@@ -1,6 +1,16 @@
-use std::hash::Hash;
+use std::{
+    hash::Hash,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 use ahash::{AHashMap, AHashSet};
+use itertools::{Either, Itertools};
+
+use crate::{
+    controls::Controls, handler::impl_input_handler, latency::LatencyTracker, ControlEvent,
+    LatencyStats, RecordedChange, Recorder, RepeatPolicy, SavedState, UpdateSummary,
+};
 
 /// Input handler for an event-based game engine.
 ///
@@ -75,16 +85,55 @@ use ahash::{AHashMap, AHashSet};
 /// ```
 ///
 /// `I` is the type of your inputs, and `C` is the type of your controls.
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct EventInputHandler<I, C> {
-    /// Maps inputs to the controls they activate
-    control_config: AHashMap<I, C>,
-    /// How long each control has been pressed
-    control_time: AHashMap<C, u32>,
-    /// This is loaded into `input_time` at the `update` method.
-    pressed_controls: AHashSet<C>,
+    /// The bindings, settings, and state of the controls
+    controls: Controls<I, C>,
+    /// Input events received since the last `update`, in the order they came in.
+    /// `true` is a press and `false` is a release.
+    event_queue: Vec<(I, bool)>,
+    /// The most events one `update` works through, if there's a limit
+    max_events_per_update: Option<usize>,
+    /// Inputs that are held down. This is loaded from `event_queue` in the `update` method.
+    pressed_inputs: AHashSet<I>,
+    /// Events sent from [`EventSender`]s, possibly on other threads.
+    /// This is only created once someone asks for a sender.
+    shared_queue: Option<SharedQueue<I>>,
+    /// What to do with key-down events for inputs that are already held
+    repeat_policy: RepeatPolicy,
+    /// If this is set, held inputs are released if they aren't re-reported in time
+    release_timeout: Option<ReleaseTimeout>,
+    /// The frame and time each held input was last reported down at.
+    /// This is only tracked when `release_timeout` is set.
+    last_reported: AHashMap<I, (u64, Instant)>,
+    /// How many times `update` has been called
+    frame_count: u64,
+    /// The window that has focus, if the game has said
+    focused_window: Option<u64>,
+    /// When each input waiting in the queue was pressed, if real-time mode is on
+    press_stamps: Option<AHashMap<I, Instant>>,
+    /// How far behind each device's events arrive
+    device_offsets: AHashMap<u32, Duration>,
+    /// Measures how long presses take to get through, if that's turned on
+    latency: Option<LatencyTracker<I, C>>,
+}
+
+/// How long an input can go without being reported again before an [`EventInputHandler`]
+/// pretends it was released.
+///
+/// See [`EventInputHandler::set_release_timeout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReleaseTimeout {
+    /// Release the input after this many updates without it being reported.
+    /// This should be at least 1.
+    Frames(u32),
+    /// Release the input after this much time without it being reported.
+    Duration(Duration),
 }
 
+type SharedQueue<I> = Arc<Mutex<Vec<(I, bool)>>>;
+
 impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> EventInputHandler<I, C> {
     /// Create a new `EventInputHandler` with no control mapping.
     pub fn new() -> Self {
@@ -96,77 +145,800 @@ impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> EventInputHandler<I, C> {
     /// If two entries in the iterator have the same input, the first one will be clobbered
     /// and the second one will remain.
     pub fn new_with_controls(map: impl IntoIterator<Item = (I, C)>) -> Self {
-        let control_config = map.into_iter().collect();
         Self {
-            control_config,
-            control_time: AHashMap::new(),
-            pressed_controls: AHashSet::new(),
+            controls: Controls::with_config(map.into_iter().collect()),
+            event_queue: Vec::new(),
+            max_events_per_update: None,
+            pressed_inputs: AHashSet::new(),
+            shared_queue: None,
+            repeat_policy: RepeatPolicy::default(),
+            release_timeout: None,
+            last_reported: AHashMap::new(),
+            frame_count: 0,
+            focused_window: None,
+            press_stamps: None,
+            device_offsets: AHashMap::new(),
+            latency: None,
         }
     }
 
     /// Call this function when your game engine gives you a `KeyDown` event.
+    ///
+    /// The event is queued up and processed in order at the next [`update`](Self::update).
     pub fn on_input_down(&mut self, input: I) {
-        if let Some(ctrl) = self.control_config.get(&input) {
-            self.pressed_controls.insert(ctrl.clone());
-        }
+        self.queue_event(input, true);
+    }
+
+    /// Call this function when your game engine gives you a `KeyDown` event from a particular device, like a
+    /// game controller, by the ID the engine gives it. This lets the device's
+    /// [latency offset](Self::set_latency_offset) be taken into account.
+    pub fn on_device_input_down(&mut self, device: u32, input: I) {
+        self.queue_event_from(input, true, Some(device));
     }
 
     /// Call this function when your game engine gives you a `KeyUp` event.
+    ///
+    /// The event is queued up and processed in order at the next [`update`](Self::update).
     pub fn on_input_up(&mut self, input: I) {
-        if let Some(ctrl) = self.control_config.get(&input) {
-            self.pressed_controls.remove(ctrl);
+        self.queue_event(input, false);
+    }
+
+    /// Call this function when your game engine gives you an input event, with `pressed` being `true` for
+    /// a `KeyDown` event and `false` for a `KeyUp` event.
+    ///
+    /// The event is queued up and processed in order at the next [`update`](Self::update).
+    pub fn on_input_event(&mut self, input: I, pressed: bool) {
+        self.queue_event(input, pressed);
+    }
+
+    /// Queue up the presses and releases from a frame of recorded inputs, like they came from your game engine.
+    /// Call this before each update with each item from
+    /// [`Recording::frame_changes`](crate::Recording::frame_changes).
+    pub fn on_recorded_change(&mut self, change: Option<&RecordedChange<I>>) {
+        for input in change.into_iter().flat_map(|change| change.released.iter()) {
+            self.on_input_up(input.clone());
+        }
+        for input in change.into_iter().flat_map(|change| change.pressed.iter()) {
+            self.on_input_down(input.clone());
+        }
+    }
+
+    /// Call this function when your game engine gives you an input event for one of several windows, with
+    /// `window` being whatever ID your engine gives the window.
+    ///
+    /// If another window has [focus](Self::set_focused_window), presses are ignored, so clicking on a tool
+    /// window in the background doesn't do anything in the game. Releases always go through, so nothing
+    /// gets stuck down. If no window has focus, this is the same as [`on_input_event`](Self::on_input_event).
+    ///
+    /// ```rust
+    /// # use puppetmaster::EventInputHandler;
+    /// const GAME: u64 = 1;
+    /// const PALETTE: u64 = 2;
+    ///
+    /// let mut input_handler = EventInputHandler::new_with_controls(vec![("lmb", "shoot")]);
+    /// input_handler.set_focused_window(Some(GAME));
+    ///
+    /// input_handler.on_window_input_event(PALETTE, "lmb", true);
+    /// input_handler.update();
+    /// assert!(input_handler.up("shoot"));
+    ///
+    /// input_handler.on_window_input_event(GAME, "lmb", true);
+    /// input_handler.update();
+    /// assert!(input_handler.clicked("shoot"));
+    /// ```
+    pub fn on_window_input_event(&mut self, window: u64, input: I, pressed: bool) {
+        if pressed && self.focused_window.is_some_and(|focused| focused != window) {
+            return;
+        }
+        self.queue_event(input, pressed);
+    }
+
+    /// Tell the handler which window has focus, for games with several windows, or `None` if none of them do.
+    /// Input events tagged with a window ID go through [`on_window_input_event`](Self::on_window_input_event).
+    ///
+    /// When the focus moves, everything is released, like with [`on_focus_lost`](Self::on_focus_lost).
+    pub fn set_focused_window(&mut self, window: Option<u64>) {
+        if self.focused_window != window {
+            self.focused_window = window;
+            self.clear_inputs();
+        }
+    }
+
+    /// Get the window that has focus, if the handler has been told.
+    pub fn focused_window(&self) -> Option<u64> {
+        self.focused_window
+    }
+
+    /// Feed the handler a whole batch of input events at once, with `true` being a press and `false` being
+    /// a release. This is handy if your game engine gives you a list of events each frame.
+    ///
+    /// The events are queued up and processed in order at the next [`update`](Self::update).
+    pub fn extend_events(&mut self, events: impl IntoIterator<Item = (I, bool)>) {
+        for (input, pressed) in events {
+            self.queue_event(input, pressed);
+        }
+    }
+
+    fn queue_event(&mut self, input: I, pressed: bool) {
+        self.queue_event_from(input, pressed, None);
+    }
+
+    fn queue_event_from(&mut self, input: I, pressed: bool, device: Option<u32>) {
+        // High polling rate devices can report the same button state thousands of times a frame.
+        // Back-to-back repeats of the same event don't change anything unless repeats are being surfaced,
+        // so don't let them pile up.
+        if self.repeat_policy == RepeatPolicy::Ignore
+            && self
+                .event_queue
+                .last()
+                .is_some_and(|(last, last_pressed)| *last == input && *last_pressed == pressed)
+        {
+            return;
+        }
+        if pressed && (self.latency.is_some() || self.press_stamps.is_some()) {
+            let now = Instant::now();
+            if let Some(latency) = &mut self.latency {
+                latency.queued(input.clone(), now);
+            }
+            if let Some(stamps) = &mut self.press_stamps {
+                let offset = device
+                    .and_then(|device| self.device_offsets.get(&device))
+                    .copied()
+                    .unwrap_or_default();
+                stamps
+                    .entry(input.clone())
+                    .or_insert(now.checked_sub(offset).unwrap_or(now));
+            }
+        }
+        self.event_queue.push((input, pressed));
+    }
+
+    /// Set how far behind a device's events arrive, like 40 milliseconds for a laggy Bluetooth controller, or
+    /// pass `None` to clear it. Devices are identified by the ID the engine gives them, as passed to
+    /// [`on_device_input_down`](Self::on_device_input_down).
+    ///
+    /// In [real-time mode](Self::set_real_time), presses from the device are stamped this much earlier than
+    /// they came in, so [`pressed_at`](Controls::pressed_at) and [`press_duration`](Controls::press_duration) are fair
+    /// to every player. This is what rhythm judgments should be made with in games where players are on
+    /// different kinds of controller. Frame counts aren't changed.
+    ///
+    /// ```rust
+    /// # use std::time::{Duration, Instant};
+    /// # use puppetmaster::EventInputHandler;
+    /// let mut input_handler = EventInputHandler::new_with_controls(vec![('a', "hit")]);
+    /// input_handler.set_real_time(true);
+    /// input_handler.set_latency_offset(1, Some(Duration::from_millis(40)));
+    ///
+    /// let reported = Instant::now();
+    /// input_handler.on_device_input_down(1, 'a');
+    /// input_handler.update();
+    /// assert!(input_handler.pressed_at("hit").unwrap() < reported);
+    /// ```
+    pub fn set_latency_offset(&mut self, device: u32, offset: Option<Duration>) {
+        match offset {
+            Some(offset) => self.device_offsets.insert(device, offset),
+            None => self.device_offsets.remove(&device),
+        };
+    }
+
+    /// Get how far behind a device's events arrive. This is zero unless it's been set.
+    pub fn latency_offset(&self, device: u32) -> Duration {
+        self.device_offsets
+            .get(&device)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Turn real-time mode on or off. This is off by default.
+    ///
+    /// In real-time mode, the handler notes the time each press event comes in, so
+    /// [`pressed_at`](Controls::pressed_at) and [`press_duration`](Controls::press_duration) are exact no matter how
+    /// long it is until the next update. Otherwise they're measured from the update that processes the press,
+    /// which is close enough for games that update every frame.
+    ///
+    /// Events from a [`sender`](Self::sender) are still measured from the update.
+    pub fn set_real_time(&mut self, real_time: bool) {
+        match (real_time, &self.press_stamps) {
+            (true, None) => self.press_stamps = Some(AHashMap::new()),
+            (false, _) => self.press_stamps = None,
+            (true, Some(_)) => {}
+        }
+    }
+
+    /// Return if the handler is in real-time mode.
+    pub fn is_real_time(&self) -> bool {
+        self.press_stamps.is_some()
+    }
+
+    /// Start or stop measuring how long presses take to get through the handler. This is off by default.
+    /// See [`LatencyStats`].
+    ///
+    /// Events from a [`sender`](Self::sender) can't be timed as they come in, so they're measured from
+    /// the start of the update that picks them up. Turning this off throws away the stats.
+    pub fn set_latency_tracking(&mut self, track: bool) {
+        match (track, &self.latency) {
+            (true, None) => self.latency = Some(LatencyTracker::default()),
+            (false, _) => self.latency = None,
+            (true, Some(_)) => {}
+        }
+    }
+
+    /// Return if the handler is measuring latency.
+    pub fn tracks_latency(&self) -> bool {
+        self.latency.is_some()
+    }
+
+    /// Get how long the control's presses have taken to get through the handler, if latency is being
+    /// [measured](Self::set_latency_tracking) and the control has been pressed since.
+    pub fn latency_stats(&self, ctrl: C) -> Option<LatencyStats> {
+        self.latency
+            .as_ref()
+            .and_then(|latency| latency.stats(&ctrl))
+    }
+
+    /// Throw away the latency stats measured so far, and start over.
+    pub fn reset_latency_stats(&mut self) {
+        if let Some(latency) = &mut self.latency {
+            latency.reset_stats();
+        }
+    }
+
+    /// Get a handle that can feed input events into this handler from another thread.
+    ///
+    /// This is useful if your game engine delivers input on a different thread than your game loop.
+    /// Events sent through the handle are queued up and processed at the next [`update`](Self::update),
+    /// after the events given to this handler directly.
+    ///
+    /// Senders only ever feed the handler they came from. A clone of the handler doesn't get their events;
+    /// ask the clone for a sender of its own.
+    ///
+    /// ```rust
+    /// # use puppetmaster::EventInputHandler;
+    /// let mut input_handler = EventInputHandler::new_with_controls(vec![('w', "up")]);
+    /// let sender = input_handler.sender();
+    /// let mut clone = input_handler.clone();
+    ///
+    /// sender.on_input_down('w');
+    /// input_handler.update();
+    /// clone.update();
+    /// assert!(input_handler.clicked("up"));
+    /// assert!(clone.up("up"));
+    ///
+    /// clone.sender().on_input_down('w');
+    /// clone.update();
+    /// assert!(clone.clicked("up"));
+    /// ```
+    pub fn sender(&mut self) -> EventSender<I> {
+        let queue = self.shared_queue.get_or_insert_with(Default::default);
+        EventSender {
+            queue: Arc::clone(queue),
         }
     }
 
     /// Manually unpress all inputs. This is like calling [`on_input_up`](Self::on_input_up) for every possible `I`.
     ///
+    /// This also throws away any events that are queued up but haven't been processed yet.
+    ///
     /// Note you should *not* have to call this at the beginning of your loop. (In fact, if you do,
     /// your inputs will never be pressed.)
     pub fn clear_inputs(&mut self) {
-        self.pressed_controls.clear();
+        self.controls.changed();
+        if let Some(queue) = &self.shared_queue {
+            queue.lock().unwrap_or_else(PoisonError::into_inner).clear();
+        }
+        self.event_queue.clear();
+        self.pressed_inputs.clear();
+        self.last_reported.clear();
+        if let Some(latency) = &mut self.latency {
+            latency.clear_queued();
+        }
+        if let Some(stamps) = &mut self.press_stamps {
+            stamps.clear();
+        }
         // The input times will be cleared in the `update` method.
+        self.controls.states.release_all();
+    }
+
+    /// Call this when the window loses focus. This releases everything, like
+    /// [`clear_inputs`](Self::clear_inputs).
+    ///
+    /// Most platforms don't tell the game about keys released while it's in the background, so without this
+    /// anything held when the player alt-tabbed away stays stuck down.
+    pub fn on_focus_lost(&mut self) {
+        self.clear_inputs();
     }
 
     /// Update the input handler. You MUST CALL THIS FIRST THING in your game loop.
     /// Otherwise things won't get updated correctly.
-    pub fn update(&mut self) {
-        for control in self.control_config.values() {
-            let pressed = self.pressed_controls.contains(control);
-            if pressed {
-                *self.control_time.entry(control.clone()).or_default() += 1;
-            } else {
-                self.control_time.insert(control.clone(), 0);
+    ///
+    /// This processes all the events queued up since the last update, in the order they came in.
+    /// If an input was pressed and released since the last update, it counts as pressed for this frame
+    /// and gets released at the next update, so quick taps (and mouse wheel ticks) aren't lost.
+    ///
+    /// Gaming mice can report thousands of times a frame. Pointer motion only keeps the latest position and
+    /// scrolling is added up as it comes in, and repeated reports of the same button state are dropped as
+    /// they're queued, so the work done here depends on how many times buttons actually changed, not on the
+    /// polling rate. To put a hard cap on it, see [`set_max_events_per_update`](Self::set_max_events_per_update).
+    ///
+    /// ```rust
+    /// # use puppetmaster::EventInputHandler;
+    /// let mut input_handler = EventInputHandler::new_with_controls(vec![("lmb", "shoot")]);
+    /// for frame in 0..60 {
+    ///     // Thousands of reports from a high polling rate mouse, all in one frame
+    ///     for i in 0..8000 {
+    ///         input_handler.set_pointer(Some((i as f32, frame as f32)));
+    ///         input_handler.add_scroll((0.0, 0.001));
+    ///         input_handler.on_input_event("lmb", frame % 2 == 0);
+    ///     }
+    ///     input_handler.update();
+    ///     assert_eq!(input_handler.clicked("shoot"), frame % 2 == 0);
+    /// }
+    /// assert_eq!(input_handler.pointer_position(), Some((7999.0, 59.0)));
+    /// ```
+    ///
+    /// Returns a summary of what changed, so you can skip work on frames where nothing did.
+    pub fn update(&mut self) -> UpdateSummary {
+        self.controls.changed();
+        if let Some(queue) = &self.shared_queue {
+            let mut queue = queue.lock().unwrap_or_else(PoisonError::into_inner);
+            self.event_queue.append(&mut queue);
+        }
+        if self.controls.states.suppressing() {
+            // Don't let anything that came in while the window was refocusing through
+            self.event_queue.clear();
+        }
+        self.frame_count += 1;
+        let now = self.release_timeout.map(|_| Instant::now());
+        let started = self.latency.as_ref().map(|_| Instant::now());
+
+        self.controls.raw_inputs.new_frame();
+        self.controls.axes.update();
+        let mut repeated_controls = Vec::new();
+        let mut new_presses = AHashSet::new();
+        let mut deferred_releases = Vec::new();
+        let count = self
+            .max_events_per_update
+            .map_or(self.event_queue.len(), |max| {
+                max.min(self.event_queue.len())
+            });
+        for (input, pressed) in self.event_queue.drain(..count) {
+            if let Some(now) = now {
+                if pressed {
+                    match self.last_reported.get_mut(&input) {
+                        Some(reported) => *reported = (self.frame_count, now),
+                        None => {
+                            self.last_reported
+                                .insert(input.clone(), (self.frame_count, now));
+                        }
+                    }
+                } else {
+                    self.last_reported.remove(&input);
+                }
+            }
+
+            if !pressed {
+                if new_presses.contains(&input) {
+                    // The input was pressed and released within one frame. Keep it down for this frame
+                    // so the press isn't lost, and release it next frame.
+                    if !deferred_releases.contains(&input) {
+                        deferred_releases.push(input);
+                    }
+                } else {
+                    self.pressed_inputs.remove(&input);
+                }
+            } else if let Some(idx) = deferred_releases.iter().position(|i| *i == input) {
+                // It got pressed again in the same frame, so it's just down.
+                deferred_releases.swap_remove(idx);
+            } else if !self.pressed_inputs.contains(&input) {
+                let mapped = self
+                    .controls
+                    .control_config
+                    .contains_key(self.controls.input_remap.get(&input).unwrap_or(&input));
+                self.controls
+                    .raw_inputs
+                    .pressed(input.clone(), self.frame_count, mapped);
+                new_presses.insert(input.clone());
+                self.pressed_inputs.insert(input);
+            } else if self.repeat_policy == RepeatPolicy::Surface {
+                // The input is already down, so this must be the OS auto-repeating it.
+                let remapped = self.controls.input_remap.get(&input).unwrap_or(&input);
+                if let Some(ctrl) = self.controls.control_config.get(remapped) {
+                    if !repeated_controls.contains(ctrl) {
+                        repeated_controls.push(ctrl.clone());
+                    }
+                }
+            }
+        }
+        // These go ahead of any events left over for the next update, since they happened first.
+        self.event_queue.splice(
+            0..0,
+            deferred_releases.into_iter().map(|input| (input, false)),
+        );
+
+        if let (Some(timeout), Some(now)) = (self.release_timeout, now) {
+            let frame = self.frame_count;
+            let last_reported = &mut self.last_reported;
+            self.pressed_inputs.retain(|input| {
+                // Inputs that were held before the timeout was turned on count as reported just now.
+                let (last_frame, last_time) =
+                    *last_reported.entry(input.clone()).or_insert((frame, now));
+                let expired = match timeout {
+                    ReleaseTimeout::Frames(frames) => {
+                        frame.saturating_sub(last_frame) >= frames as u64
+                    }
+                    ReleaseTimeout::Duration(duration) => now - last_time >= duration,
+                };
+                if expired {
+                    last_reported.remove(input);
+                }
+                !expired
+            });
+        }
+
+        // Controls are looked up now instead of when the event came in, so rebinding takes
+        // effect even for inputs that are already held.
+        let filtered = self.controls.filters.apply(&self.pressed_inputs);
+        let held_inputs = match &filtered {
+            Some(filtered) => Either::Left(filtered.iter()),
+            None => Either::Right(self.pressed_inputs.iter()),
+        };
+        let held_controls = held_inputs
+            .filter_map(|input| {
+                self.controls
+                    .control_config
+                    .get(self.controls.input_remap.get(input).unwrap_or(input))
+            })
+            .collect_vec();
+        self.controls
+            .states
+            .count_inputs(held_controls.iter().copied());
+        let pressed_controls: AHashSet<&C> = held_controls.into_iter().collect();
+        if let Some(stamps) = &mut self.press_stamps {
+            for (input, stamp) in stamps.drain() {
+                let remapped = self.controls.input_remap.get(&input).unwrap_or(&input);
+                if let Some(ctrl) = self.controls.control_config.get(remapped) {
+                    self.controls.states.stamp_press(ctrl.clone(), stamp);
+                }
+            }
+        }
+        // Several inputs can map to the same control; make sure each control only ticks once.
+        self.controls
+            .states
+            .tick(self.controls.control_config.values().unique(), |ctrl| {
+                pressed_controls.contains(ctrl)
+            });
+        for control in repeated_controls {
+            if self.down(control.clone()) {
+                self.controls
+                    .states
+                    .push_event(ControlEvent::Repeated(control));
+            }
+        }
+        if let (Some(latency), Some(started)) = (&mut self.latency, started) {
+            let now = Instant::now();
+            for input in new_presses.iter() {
+                // Presses from senders weren't timed as they came in, so they count from the start of the update
+                let queued_at = latency.take_queued(input, started);
+                let remapped = self.controls.input_remap.get(input).unwrap_or(input);
+                if let Some(ctrl) = self.controls.control_config.get(remapped) {
+                    if self.controls.states.clicked(ctrl) {
+                        latency.record(ctrl.clone(), now - queued_at);
+                    }
+                }
             }
+            latency.clear_queued();
+        }
+        self.controls.states.notify_listeners();
+        self.controls.states.summary()
+    }
+
+    /// Return what happened to the controls during the last [`update`](Self::update).
+    pub fn events(&self) -> &[ControlEvent<C>] {
+        self.controls.states.events()
+    }
+
+    /// Return if the OS sent a key-repeat for this control this frame.
+    ///
+    /// This is always `false` unless the repeat policy is [`RepeatPolicy::Surface`].
+    pub fn repeated(&self, ctrl: C) -> bool {
+        self.controls
+            .states
+            .events()
+            .iter()
+            .any(|evt| matches!(evt, ControlEvent::Repeated(c) if *c == ctrl))
+    }
+
+    /// Limit how many queued events one [`update`](Self::update) works through, or pass `None` to work
+    /// through all of them. This is off by default.
+    ///
+    /// Events past the limit wait in the queue for the next update, in order, so a flood of events (like
+    /// a stuck or malicious device) can't make one frame take arbitrarily long; instead, the backlog
+    /// drains at the limit's rate. Quick taps still work, since a press and release within one update
+    /// count as a press that's released at the next one.
+    ///
+    /// ```rust
+    /// # use puppetmaster::EventInputHandler;
+    /// let mut input_handler = EventInputHandler::new_with_controls(vec![("a", "fire")]);
+    /// input_handler.set_max_events_per_update(Some(2));
+    /// for _ in 0..3 {
+    ///     input_handler.on_input_down("a");
+    ///     input_handler.on_input_up("a");
+    /// }
+    /// assert_eq!(input_handler.queued_events(), 6);
+    ///
+    /// input_handler.update();
+    /// assert!(input_handler.clicked("fire"));
+    /// // The tap's release waits for the next update, ahead of the other 4 events.
+    /// assert_eq!(input_handler.queued_events(), 5);
+    /// ```
+    pub fn set_max_events_per_update(&mut self, max: Option<usize>) {
+        self.max_events_per_update = max;
+    }
+
+    /// Get the most events one update works through, if there's a limit.
+    pub fn max_events_per_update(&self) -> Option<usize> {
+        self.max_events_per_update
+    }
+
+    /// Get how many events are waiting for the next update. This doesn't count events waiting in
+    /// [`EventSender`]s, which are only picked up at the update.
+    pub fn queued_events(&self) -> usize {
+        self.event_queue.len()
+    }
+
+    /// Set what happens to key-down events for inputs that are already held down.
+    pub fn set_repeat_policy(&mut self, policy: RepeatPolicy) {
+        self.repeat_policy = policy;
+    }
+
+    /// Get what happens to key-down events for inputs that are already held down.
+    pub fn repeat_policy(&self) -> RepeatPolicy {
+        self.repeat_policy
+    }
+
+    /// Make the handler release held inputs by itself if they aren't reported down again within the timeout.
+    ///
+    /// Use this if your game engine only reports key-down events and never key-up events, like many terminal
+    /// backends do. Such backends usually repeat the key-down event while the key is held, so set the timeout
+    /// a little longer than the OS's key-repeat delay to keep [`down`](Controls::down) and
+    /// [`press_time`](Controls::press_time) meaningful.
+    ///
+    /// Pass `None` to turn this off (the default) and only release inputs on key-up events.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{EventInputHandler, ReleaseTimeout};
+    /// let mut input_handler = EventInputHandler::new_with_controls(vec![('h', "left")]);
+    /// input_handler.set_release_timeout(Some(ReleaseTimeout::Frames(2)));
+    ///
+    /// input_handler.on_input_down('h');
+    /// input_handler.update();
+    /// input_handler.update();
+    /// assert!(input_handler.down("left"));
+    ///
+    /// // 'h' hasn't been reported for 2 frames now ...
+    /// input_handler.update();
+    /// assert!(input_handler.up("left"));
+    /// ```
+    pub fn set_release_timeout(&mut self, timeout: Option<ReleaseTimeout>) {
+        self.release_timeout = timeout;
+        if timeout.is_none() {
+            self.last_reported.clear();
         }
     }
 
-    /// Return the number of frames the given control has been pressed for
-    pub fn press_time(&self, ctrl: C) -> u32 {
-        self.control_time.get(&ctrl).copied().unwrap_or_default()
+    /// Get how long held inputs can go without being reported before they're released, if this is turned on.
+    pub fn release_timeout(&self) -> Option<ReleaseTimeout> {
+        self.release_timeout
     }
 
-    /// Return if this control is held down (ie, the corresponding input has been pressed for 1 or more frames).
-    pub fn down(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) >= 1
+    /// Start or stop recording inputs that are pressed but aren't mapped to any control. This is off by default.
+    pub fn set_track_unmapped(&mut self, track: bool) {
+        self.controls.raw_inputs.set_track_unmapped(track);
     }
 
-    /// Return if this control is up.
-    pub fn up(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) == 0
+    /// Return if the handler is recording unmapped inputs.
+    pub fn tracks_unmapped(&self) -> bool {
+        self.controls.raw_inputs.tracks_unmapped()
     }
 
-    /// Return if this control was *clicked* down this frame (ie, the corresponding input was *just* pressed this frame).
-    pub fn clicked(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) == 1
+    /// Get the inputs that were pressed this frame but aren't mapped to any control, if
+    /// [tracking](Self::set_track_unmapped) is on.
+    ///
+    /// Use this to capture a new binding, or to tell the player they pressed something that doesn't do
+    /// anything.
+    pub fn unmapped_inputs(&self) -> &[I] {
+        self.controls.raw_inputs.unmapped()
+    }
+
+    /// Save the bindings, settings, and which inputs are held down, so the handler can be restored after a
+    /// hot reload. See [`SavedState`] for what's included.
+    pub fn save_state(&self) -> SavedState<I, C> {
+        SavedState {
+            version: SavedState::<I, C>::VERSION,
+            bindings: self
+                .controls
+                .control_config
+                .iter()
+                .map(|(input, ctrl)| (input.clone(), ctrl.clone()))
+                .collect(),
+            remap: self
+                .controls
+                .input_remap
+                .iter()
+                .map(|(from, to)| (from.clone(), to.clone()))
+                .collect(),
+            timing: *self.controls.states.timing(),
+            control_settings: self.controls.states.control_settings(),
+            axes: self.controls.axes.save(),
+            frame: self.controls.states.frame(),
+            held_inputs: self.pressed_inputs.iter().cloned().collect(),
+            pressed_controls: self.controls.states.pressed_times(),
+            toggled_on: self.controls.states.toggled_on(),
+        }
+    }
+
+    /// Restore a state saved with [`save_state`](Self::save_state), replacing the bindings, settings, and
+    /// what's held down.
+    ///
+    /// Returns `false` and leaves the handler alone if the state was saved in a different
+    /// [version](SavedState::VERSION) of the format.
+    pub fn restore_state(&mut self, state: SavedState<I, C>) -> bool {
+        if state.version != SavedState::<I, C>::VERSION {
+            return false;
+        }
+        self.controls.changed();
+        self.controls.control_config = state.bindings.into_iter().collect();
+        self.controls.input_remap = state.remap.into_iter().collect();
+        self.controls.states.set_timing(state.timing);
+        self.controls
+            .states
+            .restore_control_settings(state.control_settings);
+        self.controls.axes.restore(state.axes);
+        self.controls
+            .states
+            .restore(state.frame, state.pressed_controls, state.toggled_on);
+        self.event_queue.clear();
+        if let Some(stamps) = &mut self.press_stamps {
+            stamps.clear();
+        }
+        self.controls.raw_inputs.forget_last();
+        self.pressed_inputs = state.held_inputs.into_iter().collect();
+        self.frame_count = state.frame;
+        // Held inputs count as last reported on the frame being restored to, not some later one.
+        self.last_reported.clear();
+        if self.release_timeout.is_some() {
+            let now = Instant::now();
+            for input in self.pressed_inputs.iter() {
+                self.last_reported.insert(input.clone(), (state.frame, now));
+            }
+        }
+        true
+    }
+
+    /// Record the inputs held down this frame, with the bindings and everything else not applied yet. Call
+    /// this once per frame, after updating. See [`Recorder`] for the difference between recording inputs and
+    /// controls.
+    pub fn record_inputs(&self, recorder: &mut Recorder<I>) {
+        recorder.record(self.pressed_inputs.iter().cloned());
+    }
+
+    /// Update the handler from recorded controls instead of its inputs, holding down exactly the controls given.
+    /// Call this instead of `update`, with a frame from a recording made by
+    /// [`record_controls`](Controls::record_controls).
+    ///
+    /// The controls were already worked out when they were recorded, so toggles, debouncing, long presses,
+    /// and everything else that turns inputs into controls are skipped.
+    ///
+    /// Input events stay queued up until the next real update.
+    pub fn replay_controls(&mut self, held: impl IntoIterator<Item = C>) -> UpdateSummary {
+        self.controls.changed();
+        self.frame_count += 1;
+        self.controls.states.tick_replay(held);
+        self.controls.states.notify_listeners();
+        self.controls.states.summary()
+    }
+
+    /// Call this function when your game engine gives you an event for an axis input moving, like a stick
+    /// or trigger, with its new value from -1 to 1 (or 0 to 1 for triggers). The handler keeps the value
+    /// until the input moves again.
+    ///
+    /// The value is queued up like button events are, and takes effect at the next update.
+    pub fn on_axis_input(&mut self, input: I, value: f32) {
+        self.controls.axes.set_input(input, value);
     }
 }
 
+/// The clone starts with everything the handler has, except events sent through [senders](Self::sender):
+/// those keep going to the original handler, and the clone gets a queue of its own if it's asked for a
+/// sender.
+impl<I: Clone, C: Clone> Clone for EventInputHandler<I, C> {
+    fn clone(&self) -> Self {
+        Self {
+            controls: self.controls.clone(),
+            event_queue: self.event_queue.clone(),
+            max_events_per_update: self.max_events_per_update,
+            pressed_inputs: self.pressed_inputs.clone(),
+            shared_queue: None,
+            repeat_policy: self.repeat_policy,
+            release_timeout: self.release_timeout,
+            last_reported: self.last_reported.clone(),
+            frame_count: self.frame_count,
+            focused_window: self.focused_window,
+            press_stamps: self.press_stamps.clone(),
+            device_offsets: self.device_offsets.clone(),
+            latency: self.latency.clone(),
+        }
+    }
+}
+
+impl_input_handler!(EventInputHandler);
+
 impl<I, C> Default for EventInputHandler<I, C> {
     fn default() -> Self {
         Self {
-            control_config: AHashMap::new(),
-            control_time: AHashMap::new(),
-            pressed_controls: AHashSet::new(),
+            controls: Controls::default(),
+            event_queue: Vec::new(),
+            max_events_per_update: None,
+            pressed_inputs: AHashSet::new(),
+            shared_queue: None,
+            repeat_policy: RepeatPolicy::default(),
+            release_timeout: None,
+            last_reported: AHashMap::new(),
+            frame_count: 0,
+            focused_window: None,
+            press_stamps: None,
+            device_offsets: AHashMap::new(),
+            latency: None,
+        }
+    }
+}
+
+/// A handle for feeding input events into an [`EventInputHandler`], possibly from another thread.
+///
+/// Get one with [`EventInputHandler::sender`]. The events are processed at the handler's next `update`.
+///
+/// ```rust
+/// # use puppetmaster::EventInputHandler;
+/// let mut input_handler = EventInputHandler::new_with_controls(vec![('w', "up")]);
+/// let sender = input_handler.sender();
+///
+/// std::thread::spawn(move || sender.on_input_down('w'))
+///     .join()
+///     .unwrap();
+///
+/// input_handler.update();
+/// assert!(input_handler.clicked("up"));
+/// ```
+#[derive(Debug)]
+pub struct EventSender<I> {
+    queue: SharedQueue<I>,
+}
+
+impl<I> EventSender<I> {
+    /// Call this function when your game engine gives you a `KeyDown` event.
+    pub fn on_input_down(&self, input: I) {
+        self.on_input_event(input, true);
+    }
+
+    /// Call this function when your game engine gives you a `KeyUp` event.
+    pub fn on_input_up(&self, input: I) {
+        self.on_input_event(input, false);
+    }
+
+    /// Call this function when your game engine gives you an input event, with `pressed` being `true` for
+    /// a `KeyDown` event and `false` for a `KeyUp` event.
+    pub fn on_input_event(&self, input: I, pressed: bool) {
+        self.queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((input, pressed));
+    }
+}
+
+impl<I> Clone for EventSender<I> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: Arc::clone(&self.queue),
         }
     }
 }
@@ -0,0 +1,337 @@
+//! Writing each frame's control state out to CSV or JSON Lines, for charting input patterns in a
+//! spreadsheet or notebook, and sending it live to other programs with an [`OverlaySender`].
+//!
+//! Enable this with the `export` feature.
+//!
+//! Each frame is one flat row: the frame number, then each control's press time (0 when it's up) and
+//! whether it was clicked. Rows are written as they come, so wrap the writer in a `BufWriter` and a long
+//! session never has to fit in memory.
+//!
+//! ```rust
+//! # use puppetmaster::{PollingInputHandler, export::{ExportFormat, FrameExporter}};
+//! let mut input_handler = PollingInputHandler::new_with_controls(vec![("space", "jump"), ("d", "right")]);
+//! let columns = [("jump", "jump"), ("right", "right")];
+//! let mut exporter = FrameExporter::new(Vec::new(), ExportFormat::Csv, columns).unwrap();
+//!
+//! for held in [vec!["d"], vec!["d", "space"]] {
+//!     input_handler.update(held);
+//!     exporter
+//!         .export_snapshot(input_handler.frame(), &input_handler.snapshot())
+//!         .unwrap();
+//! }
+//!
+//! let csv = String::from_utf8(exporter.into_inner()).unwrap();
+//! assert_eq!(
+//!     csv,
+//!     "frame,jump,jump_clicked,right,right_clicked\n1,0,false,1,true\n2,1,true,2,false\n",
+//! );
+//! ```
+
+use std::{
+    fmt,
+    hash::Hash,
+    io::{self, Write},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+use crate::{ControlEvent, FrameNumber, InputSnapshot, Recording};
+
+/// The format a [`FrameExporter`] writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    /// Comma-separated values, with a header row naming the columns.
+    Csv,
+    /// One JSON object per line, with the same fields as the CSV columns.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{PollingInputHandler, export::{ExportFormat, FrameExporter}};
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("e", "use")]);
+    /// let mut exporter =
+    ///     FrameExporter::new(Vec::new(), ExportFormat::JsonLines, [("use", "say \"use\"")]).unwrap();
+    ///
+    /// input_handler.update(vec!["e"]);
+    /// exporter
+    ///     .export_snapshot(input_handler.frame(), &input_handler.snapshot())
+    ///     .unwrap();
+    ///
+    /// let json = String::from_utf8(exporter.into_inner()).unwrap();
+    /// assert_eq!(
+    ///     json,
+    ///     "{\"frame\":1,\"say \\\"use\\\"\":1,\"say \\\"use\\\"_clicked\":true}\n",
+    /// );
+    /// ```
+    JsonLines,
+}
+
+/// Writes each frame's control state to a writer as it happens. See the [module docs](self).
+#[derive(Debug)]
+pub struct FrameExporter<W, C> {
+    writer: W,
+    format: ExportFormat,
+    /// The controls to write, and what to call them
+    columns: Vec<(C, String)>,
+}
+
+impl<W: Write, C: Hash + Eq + Clone> FrameExporter<W, C> {
+    /// Create an exporter writing the given controls, each with the name to use for its columns. For CSV,
+    /// this writes the header row right away.
+    pub fn new(
+        writer: W,
+        format: ExportFormat,
+        columns: impl IntoIterator<Item = (C, impl Into<String>)>,
+    ) -> io::Result<Self> {
+        let mut exporter = Self {
+            writer,
+            format,
+            columns: columns
+                .into_iter()
+                .map(|(ctrl, name)| (ctrl, name.into()))
+                .collect(),
+        };
+        if format == ExportFormat::Csv {
+            let mut header = String::from("frame");
+            for (_, name) in exporter.columns.iter() {
+                let clicked = format!("{}_clicked", name);
+                header.push(',');
+                header.push_str(&csv_field(name));
+                header.push(',');
+                header.push_str(&csv_field(&clicked));
+            }
+            writeln!(exporter.writer, "{}", header)?;
+        }
+        Ok(exporter)
+    }
+
+    /// Write a row for a frame, from a snapshot of a handler taken after updating.
+    pub fn export_snapshot(
+        &mut self,
+        frame: FrameNumber,
+        snapshot: &InputSnapshot<C>,
+    ) -> io::Result<()> {
+        let row = self
+            .columns
+            .iter()
+            .map(|(ctrl, _)| {
+                (
+                    snapshot.press_time(ctrl.clone()),
+                    snapshot.clicked(ctrl.clone()),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.write_row(frame, &row)
+    }
+
+    /// Write a row for every frame of a recording of controls, numbering the frames from 1.
+    ///
+    /// Press times are counted from the start of the recording, so controls held down at its start count as
+    /// clicked on its first frame.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{Recorder, export::{ExportFormat, FrameExporter}};
+    /// let mut recorder = Recorder::new();
+    /// for held in [vec!["dash"], vec!["dash"], vec![], vec!["dash"]] {
+    ///     recorder.record(held);
+    /// }
+    /// let recording = recorder.finish().unwrap();
+    ///
+    /// let mut exporter =
+    ///     FrameExporter::new(Vec::new(), ExportFormat::Csv, [("dash", "dash, fast")]).unwrap();
+    /// exporter.export_recording(&recording).unwrap();
+    ///
+    /// let csv = String::from_utf8(exporter.into_inner()).unwrap();
+    /// assert_eq!(
+    ///     csv,
+    ///     "frame,\"dash, fast\",\"dash, fast_clicked\"\n1,1,true\n2,2,false\n3,0,false\n4,1,true\n",
+    /// );
+    /// ```
+    pub fn export_recording(&mut self, recording: &Recording<C>) -> io::Result<()> {
+        let mut times = vec![0; self.columns.len()];
+        for (frame, held) in recording.held_frames().enumerate() {
+            for ((ctrl, _), time) in self.columns.iter().zip(times.iter_mut()) {
+                *time = if held.contains(ctrl) { *time + 1 } else { 0 };
+            }
+            let row = times
+                .iter()
+                .map(|&time| (time, time == 1))
+                .collect::<Vec<_>>();
+            self.write_row(frame as FrameNumber + 1, &row)?;
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, frame: FrameNumber, row: &[(u32, bool)]) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => {
+                write!(self.writer, "{}", frame)?;
+                for (time, clicked) in row {
+                    write!(self.writer, ",{},{}", time, clicked)?;
+                }
+            }
+            ExportFormat::JsonLines => {
+                write!(self.writer, "{{\"frame\":{}", frame)?;
+                for ((_, name), (time, clicked)) in self.columns.iter().zip(row) {
+                    let clicked_name = format!("{}_clicked", name);
+                    write!(
+                        self.writer,
+                        ",{}:{},{}:{}",
+                        json_string(name),
+                        time,
+                        json_string(&clicked_name),
+                        clicked
+                    )?;
+                }
+                write!(self.writer, "}}")?;
+            }
+        }
+        writeln!(self.writer)
+    }
+
+    /// Flush the writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Stop exporting, and get the writer back.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Sends each frame's control events and held controls to another program as they happen, like a stream
+/// input display or a coaching tool.
+///
+/// Each frame is one line of JSON, so it's easy to read in any language:
+///
+/// ```json
+/// {"frame":12,"events":[{"pressed":"jump"},{"released":"left"}],"held":{"jump":1,"right":30}}
+/// ```
+///
+/// `events` has the frame's [`ControlEvent`]s in order, each as `pressed`, `released` or `repeated`. `held` has
+/// every control that's down, and how many frames it's been down for. Send it over anything you can write
+/// to, like a pipe to a child process, or a TCP connection with [`connect`](OverlaySender::connect).
+///
+/// ```rust
+/// # use puppetmaster::{export::OverlaySender, EventInputHandler};
+/// let mut input_handler = EventInputHandler::new_with_controls(vec![("space", "jump")]);
+/// let mut sender = OverlaySender::new(Vec::new(), |ctrl: &&str| ctrl.to_string());
+///
+/// input_handler.on_input_down("space");
+/// input_handler.update();
+/// sender
+///     .send_frame(input_handler.frame(), input_handler.events(), &input_handler.snapshot())
+///     .unwrap();
+///
+/// let sent = String::from_utf8(sender.into_inner()).unwrap();
+/// assert_eq!(sent, "{\"frame\":1,\"events\":[{\"pressed\":\"jump\"}],\"held\":{\"jump\":1}}\n");
+/// ```
+pub struct OverlaySender<W, C> {
+    writer: W,
+    /// What to call each control
+    name: Box<dyn Fn(&C) -> String + Send>,
+}
+
+impl<C: Hash + Eq + Clone> OverlaySender<TcpStream, C> {
+    /// Connect to an overlay listening for TCP connections, like one on `127.0.0.1:4242`.
+    pub fn connect(
+        addr: impl ToSocketAddrs,
+        name: impl Fn(&C) -> String + Send + 'static,
+    ) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        // Frames are small and should arrive right away
+        stream.set_nodelay(true)?;
+        Ok(Self::new(stream, name))
+    }
+}
+
+impl<W: Write, C: Hash + Eq + Clone> OverlaySender<W, C> {
+    /// Create a sender writing to `writer`, calling each control what `name` says.
+    pub fn new(writer: W, name: impl Fn(&C) -> String + Send + 'static) -> Self {
+        Self {
+            writer,
+            name: Box::new(name),
+        }
+    }
+
+    /// Send one frame: its number, the events from the update, and a snapshot taken after it. This flushes
+    /// the writer, so the other end gets it right away.
+    ///
+    /// If the other end has gone away, this returns the error; the game can carry on without it.
+    pub fn send_frame(
+        &mut self,
+        frame: FrameNumber,
+        events: &[ControlEvent<C>],
+        snapshot: &InputSnapshot<C>,
+    ) -> io::Result<()> {
+        let mut line = format!("{{\"frame\":{},\"events\":[", frame);
+        for (i, event) in events.iter().enumerate() {
+            let (kind, ctrl) = match event {
+                ControlEvent::Pressed(ctrl) => ("pressed", ctrl),
+                ControlEvent::Released(ctrl) => ("released", ctrl),
+                ControlEvent::Repeated(ctrl) => ("repeated", ctrl),
+            };
+            if i > 0 {
+                line.push(',');
+            }
+            line.push_str(&format!(
+                "{{\"{}\":{}}}",
+                kind,
+                json_string(&(self.name)(ctrl))
+            ));
+        }
+        line.push_str("],\"held\":{");
+        for (i, ctrl) in snapshot.all_pressed().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            line.push_str(&format!(
+                "{}:{}",
+                json_string(&(self.name)(ctrl)),
+                snapshot.press_time(ctrl.clone())
+            ));
+        }
+        line.push_str("}}\n");
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Stop sending, and get the writer back.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: fmt::Debug, C> fmt::Debug for OverlaySender<W, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OverlaySender")
+            .field("writer", &self.writer)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Quote a CSV field if it needs it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Write a string as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
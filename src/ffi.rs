@@ -0,0 +1,195 @@
+//! A C-compatible interface, for embedding puppetmaster in games written in other languages.
+//!
+//! Enable this with the `ffi` feature. To get a library you can link against, build with
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`). The prototypes are in
+//! `include/puppetmaster.h`.
+//!
+//! This wraps an [`EventInputHandler`] behind an opaque pointer. Inputs and controls are both plain integers:
+//! use whatever numbering your engine has for keys and buttons, and number your controls however you like.
+//!
+//! ```c
+//! PuppetmasterHandler *controls = puppetmaster_new();
+//! puppetmaster_bind(controls, KEY_SPACE, CONTROL_JUMP);
+//!
+//! // When your engine gives you events ...
+//! puppetmaster_input_down(controls, KEY_SPACE);
+//!
+//! // At the top of your game loop ...
+//! puppetmaster_update(controls);
+//! if (puppetmaster_clicked(controls, CONTROL_JUMP)) {
+//!     // boing
+//! }
+//!
+//! puppetmaster_free(controls);
+//! ```
+//!
+//! Every function taking a handler pointer treats null as a handler with nothing pressed.
+
+use crate::EventInputHandler;
+
+/// An input handler, as seen from C. Only ever use it through a pointer.
+pub type PuppetmasterHandler = EventInputHandler<u32, u32>;
+
+/// Create a new handler with no control mapping. Free it with [`puppetmaster_free`].
+///
+/// ```rust
+/// # use puppetmaster::ffi::*;
+/// unsafe {
+///     let controls = puppetmaster_new();
+///     puppetmaster_bind(controls, 32, 0);
+///     puppetmaster_input_down(controls, 32);
+///     puppetmaster_update(controls);
+///     assert!(puppetmaster_clicked(controls, 0));
+///     puppetmaster_free(controls);
+/// }
+/// ```
+#[no_mangle]
+pub extern "C" fn puppetmaster_new() -> *mut PuppetmasterHandler {
+    Box::into_raw(Box::new(EventInputHandler::new()))
+}
+
+/// Free a handler made by [`puppetmaster_new`].
+///
+/// # Safety
+///
+/// `handler` must be null, or a pointer from [`puppetmaster_new`] that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_free(handler: *mut PuppetmasterHandler) {
+    if !handler.is_null() {
+        drop(Box::from_raw(handler));
+    }
+}
+
+/// Map the input to the control, replacing whatever it was mapped to before.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_bind(
+    handler: *mut PuppetmasterHandler,
+    input: u32,
+    control: u32,
+) {
+    if let Some(handler) = handler.as_mut() {
+        handler.control_config_mut().insert(input, control);
+    }
+}
+
+/// Remove the input's mapping, if it has one.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_unbind(handler: *mut PuppetmasterHandler, input: u32) {
+    if let Some(handler) = handler.as_mut() {
+        handler.control_config_mut().remove(&input);
+    }
+}
+
+/// Call this when your engine says an input was pressed.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_input_down(handler: *mut PuppetmasterHandler, input: u32) {
+    if let Some(handler) = handler.as_mut() {
+        handler.on_input_down(input);
+    }
+}
+
+/// Call this when your engine says an input was released.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_input_up(handler: *mut PuppetmasterHandler, input: u32) {
+    if let Some(handler) = handler.as_mut() {
+        handler.on_input_up(input);
+    }
+}
+
+/// Release everything, like when your window loses focus.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_clear_inputs(handler: *mut PuppetmasterHandler) {
+    if let Some(handler) = handler.as_mut() {
+        handler.clear_inputs();
+    }
+}
+
+/// Update the handler. Call this at the top of your game loop.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_update(handler: *mut PuppetmasterHandler) {
+    if let Some(handler) = handler.as_mut() {
+        handler.update();
+    }
+}
+
+/// Get the number of frames the control has been held for. If it's not pressed, this is 0.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_press_time(
+    handler: *const PuppetmasterHandler,
+    control: u32,
+) -> u32 {
+    handler
+        .as_ref()
+        .map_or(0, |handler| handler.press_time(control))
+}
+
+/// Return if the control is held down.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_down(
+    handler: *const PuppetmasterHandler,
+    control: u32,
+) -> bool {
+    handler
+        .as_ref()
+        .is_some_and(|handler| handler.down(control))
+}
+
+/// Return if the control is not held down.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_up(
+    handler: *const PuppetmasterHandler,
+    control: u32,
+) -> bool {
+    handler.as_ref().is_none_or(|handler| handler.up(control))
+}
+
+/// Return if the control was pressed this frame.
+///
+/// # Safety
+///
+/// `handler` must be null, or a live pointer from [`puppetmaster_new`].
+#[no_mangle]
+pub unsafe extern "C" fn puppetmaster_clicked(
+    handler: *const PuppetmasterHandler,
+    control: u32,
+) -> bool {
+    handler
+        .as_ref()
+        .is_some_and(|handler| handler.clicked(control))
+}
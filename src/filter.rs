@@ -0,0 +1,107 @@
+use std::fmt;
+
+/// Something the raw inputs pass through on their way to being mapped to controls.
+///
+/// Each update, the handler hands its filters the inputs that are held down, in the order they were added.
+/// A filter can rewrite the list however it likes: change inputs into other inputs, drop inputs to swallow
+/// them, or hold on to them and add them back on a later frame to delay them. Filters run before
+/// [remapping](crate::Controls::input_remap_mut), so a remap sees what the filters left.
+///
+/// Any closure that takes a `&mut Vec<I>` is a filter, as long as it's `Clone`. For filters with more
+/// going on, implement this trait on your own type.
+///
+/// ```rust
+/// # use puppetmaster::PollingInputHandler;
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("a", "confirm"), ("b", "cancel")]);
+///
+/// // In some regions, confirm and cancel are the other way around
+/// input_handler.add_input_filter(|inputs: &mut Vec<&str>| {
+///     for input in inputs.iter_mut() {
+///         *input = match *input {
+///             "a" => "b",
+///             "b" => "a",
+///             other => other,
+///         };
+///     }
+/// });
+///
+/// input_handler.update(vec!["a"]);
+/// assert!(input_handler.clicked("cancel"));
+/// ```
+pub trait InputFilter<I>: CloneInputFilter<I> + Send + Sync {
+    /// Rewrite the inputs held down this frame.
+    fn filter(&mut self, inputs: &mut Vec<I>);
+}
+
+impl<I, F> InputFilter<I> for F
+where
+    F: FnMut(&mut Vec<I>) + Clone + Send + Sync + 'static,
+{
+    fn filter(&mut self, inputs: &mut Vec<I>) {
+        self(inputs);
+    }
+}
+
+/// Copies a boxed [`InputFilter`], so handlers with filters can still be cloned.
+///
+/// This is implemented for every filter that's `Clone`, so you shouldn't need to implement it yourself.
+pub trait CloneInputFilter<I> {
+    /// Copy the filter into a new box.
+    fn clone_filter(&self) -> Box<dyn InputFilter<I>>;
+}
+
+impl<I, F: InputFilter<I> + Clone + 'static> CloneInputFilter<I> for F {
+    fn clone_filter(&self) -> Box<dyn InputFilter<I>> {
+        Box::new(self.clone())
+    }
+}
+
+/// A handler's filters, in the order the inputs go through them.
+pub(crate) struct InputFilters<I>(Vec<Box<dyn InputFilter<I>>>);
+
+impl<I: Clone> InputFilters<I> {
+    pub fn push(&mut self, filter: Box<dyn InputFilter<I>>) {
+        self.0.push(filter);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Run the inputs through every filter. Returns `None` without cloning anything if there aren't any.
+    pub fn apply<'a>(&mut self, inputs: impl IntoIterator<Item = &'a I>) -> Option<Vec<I>>
+    where
+        I: 'a,
+    {
+        if self.0.is_empty() {
+            return None;
+        }
+        let mut inputs = inputs.into_iter().cloned().collect();
+        for filter in self.0.iter_mut() {
+            filter.filter(&mut inputs);
+        }
+        Some(inputs)
+    }
+}
+
+impl<I> Clone for InputFilters<I> {
+    fn clone(&self) -> Self {
+        Self(self.0.iter().map(|filter| filter.clone_filter()).collect())
+    }
+}
+
+impl<I> Default for InputFilters<I> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<I> fmt::Debug for InputFilters<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "InputFilters({})", self.0.len())
+    }
+}
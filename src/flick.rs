@@ -0,0 +1,130 @@
+/// Settings for stick flicks, turning a quick flick of an analog stick and back into a one-shot click.
+///
+/// A flick is the stick leaving the middle, going past `threshold`, and coming back inside `rest` within
+/// `max_frames` frames. The control for the direction it went furthest in clicks on the frame it comes
+/// back. Holding the stick over for longer than that isn't a flick, so the same stick can still be used
+/// for sustained things like aiming.
+///
+/// Give the handler the stick's position each frame with `set_flick_stick`. Positive `y` is up, like
+/// [`StickNavigation`](crate::StickNavigation). The direction controls don't need any inputs bound to them.
+///
+/// ```rust
+/// # use puppetmaster::{PollingInputHandler, StickFlicks};
+/// let mut input_handler = PollingInputHandler::<(), _>::new();
+/// input_handler.set_stick_flicks(Some(StickFlicks {
+///     up: Some("quick_item"),
+///     down: None,
+///     left: None,
+///     right: None,
+///     threshold: 0.8,
+///     rest: 0.2,
+///     max_frames: 8,
+/// }));
+///
+/// let mut used_item = false;
+/// for y in [0.0, 0.5, 1.0, 0.6, 0.1, 0.0] {
+///     input_handler.set_flick_stick((0.0, y));
+///     input_handler.update(vec![]);
+///     used_item |= input_handler.clicked("quick_item");
+/// }
+/// assert!(used_item);
+///
+/// // Holding the stick up is not a flick.
+/// let mut used_item = false;
+/// for frame in 0..30 {
+///     input_handler.set_flick_stick((0.0, if frame < 29 { 1.0 } else { 0.0 }));
+///     input_handler.update(vec![]);
+///     used_item |= input_handler.clicked("quick_item");
+/// }
+/// assert!(!used_item);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct StickFlicks<C> {
+    /// The control to click for a flick up (positive `y`).
+    pub up: Option<C>,
+    /// The control to click for a flick down (negative `y`).
+    pub down: Option<C>,
+    /// The control to click for a flick left (negative `x`).
+    pub left: Option<C>,
+    /// The control to click for a flick right (positive `x`).
+    pub right: Option<C>,
+    /// How far out the stick has to go, from 0 to 1.
+    pub threshold: f32,
+    /// How close to the middle the stick has to be to count as back at rest. This should be less than
+    /// `threshold`.
+    pub rest: f32,
+    /// How many frames the stick can spend away from rest for it to still be a flick.
+    pub max_frames: u32,
+}
+
+/// Where the flick stick has been since it left rest.
+#[derive(Clone, Debug)]
+pub(crate) struct StickFlickState<C> {
+    pub settings: StickFlicks<C>,
+    /// The latest position of the stick
+    stick: (f32, f32),
+    /// If the stick is away from rest, how many frames it's been away and the furthest it's gone
+    away: Option<(u32, (f32, f32))>,
+}
+
+impl<C> StickFlickState<C> {
+    pub fn new(settings: StickFlicks<C>) -> Self {
+        Self {
+            settings,
+            stick: (0.0, 0.0),
+            away: None,
+        }
+    }
+
+    pub fn set_stick(&mut self, stick: (f32, f32)) {
+        self.stick = stick;
+    }
+
+    /// Move forward a frame. Returns the control to click this frame, if any.
+    pub fn update(&mut self) -> Option<&C> {
+        let (x, y) = self.stick;
+        let distance = x.hypot(y);
+        if distance > self.settings.rest {
+            let (frames, peak) = self.away.get_or_insert((0, (x, y)));
+            *frames = frames.saturating_add(1);
+            if distance > peak.0.hypot(peak.1) {
+                *peak = (x, y);
+            }
+            return None;
+        }
+
+        let (frames, (px, py)) = self.away.take()?;
+        if frames > self.settings.max_frames || px.hypot(py) < self.settings.threshold {
+            return None;
+        }
+        let settings = &self.settings;
+        let direction = if px.abs() >= py.abs() {
+            if px < 0.0 {
+                &settings.left
+            } else {
+                &settings.right
+            }
+        } else if py < 0.0 {
+            &settings.down
+        } else {
+            &settings.up
+        };
+        direction.as_ref()
+    }
+
+    /// Every control that can be clicked by a flick.
+    pub fn controls(&self) -> impl Iterator<Item = &C> + '_ {
+        [
+            &self.settings.up,
+            &self.settings.down,
+            &self.settings.left,
+            &self.settings.right,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    pub fn reset(&mut self) {
+        self.away = None;
+    }
+}
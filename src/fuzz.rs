@@ -0,0 +1,197 @@
+//! Random input for property-testing game logic.
+//!
+//! Enable this with the `arbitrary` feature for [`arbitrary`](https://docs.rs/arbitrary) implementations,
+//! for fuzzers like `cargo fuzz`, or the `proptest` feature for [`proptest`](https://docs.rs/proptest)
+//! strategies.
+//!
+//! Random input is an [`InputStream`]: what's held down on each frame, which is exactly what a
+//! [`PollingInputHandler`](crate::PollingInputHandler) wants each update. This shrinks well, since taking
+//! away a frame or one held input always leaves a sensible stream. [`Recording`]s and [`InputSnapshot`]s are
+//! built out of streams and press times, so they can't come out in states a handler could never be in.
+
+use std::hash::Hash;
+
+use ahash::AHashMap;
+
+use crate::{control_state::ControlState, InputSnapshot, Recorder, Recording};
+
+/// A stream of random input: what's held down on each frame, in order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InputStream<T> {
+    /// What's held down on each frame.
+    pub frames: Vec<Vec<T>>,
+}
+
+impl<T: Hash + Eq + Clone> InputStream<T> {
+    /// Get how many frames long the stream is.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Return if the stream has no frames at all.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Record the stream, for playing it back with a [`Replay`](crate::Replay).
+    pub fn to_recording(&self) -> Recording<T> {
+        let mut recorder = Recorder::new();
+        for held in self.frames.iter() {
+            recorder.record(held.iter().cloned());
+        }
+        recorder.finish().unwrap_or_default()
+    }
+}
+
+/// Make a snapshot where each control has been down for the given number of frames, and clicked if that's 1.
+fn snapshot_from_times<C: Hash + Eq>(
+    times: impl IntoIterator<Item = (C, u32)>,
+) -> InputSnapshot<C> {
+    InputSnapshot::new(
+        times
+            .into_iter()
+            .filter(|(_, time)| *time > 0)
+            .map(|(ctrl, time)| {
+                (
+                    ctrl,
+                    ControlState {
+                        time,
+                        clicked: time == 1,
+                    },
+                )
+            })
+            .collect::<AHashMap<_, _>>(),
+    )
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use std::hash::Hash;
+
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    use super::{snapshot_from_times, InputStream};
+    use crate::{InputSnapshot, Recording};
+
+    /// ```rust
+    /// # use arbitrary::{Arbitrary, Unstructured};
+    /// # use puppetmaster::{fuzz::InputStream, PollingInputHandler};
+    /// let bytes = [3, 1, 2, 0, 1, 4, 1, 1, 0, 2, 0, 3, 0];
+    /// let stream = InputStream::<bool>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+    ///
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![(true, "jump")]);
+    /// for held in stream.frames {
+    ///     input_handler.update(held);
+    ///     assert!(!input_handler.clicked("jump") || input_handler.press_time("jump") == 1);
+    /// }
+    /// ```
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for InputStream<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self {
+                frames: u.arbitrary()?,
+            })
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            Ok(Self {
+                frames: Vec::arbitrary_take_rest(u)?,
+            })
+        }
+
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            <Vec<Vec<T>>>::size_hint(depth)
+        }
+    }
+
+    /// Random recordings are recorded from a random [`InputStream`].
+    impl<'a, T: Arbitrary<'a> + Hash + Eq + Clone> Arbitrary<'a> for Recording<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(InputStream::arbitrary(u)?.to_recording())
+        }
+
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            InputStream::<T>::size_hint(depth)
+        }
+    }
+
+    /// Random snapshots give each control a random press time, and count the ones down for exactly one
+    /// frame as clicked.
+    impl<'a, C: Arbitrary<'a> + Hash + Eq> Arbitrary<'a> for InputSnapshot<C> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(snapshot_from_times(u.arbitrary::<Vec<(C, u32)>>()?))
+        }
+
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            <Vec<(C, u32)>>::size_hint(depth)
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub use proptest_strategies::*;
+
+#[cfg(feature = "proptest")]
+mod proptest_strategies {
+    use std::{fmt, hash::Hash};
+
+    use proptest::{collection::SizeRange, prelude::*};
+
+    use super::{snapshot_from_times, InputStream};
+    use crate::{InputSnapshot, Recording};
+
+    /// Make input streams of `frames` frames, each holding down `held` inputs picked by `input`.
+    ///
+    /// ```rust
+    /// # use proptest::{prelude::*, test_runner::TestRunner};
+    /// # use puppetmaster::{fuzz::input_streams, PollingInputHandler};
+    /// let inputs = prop::sample::select(vec!["a", "d", "space"]);
+    /// TestRunner::default()
+    ///     .run(&input_streams(inputs, 0..60, 0..3), |stream| {
+    ///         let mut input_handler = PollingInputHandler::new_with_controls(vec![
+    ///             ("a", "left"),
+    ///             ("d", "right"),
+    ///             ("space", "jump"),
+    ///         ]);
+    ///         for held in stream.frames {
+    ///             input_handler.update(held);
+    ///             prop_assert!(!input_handler.clicked("jump") || input_handler.press_time("jump") == 1);
+    ///         }
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn input_streams<S: Strategy>(
+        input: S,
+        frames: impl Into<SizeRange>,
+        held: impl Into<SizeRange>,
+    ) -> impl Strategy<Value = InputStream<S::Value>> {
+        prop::collection::vec(prop::collection::vec(input, held), frames)
+            .prop_map(|frames| InputStream { frames })
+    }
+
+    /// Make recordings by recording streams from [`input_streams`].
+    pub fn recordings<S>(
+        input: S,
+        frames: impl Into<SizeRange>,
+        held: impl Into<SizeRange>,
+    ) -> impl Strategy<Value = Recording<S::Value>>
+    where
+        S: Strategy,
+        S::Value: Hash + Eq + Clone,
+    {
+        input_streams(input, frames, held).prop_map(|stream| stream.to_recording())
+    }
+
+    /// Make snapshots where each control picked by `control` has been down for up to `max_time` frames.
+    pub fn snapshots<S>(
+        control: S,
+        controls: impl Into<SizeRange>,
+        max_time: u32,
+    ) -> impl Strategy<Value = InputSnapshot<S::Value>>
+    where
+        S: Strategy,
+        S::Value: Hash + Eq + fmt::Debug,
+    {
+        prop::collection::vec((control, 0..=max_time), controls).prop_map(snapshot_from_times)
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+/// Settings for mouse gestures, turning quick strokes of the pointer up, down, left, or right into clicks.
+///
+/// A stroke counts when the pointer moves at least `min_length` in one direction within `max_frames`
+/// frames, so slow drifting doesn't count, and the control for the direction it moved furthest in clicks.
+/// Strokes can be chained: after one counts, the next one starts from where it ended, so drawing an L
+/// clicks down and then right.
+///
+/// If `hold` is set, strokes only count while that control is down, like holding the right mouse button
+/// for browser-style gestures. The pointer comes from `set_pointer`, in screen coordinates, so up is
+/// towards the top of the screen (negative `y`). The direction controls don't need any inputs bound to
+/// them.
+///
+/// ```rust
+/// # use puppetmaster::{MouseGestures, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("rmb", "gesture")]);
+/// input_handler.set_mouse_gestures(Some(MouseGestures {
+///     up: None,
+///     down: None,
+///     left: Some("back"),
+///     right: Some("forward"),
+///     hold: Some("gesture"),
+///     min_length: 100.0,
+///     max_frames: 10,
+/// }));
+///
+/// // Moving without the button held does nothing ...
+/// for x in 0..5 {
+///     input_handler.set_pointer(Some((500.0 - x as f32 * 40.0, 300.0)));
+///     input_handler.update(vec![]);
+///     assert!(!input_handler.clicked("back"));
+/// }
+///
+/// // ... but a quick stroke left with it held goes back.
+/// let mut went_back = false;
+/// for x in 0..5 {
+///     input_handler.set_pointer(Some((500.0 - x as f32 * 40.0, 300.0)));
+///     input_handler.update(vec!["rmb"]);
+///     went_back |= input_handler.clicked("back");
+/// }
+/// assert!(went_back);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MouseGestures<C> {
+    /// The control to click for a stroke up (negative `y`).
+    pub up: Option<C>,
+    /// The control to click for a stroke down (positive `y`).
+    pub down: Option<C>,
+    /// The control to click for a stroke left (negative `x`).
+    pub left: Option<C>,
+    /// The control to click for a stroke right (positive `x`).
+    pub right: Option<C>,
+    /// The control that has to be held down for strokes to count, if any.
+    pub hold: Option<C>,
+    /// How far the pointer has to move for a stroke to count.
+    pub min_length: f32,
+    /// How many frames a stroke can take at most. 0 counts as 1.
+    pub max_frames: u32,
+}
+
+/// Where the pointer has been recently, for spotting strokes.
+#[derive(Clone, Debug)]
+pub(crate) struct MouseGestureState<C> {
+    pub settings: MouseGestures<C>,
+    /// The pointer's recent positions, oldest first, going back at most `max_frames` frames
+    trail: VecDeque<(f32, f32)>,
+}
+
+impl<C> MouseGestureState<C> {
+    pub fn new(settings: MouseGestures<C>) -> Self {
+        Self {
+            settings,
+            trail: VecDeque::new(),
+        }
+    }
+
+    /// Move forward a frame, given where the pointer is and if the hold control is down. Returns the control
+    /// to click this frame, if any.
+    pub fn update(&mut self, pointer: Option<(f32, f32)>, held: bool) -> Option<&C> {
+        let Some((x, y)) = pointer.filter(|_| held || self.settings.hold.is_none()) else {
+            self.trail.clear();
+            return None;
+        };
+        self.trail.push_back((x, y));
+        while self.trail.len() > self.settings.max_frames.max(1) as usize + 1 {
+            self.trail.pop_front();
+        }
+
+        let &(start_x, start_y) = self.trail.front()?;
+        let (dx, dy) = (x - start_x, y - start_y);
+        if dx.hypot(dy) < self.settings.min_length {
+            return None;
+        }
+        // The next stroke starts from here
+        self.trail.clear();
+        self.trail.push_back((x, y));
+
+        let settings = &self.settings;
+        let direction = if dx.abs() >= dy.abs() {
+            if dx < 0.0 {
+                &settings.left
+            } else {
+                &settings.right
+            }
+        } else if dy < 0.0 {
+            &settings.up
+        } else {
+            &settings.down
+        };
+        direction.as_ref()
+    }
+
+    /// Every control that can be clicked by a stroke.
+    pub fn controls(&self) -> impl Iterator<Item = &C> + '_ {
+        [
+            &self.settings.up,
+            &self.settings.down,
+            &self.settings.left,
+            &self.settings.right,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    pub fn reset(&mut self) {
+        self.trail.clear();
+    }
+}
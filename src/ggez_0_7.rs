@@ -0,0 +1,146 @@
+//! Glue for using puppetmaster with [`ggez`] 0.7.
+//!
+//! Enable this with the `ggez_0_7` feature. Each major release of ggez gets its own feature and module,
+//! so upgrading ggez doesn't silently change your bindings.
+//!
+//! [`GgezInput`] covers everything ggez treats as a button: keys, mouse buttons, and gamepad buttons.
+//! Then either:
+//!
+//! - forward your [`EventHandler`](::ggez::event::EventHandler) callbacks to the functions in this module
+//!   to drive an [`EventInputHandler`], or
+//! - pass [`is_pressed`] to [`QueryInputHandler::update`](crate::QueryInputHandler::update).
+//!
+//! ```rust,ignore
+//! use puppetmaster::ggez_0_7 as adapter;
+//!
+//! impl EventHandler for MainState {
+//!     fn key_down_event(&mut self, _ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods, _repeat: bool) {
+//!         adapter::key_down_event(&mut self.controls, keycode);
+//!     }
+//!
+//!     fn key_up_event(&mut self, _ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods) {
+//!         adapter::key_up_event(&mut self.controls, keycode);
+//!     }
+//!
+//!     fn gamepad_button_down_event(&mut self, _ctx: &mut Context, btn: Button, _id: GamepadId) {
+//!         adapter::gamepad_button_down_event(&mut self.controls, btn);
+//!     }
+//!
+//!     // and so on ...
+//! }
+//! ```
+
+use std::hash::Hash;
+
+use ::ggez::{
+    event::{Button, KeyCode, MouseButton},
+    input::{gamepad, keyboard, mouse},
+    Context,
+};
+
+use crate::EventInputHandler;
+
+/// Anything ggez treats as a button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GgezInput {
+    /// A key on the keyboard.
+    Key(KeyCode),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// A button on any gamepad.
+    Gamepad(Button),
+}
+
+impl From<KeyCode> for GgezInput {
+    fn from(key: KeyCode) -> Self {
+        GgezInput::Key(key)
+    }
+}
+
+impl From<MouseButton> for GgezInput {
+    fn from(button: MouseButton) -> Self {
+        GgezInput::Mouse(button)
+    }
+}
+
+impl From<Button> for GgezInput {
+    fn from(button: Button) -> Self {
+        GgezInput::Gamepad(button)
+    }
+}
+
+/// Return if the given input is pressed. Pass this to [`QueryInputHandler::update`](crate::QueryInputHandler::update)
+/// in a closure that borrows the `Context`.
+///
+/// ggez panics when asked about gamepads if its gamepad support is turned off in the `Conf`, so don't bind
+/// [`GgezInput::Gamepad`] inputs in that case.
+pub fn is_pressed(ctx: &Context, input: &GgezInput) -> bool {
+    match *input {
+        GgezInput::Key(key) => keyboard::is_key_pressed(ctx, key),
+        GgezInput::Mouse(button) => mouse::button_pressed(ctx, button),
+        GgezInput::Gamepad(button) => gamepad::gamepads(ctx).any(|(_, pad)| pad.is_pressed(button)),
+    }
+}
+
+/// Call this from [`EventHandler::key_down_event`](::ggez::event::EventHandler::key_down_event).
+pub fn key_down_event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<GgezInput, C>,
+    keycode: KeyCode,
+) {
+    handler.on_input_down(GgezInput::Key(keycode));
+}
+
+/// Call this from [`EventHandler::key_up_event`](::ggez::event::EventHandler::key_up_event).
+pub fn key_up_event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<GgezInput, C>,
+    keycode: KeyCode,
+) {
+    handler.on_input_up(GgezInput::Key(keycode));
+}
+
+/// Call this from [`EventHandler::mouse_button_down_event`](::ggez::event::EventHandler::mouse_button_down_event).
+pub fn mouse_button_down_event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<GgezInput, C>,
+    button: MouseButton,
+) {
+    handler.on_input_down(GgezInput::Mouse(button));
+}
+
+/// Call this from [`EventHandler::mouse_button_up_event`](::ggez::event::EventHandler::mouse_button_up_event).
+pub fn mouse_button_up_event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<GgezInput, C>,
+    button: MouseButton,
+) {
+    handler.on_input_up(GgezInput::Mouse(button));
+}
+
+/// Call this from [`EventHandler::gamepad_button_down_event`](::ggez::event::EventHandler::gamepad_button_down_event).
+pub fn gamepad_button_down_event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<GgezInput, C>,
+    button: Button,
+) {
+    handler.on_input_down(GgezInput::Gamepad(button));
+}
+
+/// Call this from [`EventHandler::gamepad_button_up_event`](::ggez::event::EventHandler::gamepad_button_up_event).
+pub fn gamepad_button_up_event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<GgezInput, C>,
+    button: Button,
+) {
+    handler.on_input_up(GgezInput::Gamepad(button));
+}
+
+/// Call this from [`EventHandler::focus_event`](::ggez::event::EventHandler::focus_event).
+///
+/// This releases everything when the window loses focus, because ggez won't report the key-up events
+/// for keys released while the window is unfocused.
+pub fn focus_event<C: Hash + Eq + Clone>(
+    handler: &mut EventInputHandler<GgezInput, C>,
+    gained: bool,
+) {
+    if gained {
+        handler.on_focus_gained();
+    } else {
+        handler.on_focus_lost();
+    }
+}
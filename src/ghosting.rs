@@ -0,0 +1,94 @@
+use std::hash::Hash;
+
+use ahash::AHashMap;
+
+/// A model of which inputs a keyboard can't report being held all at once.
+///
+/// Cheap membrane keyboards wire their keys up in a grid, and can lose keys (or see keys that aren't
+/// pressed, called ghosting) when several are held together. Use a model with
+/// [`ValidationReport::check_ghosting`](crate::ValidationReport::check_ghosting) to find chords in your
+/// bindings that won't work on that kind of keyboard.
+///
+/// Closures taking `&[&I]` are models too.
+pub trait GhostingModel<I> {
+    /// Return if holding all of these inputs at once might not be reported right.
+    fn ghosts(&self, inputs: &[&I]) -> bool;
+}
+
+impl<I, F: Fn(&[&I]) -> bool> GhostingModel<I> for F {
+    fn ghosts(&self, inputs: &[&I]) -> bool {
+        self(inputs)
+    }
+}
+
+/// The simplest model: the keyboard can only report so many keys at once, no matter which ones.
+///
+/// The very cheapest keyboards manage 2 or 3. Inputs past that are the ones that go missing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RolloverLimit(pub usize);
+
+impl<I: PartialEq> GhostingModel<I> for RolloverLimit {
+    fn ghosts(&self, inputs: &[&I]) -> bool {
+        let mut distinct = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            if !distinct.contains(input) {
+                distinct.push(*input);
+            }
+        }
+        distinct.len() > self.0
+    }
+}
+
+/// A model of the grid a keyboard's keys are wired in, by each key's row and column.
+///
+/// When three held keys make three corners of a rectangle in the grid (two sharing a row, and two sharing a
+/// column), the keyboard can't tell whether the fourth corner is held too. Most keyboards then block one of
+/// the keys, and the rest report a key that isn't pressed. Keys that aren't in the grid, like modifiers
+/// that have their own wires, never ghost.
+///
+/// Real keyboards' grids vary, even between models from the same maker, so this is as good as the layout
+/// you give it.
+///
+/// ```rust
+/// # use puppetmaster::{GhostingModel, KeyMatrix};
+/// let matrix = KeyMatrix::new([('w', (0, 1)), ('a', (1, 0)), ('s', (1, 1)), (' ', (3, 4))]);
+///
+/// // W and S share a column, and A and S share a row.
+/// assert!(matrix.ghosts(&[&'w', &'a', &'s']));
+/// assert!(!matrix.ghosts(&[&'w', &'a', &' ']));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct KeyMatrix<I> {
+    /// The row and column of each key
+    positions: AHashMap<I, (u8, u8)>,
+}
+
+impl<I: Hash + Eq> KeyMatrix<I> {
+    /// Create a grid from each key and its row and column.
+    pub fn new(positions: impl IntoIterator<Item = (I, (u8, u8))>) -> Self {
+        Self {
+            positions: positions.into_iter().collect(),
+        }
+    }
+
+    /// Get the row and column of a key, if it's in the grid.
+    pub fn position(&self, input: &I) -> Option<(u8, u8)> {
+        self.positions.get(input).copied()
+    }
+}
+
+impl<I: Hash + Eq> GhostingModel<I> for KeyMatrix<I> {
+    fn ghosts(&self, inputs: &[&I]) -> bool {
+        let mut held = inputs
+            .iter()
+            .filter_map(|input| self.position(input))
+            .collect::<Vec<_>>();
+        held.sort_unstable();
+        held.dedup();
+        // Look for a corner key that shares a row with one key and a column with another.
+        held.iter().any(|&(row, col)| {
+            held.iter().any(|&(r, c)| r == row && c != col)
+                && held.iter().any(|&(r, c)| c == col && r != row)
+        })
+    }
+}
@@ -0,0 +1,182 @@
+use std::hash::Hash;
+
+use ahash::AHashMap;
+
+use crate::{FrameNumber, InputSnapshot};
+
+/// The query side of every input handler, for writing game code that doesn't care how input gets in.
+///
+/// Menus and player controllers only need to ask about controls, so write them against this trait, and they'll
+/// work with an [`EventInputHandler`], a [`PollingInputHandler`], or a [`QueryInputHandler`]. Feeding input to
+/// the handler and updating it stays with the handler itself, since that's the part that depends on the engine.
+///
+/// ```rust
+/// # use puppetmaster::{EventInputHandler, InputHandler, PollingInputHandler};
+/// #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+/// enum Control {
+///     Left,
+///     Right,
+///     Jump,
+/// }
+///
+/// fn walk_speed(input: &impl InputHandler<C = Control>) -> i32 {
+///     input.down(Control::Right) as i32 - input.down(Control::Left) as i32
+/// }
+///
+/// let mut polling = PollingInputHandler::new_with_controls(vec![('a', Control::Left), ('d', Control::Right)]);
+/// polling.update(vec!['d']);
+/// assert_eq!(walk_speed(&polling), 1);
+///
+/// let mut event = EventInputHandler::new_with_controls(vec![("left", Control::Left), ("space", Control::Jump)]);
+/// event.on_input_down("left");
+/// event.update();
+/// assert_eq!(walk_speed(&event), -1);
+/// ```
+///
+/// It can be used as a trait object too, like `&dyn InputHandler<I = char, C = Control>`, to pick the handler
+/// at runtime. [`all_pressed`](Self::all_pressed) can't be called through one, since it returns an
+/// `impl Iterator`; use [`all_pressed_boxed`](Self::all_pressed_boxed) there instead.
+///
+/// ```rust
+/// # use puppetmaster::{InputHandler, PollingInputHandler, QueryInputHandler};
+/// let mut polling = PollingInputHandler::new_with_controls(vec![('x', "fire")]);
+/// polling.update(vec!['x']);
+/// let query = QueryInputHandler::new_with_controls(vec![('x', "fire")]);
+///
+/// let handlers: [&dyn InputHandler<I = char, C = &str>; 2] = [&polling, &query];
+/// let firing = handlers.iter().filter(|handler| handler.all_pressed_boxed().next().is_some());
+/// assert_eq!(firing.count(), 1);
+/// ```
+pub trait InputHandler {
+    /// The inputs the handler reads.
+    type I: Hash + Eq + Clone;
+    /// The controls the inputs are mapped to.
+    type C: Hash + Eq + Clone;
+
+    /// Return the number of frames the given control has been pressed for.
+    fn press_time(&self, ctrl: Self::C) -> u32;
+
+    /// Return if this control was *clicked* down this frame.
+    fn clicked(&self, ctrl: Self::C) -> bool;
+
+    /// Return the number of frames the given control has been up for since it was released, counting the frame
+    /// it was released on. This is 0 if the control is down, or has never been released.
+    fn release_time(&self, ctrl: Self::C) -> u32;
+
+    /// Return if this control is held down.
+    fn down(&self, ctrl: Self::C) -> bool {
+        self.press_time(ctrl) >= 1
+    }
+
+    /// Return if this control is up.
+    fn up(&self, ctrl: Self::C) -> bool {
+        self.press_time(ctrl) == 0
+    }
+
+    /// Return if this control was released this frame.
+    fn just_released(&self, ctrl: Self::C) -> bool {
+        self.release_time(ctrl) == 1
+    }
+
+    /// Get the value of an axis control as of the last update, from -1 to 1.
+    fn value(&self, ctrl: Self::C) -> f32;
+
+    /// Get the value of a 2D control as of the last update, from its two axis controls.
+    fn vector(&self, ctrl: Self::C) -> (f32, f32);
+
+    /// Return an iterator over all the controls that are currently held down.
+    fn all_pressed(&self) -> impl Iterator<Item = &Self::C> + '_
+    where
+        Self: Sized;
+
+    /// Return an iterator over all the controls that are currently held down, boxed so it works on a
+    /// `dyn InputHandler`.
+    fn all_pressed_boxed(&self) -> Box<dyn Iterator<Item = &Self::C> + '_>;
+
+    /// Get which update the handler is on. This is 0 before the first update.
+    fn frame(&self) -> FrameNumber;
+
+    /// Take a read-only snapshot of the state of all the controls, which can be shared across threads.
+    fn snapshot(&self) -> InputSnapshot<Self::C>;
+
+    /// Get the mapping of inputs to controls.
+    fn control_config(&self) -> &AHashMap<Self::I, Self::C>;
+
+    /// Mutably get the mapping of inputs to controls.
+    fn control_config_mut(&mut self) -> &mut AHashMap<Self::I, Self::C>;
+}
+
+/// Implement [`InputHandler`] for a handler type with a `controls` field holding its
+/// [`Controls`](crate::Controls), and deref the handler to them so their methods can be called on it directly.
+/// Use it in the handler's own module, where the field can be seen.
+macro_rules! impl_input_handler {
+    ($handler:ident) => {
+        impl<I, C> std::ops::Deref for $handler<I, C> {
+            type Target = crate::Controls<I, C>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.controls
+            }
+        }
+
+        impl<I, C> std::ops::DerefMut for $handler<I, C> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                self.controls.changed();
+                &mut self.controls
+            }
+        }
+
+        impl<I: std::hash::Hash + Eq + Clone, C: std::hash::Hash + Eq + Clone> crate::InputHandler
+            for $handler<I, C>
+        {
+            type I = I;
+            type C = C;
+
+            fn press_time(&self, ctrl: C) -> u32 {
+                self.controls.press_time(ctrl)
+            }
+
+            fn clicked(&self, ctrl: C) -> bool {
+                self.controls.clicked(ctrl)
+            }
+
+            fn release_time(&self, ctrl: C) -> u32 {
+                self.controls.release_time(ctrl)
+            }
+
+            fn value(&self, ctrl: C) -> f32 {
+                self.controls.value(ctrl)
+            }
+
+            fn vector(&self, ctrl: C) -> (f32, f32) {
+                self.controls.vector(ctrl)
+            }
+
+            fn all_pressed(&self) -> impl Iterator<Item = &C> + '_ {
+                self.controls.all_pressed()
+            }
+
+            fn all_pressed_boxed(&self) -> Box<dyn Iterator<Item = &C> + '_> {
+                Box::new(self.controls.all_pressed())
+            }
+
+            fn frame(&self) -> crate::FrameNumber {
+                self.controls.frame()
+            }
+
+            fn snapshot(&self) -> crate::InputSnapshot<C> {
+                self.controls.snapshot()
+            }
+
+            fn control_config(&self) -> &ahash::AHashMap<I, C> {
+                self.controls.control_config()
+            }
+
+            fn control_config_mut(&mut self) -> &mut ahash::AHashMap<I, C> {
+                self.controls.control_config_mut()
+            }
+        }
+    };
+}
+
+pub(crate) use impl_input_handler;
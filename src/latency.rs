@@ -0,0 +1,101 @@
+use std::{hash::Hash, time::Duration, time::Instant};
+
+use ahash::AHashMap;
+
+/// How long a control's presses took to get through the handler, from when the input event came in to the
+/// end of the update that made the control [clicked](crate::Controls::clicked).
+///
+/// Turn measuring on with
+/// [`EventInputHandler::set_latency_tracking`](crate::EventInputHandler::set_latency_tracking). This only
+/// covers the time the press spends queued up in the handler; it can't see the time before your engine
+/// handed over the event, or the time before the frame is drawn.
+///
+/// ```rust
+/// # use puppetmaster::EventInputHandler;
+/// let mut input_handler = EventInputHandler::new_with_controls(vec![("space", "jump")]);
+/// input_handler.set_latency_tracking(true);
+///
+/// input_handler.on_input_down("space");
+/// std::thread::sleep(std::time::Duration::from_millis(5));
+/// input_handler.update();
+///
+/// let stats = input_handler.latency_stats("jump").unwrap();
+/// assert_eq!(stats.samples, 1);
+/// assert!(stats.mean().as_millis() >= 5);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LatencyStats {
+    /// How many presses were measured.
+    pub samples: u32,
+    /// The quickest press.
+    pub min: Duration,
+    /// The slowest press.
+    pub max: Duration,
+    /// All the presses' latencies added up.
+    pub total: Duration,
+}
+
+impl LatencyStats {
+    /// Get the average latency, or zero if nothing was measured.
+    pub fn mean(&self) -> Duration {
+        self.total
+            .checked_div(self.samples)
+            .unwrap_or(Duration::ZERO)
+    }
+
+    fn record(&mut self, latency: Duration) {
+        if self.samples == 0 || latency < self.min {
+            self.min = latency;
+        }
+        self.max = self.max.max(latency);
+        self.total += latency;
+        self.samples += 1;
+    }
+}
+
+/// When presses came in, and how long they took.
+#[derive(Clone, Debug)]
+pub(crate) struct LatencyTracker<I, C> {
+    /// When each input waiting in the queue was pressed
+    queued: AHashMap<I, Instant>,
+    stats: AHashMap<C, LatencyStats>,
+}
+
+impl<I: Hash + Eq, C: Hash + Eq> LatencyTracker<I, C> {
+    /// Note when an input was pressed, if it isn't already waiting.
+    pub fn queued(&mut self, input: I, now: Instant) {
+        self.queued.entry(input).or_insert(now);
+    }
+
+    /// Take when an input was pressed. Inputs that came in without being noted count as coming in at
+    /// `fallback`.
+    pub fn take_queued(&mut self, input: &I, fallback: Instant) -> Instant {
+        self.queued.remove(input).unwrap_or(fallback)
+    }
+
+    /// Forget about everything waiting in the queue.
+    pub fn clear_queued(&mut self) {
+        self.queued.clear();
+    }
+
+    pub fn record(&mut self, ctrl: C, latency: Duration) {
+        self.stats.entry(ctrl).or_default().record(latency);
+    }
+
+    pub fn stats(&self, ctrl: &C) -> Option<LatencyStats> {
+        self.stats.get(ctrl).copied()
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats.clear();
+    }
+}
+
+impl<I, C> Default for LatencyTracker<I, C> {
+    fn default() -> Self {
+        Self {
+            queued: AHashMap::new(),
+            stats: AHashMap::new(),
+        }
+    }
+}
@@ -9,6 +9,31 @@
 //! - [`PollingInputHandler`] for when your game engine provides a set of the currently pressed keys
 //! - [`QueryInputHandler`] for when your game engine provides a function to call to query the state of a key.
 //!
+//! All three implement [`InputHandler`], so game code that only asks about controls can work with any of them.
+//!
+//! If you're using an ECS, [`InputResource`] wraps an `EventInputHandler` so it can sit in a resource slot.
+//!
+//! ## Engine Adapters
+//!
+//! Some game engines and windowing libraries have ready-made glue behind feature flags:
+//!
+//! - `arbitrary`: `Arbitrary` implementations for fuzzing, in the `fuzz` module
+//! - `bevy`: the `bevy` module, with a Bevy plugin
+//! - `crossterm`: the `crossterm` module, for terminal games
+//! - `export`: the `export` module, for writing control state to CSV or JSON Lines, or sending it to overlays
+//! - `ffi`: the `ffi` module, a C interface for games in other languages
+//! - `ggez_0_7`: the `ggez_0_7` module, for ggez 0.7
+//! - `macroquad`: the `macroquad` module
+//! - `miniquad`: the `miniquad` module
+//! - `mlua`: the `mlua` module, for reading controls from Lua scripts. Pick a Lua with `mlua-lua54` or
+//!   `mlua-luajit`, and add `mlua-vendored` to build it instead of linking the system's
+//! - `proptest`: `proptest` strategies for property-testing, in the `fuzz` module
+//! - `sdl2`: the `sdl2` module
+//! - `serde`: `Serialize` and `Deserialize` for bindings, settings, recordings, and [`SavedState`]
+//! - `steamworks`: the `steamworks` module, for Steam Input
+//! - `web`: the `web` module, for browser games on wasm
+//! - `winit`: the `winit` module
+//!
 //! ## Inputs vs Controls
 //!
 //! This crate makes a distinction between *inputs* and *controls*.
@@ -20,10 +45,111 @@
 //! Multiple inputs can map to the same control, but not vice versa. So, both the W key and the up arrow could
 //! map to `Control::Up`, but you couldn't have the shift key map to both Crouch and Sprint.
 
+mod analog;
+mod attract;
+mod charge;
+mod chord;
+mod control_event;
+mod control_state;
+mod controls;
+mod dwell;
 mod event;
+mod filter;
+mod flick;
+mod gesture;
+mod ghosting;
+mod handler;
+mod latency;
+mod lobby;
+mod long_press;
+mod macros;
+mod mirror;
+mod motion;
+mod players;
+mod pointer;
 mod polling;
 mod query;
+mod raw_input;
+mod recording;
+mod registry;
+mod resource;
+mod rumble;
+mod saved_state;
+mod scanning;
+mod scroll;
+mod snapshot;
+mod stick_navigation;
+mod summary;
+mod text;
+mod timing;
+mod validate;
+
+#[cfg(feature = "bevy")]
+pub mod bevy;
+#[cfg(feature = "crossterm")]
+pub mod crossterm;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub mod fuzz;
+#[cfg(feature = "ggez_0_7")]
+pub mod ggez_0_7;
+#[cfg(feature = "macroquad")]
+pub mod macroquad;
+#[cfg(feature = "miniquad")]
+pub mod miniquad;
+#[cfg(feature = "mlua")]
+pub mod mlua;
+#[cfg(feature = "sdl2")]
+pub mod sdl2;
+#[cfg(feature = "steamworks")]
+pub mod steamworks;
+#[cfg(feature = "web")]
+pub mod web;
+#[cfg(feature = "winit")]
+pub mod winit;
 
-pub use event::EventInputHandler;
+pub use analog::{AxisConflict, AxisOverride, DualAxis, VirtualAxis};
+pub use attract::AttractMode;
+pub use charge::Charge;
+pub use chord::Chord;
+pub use control_event::{ControlEvent, ListenerId, RepeatPolicy, RetriggerPolicy, UpdateSummary};
+pub use controls::Controls;
+pub use dwell::{Dwell, DwellRegion};
+pub use event::{EventInputHandler, EventSender, ReleaseTimeout};
+pub use filter::{CloneInputFilter, InputFilter};
+pub use flick::StickFlicks;
+pub use gesture::MouseGestures;
+pub use ghosting::{GhostingModel, KeyMatrix, RolloverLimit};
+pub use handler::InputHandler;
+pub use latency::LatencyStats;
+pub use lobby::{Lobby, LobbyEvent};
+pub use long_press::{LongPress, TapOrHold};
+pub use macros::{Macro, MacroStep};
+pub use mirror::BindingMirror;
+pub use motion::{Directions, Facing, MotionInput, MotionLeniency, MotionMatcher, MotionStep};
+pub use players::Players;
+pub use pointer::{Drag, Rect};
 pub use polling::PollingInputHandler;
 pub use query::QueryInputHandler;
+pub use recording::{RecordedChange, Recorder, Recording, Replay};
+pub use registry::{ControlId, ControlRegistry};
+pub use resource::InputResource;
+pub use rumble::{Rumble, RumbleRequest, TriggerEffect, TriggerEffectRequest, TriggerSide};
+pub use saved_state::{ControlSettings, SavedAxes, SavedState};
+pub use scanning::Scanning;
+pub use scroll::ScrollControls;
+pub use snapshot::InputSnapshot;
+pub use stick_navigation::StickNavigation;
+pub use summary::{ControlSummary, ControlsSummary, InputLabel};
+pub use text::{ImeEvent, TextEdit, TextEntry};
+pub use timing::{
+    AutoRepeat, Clock, Confirmation, Debounce, HoldConfirm, MinHold, RateLimit, TimerClocks,
+    TimingSettings,
+};
+pub use validate::{validate_bindings, IssueKind, ValidationIssue, ValidationReport};
+
+/// Which update a handler is on. The first update is frame 1.
+pub type FrameNumber = u64;
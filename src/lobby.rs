@@ -0,0 +1,208 @@
+use std::hash::Hash;
+
+use ahash::AHashMap;
+
+use crate::InputSnapshot;
+
+/// Something that happened in a [`Lobby`] this frame.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LobbyEvent<D> {
+    /// A device joined, and was given a player slot.
+    Joined {
+        /// The player slot, starting from 0.
+        player: usize,
+        /// The device that joined.
+        device: D,
+    },
+    /// A device left, and its player slot is free again.
+    Left {
+        /// The player slot it had.
+        player: usize,
+        /// The device that left.
+        device: D,
+    },
+    /// A device tried to join, but every slot was taken.
+    Full {
+        /// The device that tried to join.
+        device: D,
+    },
+}
+
+/// A "press start to join" lobby for local multiplayer, assigning devices to player slots.
+///
+/// Give each device its own input handler, and each frame hand the lobby every device's
+/// [snapshot](InputSnapshot) with [`update_from_snapshots`](Self::update_from_snapshots), along with your
+/// join and leave controls. If you work out who pressed what some other way, pass the devices straight to
+/// [`update`](Self::update) instead.
+///
+/// Devices that join get the lowest free slot, so a player who leaves and comes back usually gets their old
+/// slot back. Joining twice, leaving without having joined, and joining when it's full are all handled, and
+/// everything that happened is reported in [`events`](Self::events).
+///
+/// `D` is whatever your engine uses to tell devices apart, like a gamepad ID. The keyboard can be a device
+/// too.
+///
+/// ```rust
+/// # use puppetmaster::{Lobby, LobbyEvent, PollingInputHandler};
+/// let bindings = vec![("start", "join"), ("back", "leave")];
+/// let mut gamepads = vec![
+///     (7, PollingInputHandler::new_with_controls(bindings.clone())),
+///     (3, PollingInputHandler::new_with_controls(bindings)),
+/// ];
+/// let mut lobby = Lobby::new(4);
+///
+/// gamepads[1].1.update(vec!["start"]);
+/// lobby.update_from_snapshots(
+///     gamepads.iter().map(|(id, handler)| (*id, handler.snapshot())),
+///     "join",
+///     "leave",
+/// );
+/// assert_eq!(lobby.events(), &[LobbyEvent::Joined { player: 0, device: 3 }]);
+/// ```
+///
+/// Working out the devices yourself:
+///
+/// ```rust
+/// # use puppetmaster::{Lobby, LobbyEvent};
+/// let mut lobby = Lobby::new(2);
+///
+/// lobby.update([7, 3], []);
+/// assert_eq!(lobby.player_for(&7), Some(0));
+/// assert_eq!(lobby.player_for(&3), Some(1));
+///
+/// // Pressing start again doesn't do anything, and there's no room for a third player.
+/// lobby.update([3, 9], []);
+/// assert_eq!(lobby.events(), &[LobbyEvent::Full { device: 9 }]);
+///
+/// lobby.update([], [7]);
+/// lobby.update([9], []);
+/// assert_eq!(lobby.events(), &[LobbyEvent::Joined { player: 0, device: 9 }]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Lobby<D> {
+    /// The device in each player slot
+    slots: Vec<Option<D>>,
+    /// Each device's player slot
+    players: AHashMap<D, usize>,
+    /// What happened this frame
+    events: Vec<LobbyEvent<D>>,
+}
+
+impl<D: Hash + Eq + Clone> Lobby<D> {
+    /// Create a lobby with room for `max_players` players.
+    pub fn new(max_players: usize) -> Self {
+        Self {
+            slots: vec![None; max_players],
+            players: AHashMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Move forward a frame, given the devices that pressed join and the devices that pressed leave.
+    ///
+    /// Leaves are handled first, so a slot freed up this frame can be taken by a device joining this frame.
+    pub fn update(
+        &mut self,
+        joined: impl IntoIterator<Item = D>,
+        left: impl IntoIterator<Item = D>,
+    ) {
+        self.events.clear();
+        for device in left {
+            self.leave(device);
+        }
+        for device in joined {
+            self.join(device);
+        }
+    }
+
+    /// Move forward a frame, given each device's snapshot. Devices that clicked `join` join, and devices that
+    /// clicked `leave` leave, the same way as [`update`](Self::update).
+    pub fn update_from_snapshots<C: Hash + Eq + Clone>(
+        &mut self,
+        devices: impl IntoIterator<Item = (D, InputSnapshot<C>)>,
+        join: C,
+        leave: C,
+    ) {
+        let mut joined = Vec::new();
+        let mut left = Vec::new();
+        for (device, snapshot) in devices {
+            if snapshot.clicked(leave.clone()) {
+                left.push(device.clone());
+            }
+            if snapshot.clicked(join.clone()) {
+                joined.push(device);
+            }
+        }
+        self.update(joined, left);
+    }
+
+    /// Put a device in the lowest free slot, as if it pressed join. Returns its slot, or `None` if the
+    /// lobby is full. If it's already in the lobby, this returns the slot it has.
+    pub fn join(&mut self, device: D) -> Option<usize> {
+        if let Some(&player) = self.players.get(&device) {
+            return Some(player);
+        }
+        let Some(player) = self.slots.iter().position(Option::is_none) else {
+            self.events.push(LobbyEvent::Full { device });
+            return None;
+        };
+        self.slots[player] = Some(device.clone());
+        self.players.insert(device.clone(), player);
+        self.events.push(LobbyEvent::Joined { player, device });
+        Some(player)
+    }
+
+    /// Take a device out of the lobby, as if it pressed leave. Returns the slot it had, if it was in the
+    /// lobby.
+    pub fn leave(&mut self, device: D) -> Option<usize> {
+        let player = self.players.remove(&device)?;
+        self.slots[player] = None;
+        self.events.push(LobbyEvent::Left { player, device });
+        Some(player)
+    }
+
+    /// Get what happened this frame.
+    pub fn events(&self) -> &[LobbyEvent<D>] {
+        &self.events
+    }
+
+    /// Get the player slot a device is in.
+    pub fn player_for(&self, device: &D) -> Option<usize> {
+        self.players.get(device).copied()
+    }
+
+    /// Get the device in a player slot.
+    pub fn device_for(&self, player: usize) -> Option<&D> {
+        self.slots.get(player).and_then(Option::as_ref)
+    }
+
+    /// Iterate over every player slot that's taken, and the device in it, in slot order.
+    pub fn players(&self) -> impl Iterator<Item = (usize, &D)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(player, device)| device.as_ref().map(|device| (player, device)))
+    }
+
+    /// Get how many players have joined.
+    pub fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Get how many players the lobby has room for.
+    pub fn max_players(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Return if every slot is taken.
+    pub fn is_full(&self) -> bool {
+        self.player_count() == self.max_players()
+    }
+
+    /// Take everyone out of the lobby, without any events.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.players.clear();
+        self.events.clear();
+    }
+}
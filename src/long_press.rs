@@ -0,0 +1,119 @@
+/// Splits one control into two, depending on how long it's held: a short press (a quick tap) and a long
+/// press (held past a threshold).
+///
+/// Set this up on the control the input is bound to with `set_long_press` on the handlers. When that
+/// control is tapped and let go before `frames` frames, the `short` control clicks as it's released. If
+/// it's held for `frames` frames, the `long` control goes down and stays down until it's released, and
+/// the `short` control doesn't fire at all.
+///
+/// The short and long controls don't need any inputs bound to them. The original control still works as
+/// usual, so you can use it for things like showing a "hold for menu" hint.
+///
+/// ```rust
+/// # use puppetmaster::{LongPress, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("a", "a_button")]);
+/// input_handler.set_long_press(
+///     "a_button",
+///     Some(LongPress {
+///         short: "interact",
+///         long: "open_menu",
+///         frames: 20,
+///     }),
+/// );
+///
+/// // A quick tap interacts ...
+/// input_handler.update(vec!["a"]);
+/// input_handler.update(vec![]);
+/// assert!(input_handler.clicked("interact"));
+///
+/// // ... and holding opens the menu instead.
+/// for _ in 0..20 {
+///     input_handler.update(vec!["a"]);
+/// }
+/// assert!(input_handler.clicked("open_menu"));
+/// input_handler.update(vec![]);
+/// assert!(!input_handler.clicked("interact"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LongPress<C> {
+    /// The control that clicks when the control is tapped.
+    pub short: C,
+    /// The control that's down while the control is held past the threshold.
+    pub long: C,
+    /// How many frames the control has to be held for to be a long press. 0 counts as 1.
+    pub frames: u32,
+}
+
+/// How long a long-press control has been held.
+#[derive(Clone, Debug)]
+pub(crate) struct LongPressState<C> {
+    pub settings: LongPress<C>,
+    /// How many frames the control has been held for
+    held_for: u32,
+}
+
+impl<C> LongPressState<C> {
+    pub fn new(settings: LongPress<C>) -> Self {
+        Self {
+            settings,
+            held_for: 0,
+        }
+    }
+
+    /// Move forward a frame, given if the control is held. Returns the control to hold this frame, if any.
+    pub fn update(&mut self, held: bool) -> Option<&C> {
+        let threshold = self.settings.frames.max(1);
+        if held {
+            self.held_for = self.held_for.saturating_add(1);
+            (self.held_for >= threshold).then_some(&self.settings.long)
+        } else {
+            let tapped = self.held_for > 0 && self.held_for < threshold;
+            self.held_for = 0;
+            tapped.then_some(&self.settings.short)
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.held_for = 0;
+    }
+}
+
+/// How a press of a control with a hold threshold turned out. See `set_hold_threshold` on the handlers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TapOrHold {
+    /// The control was let go before the threshold.
+    Tap,
+    /// The control was held until the threshold.
+    Hold,
+}
+
+/// How long a control with a hold threshold has been held.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct TapHoldState {
+    /// How many frames the control had been held for last frame
+    last_time: u32,
+    /// How the press turned out, on the frame it did
+    pub outcome: Option<TapOrHold>,
+}
+
+impl TapHoldState {
+    /// Move forward a frame, given the threshold and how many frames the control has been held for.
+    pub fn update(&mut self, threshold: u32, time: u32) {
+        let threshold = threshold.max(1);
+        self.outcome = if time == threshold {
+            Some(TapOrHold::Hold)
+        } else if time == 0 && self.last_time > 0 && self.last_time < threshold {
+            Some(TapOrHold::Tap)
+        } else {
+            None
+        };
+        self.last_time = time;
+    }
+
+    pub fn reset(&mut self) {
+        self.last_time = 0;
+        self.outcome = None;
+    }
+}
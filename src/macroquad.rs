@@ -0,0 +1,78 @@
+//! Glue for using puppetmaster with [`macroquad`].
+//!
+//! Enable this with the `macroquad` feature.
+//!
+//! Macroquad lets you ask whether an input is down at any time, so it pairs with a [`QueryInputHandler`].
+//!
+//! ```rust,no_run
+//! # use puppetmaster::{QueryInputHandler, macroquad::{self as adapter, MacroquadInput}};
+//! # use ::macroquad::input::{KeyCode, MouseButton};
+//! #[derive(Clone, Copy, Hash, Eq, PartialEq)]
+//! enum Control {
+//!     Jump,
+//!     Shoot,
+//!     NextWeapon,
+//! }
+//!
+//! let mut input_handler = QueryInputHandler::new_with_controls(vec![
+//!     (MacroquadInput::Key(KeyCode::Space), Control::Jump),
+//!     (MacroquadInput::Mouse(MouseButton::Left), Control::Shoot),
+//!     (MacroquadInput::WheelDown, Control::NextWeapon),
+//! ]);
+//!
+//! // At the top of your game loop ...
+//! adapter::update(&mut input_handler);
+//! ```
+
+use std::hash::Hash;
+
+use ::macroquad::input::{self, KeyCode, MouseButton};
+
+use crate::QueryInputHandler;
+
+/// Anything macroquad treats as a button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MacroquadInput {
+    /// A key on the keyboard.
+    Key(KeyCode),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// The mouse wheel was scrolled upwards this frame.
+    WheelUp,
+    /// The mouse wheel was scrolled downwards this frame.
+    WheelDown,
+    /// The mouse wheel was scrolled to the left this frame.
+    WheelLeft,
+    /// The mouse wheel was scrolled to the right this frame.
+    WheelRight,
+}
+
+impl From<KeyCode> for MacroquadInput {
+    fn from(key: KeyCode) -> Self {
+        MacroquadInput::Key(key)
+    }
+}
+
+impl From<MouseButton> for MacroquadInput {
+    fn from(button: MouseButton) -> Self {
+        MacroquadInput::Mouse(button)
+    }
+}
+
+/// Return if the given input is pressed this frame.
+pub fn is_pressed(input: &MacroquadInput) -> bool {
+    match *input {
+        MacroquadInput::Key(key) => input::is_key_down(key),
+        MacroquadInput::Mouse(button) => input::is_mouse_button_down(button),
+        MacroquadInput::WheelUp => input::mouse_wheel().1 > 0.0,
+        MacroquadInput::WheelDown => input::mouse_wheel().1 < 0.0,
+        MacroquadInput::WheelLeft => input::mouse_wheel().0 < 0.0,
+        MacroquadInput::WheelRight => input::mouse_wheel().0 > 0.0,
+    }
+}
+
+/// Update the input handler from macroquad's input state. Call this at the top of your game loop,
+/// instead of calling [`QueryInputHandler::update`] yourself.
+pub fn update<C: Hash + Eq + Clone>(handler: &mut QueryInputHandler<MacroquadInput, C>) {
+    handler.update(is_pressed);
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+/// A timed sequence of control presses and releases, played back over the frames after it's triggered.
+///
+/// Bind a macro to a trigger control with `set_macro` on the handlers, and pressing the trigger plays it.
+/// This lets a player do a complicated combo with one button. You can also play a macro directly with
+/// `play_macro`, which is handy for demos and tutorials.
+///
+/// The controls a macro presses are pressed just as if their inputs were held, so they don't need to have
+/// any inputs bound to them. Anything a macro presses and doesn't release is released when it finishes.
+///
+/// ```rust
+/// # use puppetmaster::{Macro, MacroStep, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("q", "hadouken")]);
+/// input_handler.set_macro(
+///     "hadouken",
+///     Some(Macro::new(vec![
+///         MacroStep::Press("down"),
+///         MacroStep::Wait(2),
+///         MacroStep::Press("right"),
+///         MacroStep::Release("down"),
+///         MacroStep::Wait(2),
+///         MacroStep::Press("punch"),
+///         MacroStep::Wait(1),
+///     ])),
+/// );
+///
+/// input_handler.update(vec!["q"]);
+/// assert!(input_handler.clicked("down"));
+///
+/// input_handler.update(vec![]);
+/// input_handler.update(vec![]);
+/// assert!(input_handler.clicked("right"));
+/// assert!(input_handler.up("down"));
+///
+/// input_handler.update(vec![]);
+/// input_handler.update(vec![]);
+/// assert!(input_handler.clicked("punch"));
+///
+/// input_handler.update(vec![]);
+/// assert!(input_handler.up("right") && input_handler.up("punch"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Macro<C> {
+    steps: Arc<[MacroStep<C>]>,
+}
+
+impl<C> Macro<C> {
+    /// Create a new macro that does the given steps in order.
+    pub fn new(steps: impl IntoIterator<Item = MacroStep<C>>) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+        }
+    }
+
+    /// Get the macro's steps.
+    pub fn steps(&self) -> &[MacroStep<C>] {
+        &self.steps
+    }
+}
+
+/// One step of a [`Macro`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MacroStep<C> {
+    /// Start holding the control down.
+    Press(C),
+    /// Let go of the control.
+    Release(C),
+    /// Let this many frames go by before doing the next step.
+    Wait(u32),
+}
+
+/// A macro partway through playing.
+#[derive(Clone, Debug)]
+pub(crate) struct Playback<C> {
+    steps: Macro<C>,
+    /// Index of the next step to do
+    next: usize,
+    /// How many more frames to wait before doing it
+    wait: u32,
+    /// The controls the macro is holding down
+    held: Vec<C>,
+}
+
+impl<C: PartialEq + Clone> Playback<C> {
+    pub fn new(steps: Macro<C>) -> Self {
+        Self {
+            steps,
+            next: 0,
+            wait: 0,
+            held: Vec::new(),
+        }
+    }
+
+    /// Move forward a frame. Returns if the macro is finished.
+    pub fn update(&mut self) -> bool {
+        self.wait = self.wait.saturating_sub(1);
+        while self.wait == 0 {
+            match self.steps.steps.get(self.next) {
+                Some(MacroStep::Press(ctrl)) => {
+                    if !self.held.contains(ctrl) {
+                        self.held.push(ctrl.clone());
+                    }
+                }
+                Some(MacroStep::Release(ctrl)) => self.held.retain(|held| held != ctrl),
+                Some(MacroStep::Wait(frames)) => self.wait = *frames,
+                None => return true,
+            }
+            self.next += 1;
+        }
+        false
+    }
+
+    /// The controls the macro is holding down.
+    pub fn held(&self) -> &[C] {
+        &self.held
+    }
+}
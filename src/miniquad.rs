@@ -0,0 +1,179 @@
+//! Glue for feeding [`miniquad`] events into an [`EventInputHandler`].
+//!
+//! Enable this with the `miniquad` feature. This works on every platform miniquad supports,
+//! including wasm and mobile.
+//!
+//! Forward the input callbacks of your [`EventHandler`](::miniquad::EventHandler) to a [`MiniquadAdapter`]:
+//!
+//! ```rust,ignore
+//! impl EventHandler for Stage {
+//!     fn update(&mut self) {
+//!         self.controls.update();
+//!         // Now do game logic ...
+//!     }
+//!
+//!     fn key_down_event(&mut self, keycode: KeyCode, _keymods: KeyMods, _repeat: bool) {
+//!         self.adapter.key_down_event(&mut self.controls, keycode);
+//!     }
+//!
+//!     fn key_up_event(&mut self, keycode: KeyCode, _keymods: KeyMods) {
+//!         self.adapter.key_up_event(&mut self.controls, keycode);
+//!     }
+//!
+//!     fn touch_event(&mut self, phase: TouchPhase, id: u64, _x: f32, _y: f32) {
+//!         self.adapter.touch_event(&mut self.controls, phase, id);
+//!     }
+//!
+//!     // and so on ...
+//! }
+//! ```
+
+use std::hash::Hash;
+
+use ::miniquad::{KeyCode, MouseButton, TouchPhase};
+
+use crate::EventInputHandler;
+
+/// Anything miniquad treats as a button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MiniquadInput {
+    /// A key on the keyboard.
+    Key(KeyCode),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// One tick of the mouse wheel upwards.
+    WheelUp,
+    /// One tick of the mouse wheel downwards.
+    WheelDown,
+    /// One tick of the mouse wheel to the left.
+    WheelLeft,
+    /// One tick of the mouse wheel to the right.
+    WheelRight,
+    /// At least one finger is touching the screen.
+    Touch,
+}
+
+impl From<KeyCode> for MiniquadInput {
+    fn from(key: KeyCode) -> Self {
+        MiniquadInput::Key(key)
+    }
+}
+
+impl From<MouseButton> for MiniquadInput {
+    fn from(button: MouseButton) -> Self {
+        MiniquadInput::Mouse(button)
+    }
+}
+
+/// Feeds miniquad's event callbacks into an [`EventInputHandler`].
+#[derive(Clone, Debug, Default)]
+pub struct MiniquadAdapter {
+    /// IDs of the fingers touching the screen
+    touches: Vec<u64>,
+}
+
+impl MiniquadAdapter {
+    /// Create a new `MiniquadAdapter`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call this from [`EventHandler::key_down_event`](::miniquad::EventHandler::key_down_event).
+    pub fn key_down_event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<MiniquadInput, C>,
+        keycode: KeyCode,
+    ) {
+        handler.on_input_down(MiniquadInput::Key(keycode));
+    }
+
+    /// Call this from [`EventHandler::key_up_event`](::miniquad::EventHandler::key_up_event).
+    pub fn key_up_event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<MiniquadInput, C>,
+        keycode: KeyCode,
+    ) {
+        handler.on_input_up(MiniquadInput::Key(keycode));
+    }
+
+    /// Call this from [`EventHandler::mouse_button_down_event`](::miniquad::EventHandler::mouse_button_down_event).
+    pub fn mouse_button_down_event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<MiniquadInput, C>,
+        button: MouseButton,
+    ) {
+        handler.on_input_down(MiniquadInput::Mouse(button));
+    }
+
+    /// Call this from [`EventHandler::mouse_button_up_event`](::miniquad::EventHandler::mouse_button_up_event).
+    pub fn mouse_button_up_event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<MiniquadInput, C>,
+        button: MouseButton,
+    ) {
+        handler.on_input_up(MiniquadInput::Mouse(button));
+    }
+
+    /// Call this from [`EventHandler::mouse_wheel_event`](::miniquad::EventHandler::mouse_wheel_event).
+    pub fn mouse_wheel_event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<MiniquadInput, C>,
+        x: f32,
+        y: f32,
+    ) {
+        let input = if y > 0.0 {
+            MiniquadInput::WheelUp
+        } else if y < 0.0 {
+            MiniquadInput::WheelDown
+        } else if x > 0.0 {
+            MiniquadInput::WheelRight
+        } else if x < 0.0 {
+            MiniquadInput::WheelLeft
+        } else {
+            return;
+        };
+        handler.on_input_down(input);
+        handler.on_input_up(input);
+    }
+
+    /// Call this from [`EventHandler::touch_event`](::miniquad::EventHandler::touch_event).
+    ///
+    /// Note that miniquad's default `touch_event` pretends touches are left mouse clicks;
+    /// once you forward touches here, that stops happening.
+    pub fn touch_event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<MiniquadInput, C>,
+        phase: TouchPhase,
+        id: u64,
+    ) {
+        match phase {
+            TouchPhase::Started => {
+                if self.touches.is_empty() {
+                    handler.on_input_down(MiniquadInput::Touch);
+                }
+                if !self.touches.contains(&id) {
+                    self.touches.push(id);
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.retain(|touch| *touch != id);
+                if self.touches.is_empty() {
+                    handler.on_input_up(MiniquadInput::Touch);
+                }
+            }
+            TouchPhase::Moved => {}
+        }
+    }
+
+    /// Call this from [`EventHandler::window_minimized_event`](::miniquad::EventHandler::window_minimized_event).
+    ///
+    /// This releases everything, because miniquad doesn't always send key-up events for keys released while
+    /// the window is in the background.
+    pub fn window_minimized_event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<MiniquadInput, C>,
+    ) {
+        handler.on_focus_lost();
+        self.touches.clear();
+    }
+}
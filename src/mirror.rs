@@ -0,0 +1,82 @@
+use std::hash::Hash;
+
+use ahash::AHashMap;
+
+/// A table of inputs that swap places, for making a left-handed (or otherwise mirrored) copy of a set of
+/// bindings.
+///
+/// Each pair swaps both ways, so mirroring twice gets the original bindings back, and two inputs can never
+/// end up on the same spot. Inputs that aren't in the table stay where they are.
+///
+/// What counts as the mirror image depends on the keyboard, so you give the pairs. Adapters have ready-made
+/// tables for their inputs, like [`WinitInput::left_handed`](crate::winit::WinitInput::left_handed).
+///
+/// ```rust
+/// # use puppetmaster::{BindingMirror, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![('w', "up"), ('a', "left"), (' ', "jump")]);
+/// let left_handed = BindingMirror::new([('w', 'i'), ('a', 'j'), ('s', 'k'), ('d', 'l')]);
+///
+/// // The player can tweak the mirrored bindings from here.
+/// let mirrored = left_handed.mirror_bindings(input_handler.control_config());
+/// *input_handler.control_config_mut() = mirrored;
+///
+/// input_handler.update(vec!['j', ' ']);
+/// assert!(input_handler.down("left"));
+/// assert!(input_handler.down("jump"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BindingMirror<I> {
+    /// Each input in the table, and the input it swaps with
+    swaps: AHashMap<I, I>,
+}
+
+impl<I: Hash + Eq + Clone> BindingMirror<I> {
+    /// Create a table from pairs of inputs that swap places.
+    pub fn new(pairs: impl IntoIterator<Item = (I, I)>) -> Self {
+        let mut mirror = Self {
+            swaps: AHashMap::new(),
+        };
+        for (a, b) in pairs {
+            mirror.add_swap(a, b);
+        }
+        mirror
+    }
+
+    /// Make two inputs swap places. If either was already swapping with something else, that pair is
+    /// broken up first.
+    pub fn add_swap(&mut self, a: I, b: I) {
+        self.remove_swap(&a);
+        self.remove_swap(&b);
+        if a != b {
+            self.swaps.insert(a.clone(), b.clone());
+            self.swaps.insert(b, a);
+        }
+    }
+
+    /// Stop an input swapping places. Returns what it was swapping with.
+    pub fn remove_swap(&mut self, input: &I) -> Option<I> {
+        let other = self.swaps.remove(input)?;
+        self.swaps.remove(&other);
+        Some(other)
+    }
+
+    /// Get where an input goes in the mirror image. This is the input itself if it isn't in the table.
+    pub fn mirror<'a>(&'a self, input: &'a I) -> &'a I {
+        self.swaps.get(input).unwrap_or(input)
+    }
+
+    /// Make a mirrored copy of a set of bindings, like a handler's
+    /// [`control_config`](crate::Controls::control_config).
+    pub fn mirror_bindings<'a, C: Clone + 'a>(
+        &self,
+        bindings: impl IntoIterator<Item = (&'a I, &'a C)>,
+    ) -> AHashMap<I, C>
+    where
+        I: 'a,
+    {
+        bindings
+            .into_iter()
+            .map(|(input, ctrl)| (self.mirror(input).clone(), ctrl.clone()))
+            .collect()
+    }
+}
@@ -0,0 +1,147 @@
+//! Lets Lua scripts read controls, through [`mlua`].
+//!
+//! Enable this with the `mlua` feature. It doesn't pick a Lua, so it works with whichever one the game already
+//! uses mlua with: either turn on one of mlua's own Lua features in your dependency on it, or turn on
+//! `mlua-lua54` or `mlua-luajit` here. Add `mlua-vendored` to build that Lua along with mlua instead of
+//! linking the system's.
+//!
+//! Scripts see controls by name, through a [`LuaControls`] userdata with `down`, `up`, `clicked`,
+//! `just_released`, `press_time`, and `release_time` methods for buttons, and `value` and `vector` for axes. It's built from an [`InputSnapshot`], so a script only ever sees the state from the
+//! last update, never a handler halfway through one.
+//!
+//! ```rust
+//! # use mlua::Lua;
+//! # use puppetmaster::{EventInputHandler, mlua::LuaBridge};
+//! #[derive(Clone, Copy, Hash, Eq, PartialEq)]
+//! enum Control {
+//!     Jump,
+//!     Shoot,
+//!     Aim,
+//! }
+//!
+//! let mut input_handler = EventInputHandler::new_with_controls(vec![
+//!     (' ', Control::Jump),
+//!     ('x', Control::Shoot),
+//! ]);
+//! let bridge = LuaBridge::new(vec![
+//!     ("jump", Control::Jump),
+//!     ("shoot", Control::Shoot),
+//!     ("aim", Control::Aim),
+//! ]);
+//! let lua = Lua::new();
+//!
+//! // Each frame ...
+//! input_handler.on_input_down(' ');
+//! input_handler.update();
+//! bridge.publish(&lua, "controls", input_handler.snapshot())?;
+//!
+//! let jumping: bool = lua.load(r#"return controls:clicked("jump")"#).eval()?;
+//! assert!(jumping);
+//!
+//! // Axes work the same way.
+//! input_handler.axis_config_mut().insert('h', Control::Aim);
+//! input_handler.on_axis_input('h', -0.5);
+//! input_handler.update();
+//! bridge.publish(&lua, "controls", input_handler.snapshot())?;
+//! let aim: f32 = lua.load(r#"return controls:value("aim")"#).eval()?;
+//! assert_eq!(aim, -0.5);
+//! # Ok::<(), mlua::Error>(())
+//! ```
+
+use std::{hash::Hash, sync::Arc};
+
+use ::mlua::{Error, Lua, Result, UserData, UserDataMethods};
+use ahash::AHashMap;
+
+use crate::InputSnapshot;
+
+/// Knows what your controls are called in Lua, and hands scripts [`LuaControls`] to read.
+#[derive(Debug)]
+pub struct LuaBridge<C> {
+    names: Arc<AHashMap<String, C>>,
+}
+
+impl<C: Hash + Eq + Clone + Send + Sync + 'static> LuaBridge<C> {
+    /// Create a new `LuaBridge` with the given names for the controls.
+    pub fn new(names: impl IntoIterator<Item = (impl Into<String>, C)>) -> Self {
+        Self {
+            names: Arc::new(
+                names
+                    .into_iter()
+                    .map(|(name, ctrl)| (name.into(), ctrl))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Wrap a snapshot up for Lua.
+    pub fn controls(&self, snapshot: InputSnapshot<C>) -> LuaControls<C> {
+        LuaControls {
+            snapshot,
+            names: Arc::clone(&self.names),
+        }
+    }
+
+    /// Set the given global to the controls in the snapshot.
+    ///
+    /// Call this after updating your input handler each frame, before running any scripts.
+    pub fn publish(&self, lua: &Lua, global: &str, snapshot: InputSnapshot<C>) -> Result<()> {
+        lua.globals().set(global, self.controls(snapshot))
+    }
+}
+
+impl<C> Clone for LuaBridge<C> {
+    fn clone(&self) -> Self {
+        Self {
+            names: Arc::clone(&self.names),
+        }
+    }
+}
+
+/// The state of the controls as of one update, as Lua userdata.
+///
+/// Every method takes the name of a control, and raises an error if there's no control by that name.
+#[derive(Debug)]
+pub struct LuaControls<C> {
+    snapshot: InputSnapshot<C>,
+    names: Arc<AHashMap<String, C>>,
+}
+
+impl<C: Hash + Eq + Clone> LuaControls<C> {
+    fn control(&self, name: &str) -> Result<C> {
+        self.names
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::runtime(format!("no control named {:?}", name)))
+    }
+}
+
+impl<C: Hash + Eq + Clone + Send + Sync + 'static> UserData for LuaControls<C> {
+    fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("press_time", |_, this, name: String| {
+            Ok(this.snapshot.press_time(this.control(&name)?))
+        });
+        methods.add_method("down", |_, this, name: String| {
+            Ok(this.snapshot.down(this.control(&name)?))
+        });
+        methods.add_method("up", |_, this, name: String| {
+            Ok(this.snapshot.up(this.control(&name)?))
+        });
+        methods.add_method("clicked", |_, this, name: String| {
+            Ok(this.snapshot.clicked(this.control(&name)?))
+        });
+        methods.add_method("release_time", |_, this, name: String| {
+            Ok(this.snapshot.release_time(this.control(&name)?))
+        });
+        methods.add_method("just_released", |_, this, name: String| {
+            Ok(this.snapshot.just_released(this.control(&name)?))
+        });
+        methods.add_method("value", |_, this, name: String| {
+            Ok(this.snapshot.value(this.control(&name)?))
+        });
+        // Lua gets the two parts as two return values: `local x, y = controls:vector("move")`
+        methods.add_method("vector", |_, this, name: String| {
+            Ok(this.snapshot.vector(this.control(&name)?))
+        });
+    }
+}
@@ -0,0 +1,516 @@
+use std::{collections::VecDeque, hash::Hash};
+
+use crate::{FrameNumber, InputHandler};
+
+/// How many frames a [`MotionMatcher`] remembers by default.
+const DEFAULT_BUFFER_LEN: u32 = 30;
+
+/// One step of a motion: a set of controls held together, like Down-Forward, or Forward + Punch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotionStep<C> {
+    /// The controls that have to be held for this step.
+    pub controls: Vec<MotionInput<C>>,
+    /// How many frames this step can come after the one before it. The first step's window is ignored.
+    pub window: u32,
+}
+
+impl<C> MotionStep<C> {
+    /// Create a step holding the given controls, coming at most `window` frames after the step before it.
+    /// Controls can be passed as they are, or as [`MotionInput`]s to use Forward and Back.
+    pub fn new(controls: impl IntoIterator<Item = impl Into<MotionInput<C>>>, window: u32) -> Self {
+        Self {
+            controls: controls.into_iter().map(Into::into).collect(),
+            window,
+        }
+    }
+}
+
+/// A control in a [`MotionStep`], or a direction that depends on which way the player is facing.
+///
+/// Forward and Back are the matcher's right and left [`Directions`], swapped around when it's
+/// [facing](MotionMatcher::set_facing) left, so one motion works on both sides of the opponent. A motion
+/// using them is never done until the matcher knows the directions.
+///
+/// ```rust
+/// # use puppetmaster::{Directions, Facing, MotionInput, MotionMatcher, MotionStep, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![
+///     ('s', "down"),
+///     ('a', "left"),
+///     ('d', "right"),
+///     ('j', "punch"),
+/// ]);
+/// let mut motions = MotionMatcher::new();
+/// motions.set_directions(Some(Directions {
+///     up: "up",
+///     down: "down",
+///     left: "left",
+///     right: "right",
+/// }));
+/// motions.add_motion(
+///     "fireball",
+///     [
+///         MotionStep::new(["down"], 0),
+///         MotionStep::new([MotionInput::Control("down"), MotionInput::Forward], 8),
+///         MotionStep::new([MotionInput::Forward, MotionInput::Control("punch")], 8),
+///     ],
+/// );
+///
+/// // Facing right, forward is right.
+/// for held in [vec!['s'], vec!['s', 'd'], vec!['d', 'j']] {
+///     input_handler.update(held);
+///     motions.update(&input_handler);
+/// }
+/// assert_eq!(motions.matched(), ["fireball"]);
+///
+/// // After crossing over, the same motion goes to the left.
+/// motions.set_facing(Facing::Left);
+/// for held in [vec![], vec!['s'], vec!['s', 'a'], vec!['a', 'j']] {
+///     input_handler.update(held);
+///     motions.update(&input_handler);
+/// }
+/// assert_eq!(motions.matched(), ["fireball"]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MotionInput<C> {
+    /// This control.
+    Control(C),
+    /// Toward the way the player is facing.
+    Forward,
+    /// Away from the way the player is facing.
+    Back,
+}
+
+impl<C> MotionInput<C> {
+    /// The control this stands for, facing the given way.
+    fn resolve<'a>(
+        &'a self,
+        directions: Option<&'a Directions<C>>,
+        facing: Facing,
+    ) -> Option<&'a C> {
+        match (self, facing) {
+            (Self::Control(ctrl), _) => Some(ctrl),
+            (Self::Forward, Facing::Right) | (Self::Back, Facing::Left) => {
+                directions.map(|dirs| &dirs.right)
+            }
+            (Self::Forward, Facing::Left) | (Self::Back, Facing::Right) => {
+                directions.map(|dirs| &dirs.left)
+            }
+        }
+    }
+}
+
+impl<C> From<C> for MotionInput<C> {
+    fn from(ctrl: C) -> Self {
+        Self::Control(ctrl)
+    }
+}
+
+/// Which way the player is facing, for the Forward and Back of a [`MotionInput`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Facing {
+    /// Forward is left.
+    Left,
+    /// Forward is right.
+    #[default]
+    Right,
+}
+
+/// How strict a [`MotionMatcher`] is about one motion.
+///
+/// The default is as strict about timing as the steps' own windows, and doesn't care what else happens
+/// between the steps. Give a motion its own leniency with
+/// [`add_motion_with_leniency`](MotionMatcher::add_motion_with_leniency); it's plain data, so it can be
+/// loaded from a file along with the motion, or swapped out for an easier one on a lower difficulty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotionLeniency {
+    /// How many frames each step can come after the one before it, in place of the steps' own windows.
+    /// `None` uses each step's window.
+    pub max_gap: Option<u32>,
+    /// How many stray presses the whole motion can have between its steps, of its own controls or the
+    /// [`Directions`], that aren't in the steps on either side, like Back pressed between Down and
+    /// Down-Forward. `None` allows any number.
+    pub max_dirty: Option<u32>,
+    /// If a diagonal, like Down-Forward, can stand in for a step with just one of its directions, like
+    /// Down. This needs the matcher to know the [`Directions`].
+    pub diagonals_count_as_cardinals: bool,
+}
+
+/// The four direction controls, so a [`MotionMatcher`] knows which controls are directions, and which of
+/// them make diagonals together.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Directions<C> {
+    /// The control for up.
+    pub up: C,
+    /// The control for down.
+    pub down: C,
+    /// The control for left.
+    pub left: C,
+    /// The control for right.
+    pub right: C,
+}
+
+impl<C: PartialEq> Directions<C> {
+    fn vertical(&self, ctrl: &C) -> bool {
+        *ctrl == self.up || *ctrl == self.down
+    }
+
+    fn horizontal(&self, ctrl: &C) -> bool {
+        *ctrl == self.left || *ctrl == self.right
+    }
+
+    /// Return if the control is one of the directions.
+    fn contains(&self, ctrl: &C) -> bool {
+        self.vertical(ctrl) || self.horizontal(ctrl)
+    }
+
+    /// Return if the two controls are directions at right angles, so holding both is a diagonal.
+    fn perpendicular(&self, a: &C, b: &C) -> bool {
+        (self.vertical(a) && self.horizontal(b)) || (self.horizontal(a) && self.vertical(b))
+    }
+}
+
+/// A motion being watched for.
+#[derive(Clone, Debug)]
+struct Motion<C, M> {
+    name: M,
+    steps: Vec<MotionStep<C>>,
+    leniency: MotionLeniency,
+    /// Each step's controls, with Forward and Back turned into directions for the way the player is facing,
+    /// or `None` if the matcher doesn't know the directions to turn them into
+    resolved: Option<Vec<Vec<C>>>,
+    /// Every control in any of the resolved steps
+    controls: Vec<C>,
+}
+
+impl<C: Hash + Eq + Clone, M> Motion<C, M> {
+    /// Work out the controls of each step, facing the given way.
+    fn resolve(&mut self, directions: Option<&Directions<C>>, facing: Facing) {
+        self.resolved = self
+            .steps
+            .iter()
+            .map(|step| {
+                step.controls
+                    .iter()
+                    .map(|input| input.resolve(directions, facing).cloned())
+                    .collect()
+            })
+            .collect();
+        self.controls.clear();
+        for ctrl in self.resolved.iter().flatten().flatten() {
+            if !self.controls.contains(ctrl) {
+                self.controls.push(ctrl.clone());
+            }
+        }
+    }
+
+    /// Return if the held controls are exactly what the step asks for, looking only at this motion's
+    /// controls.
+    fn holds(&self, step: &[C], held: &[C], directions: Option<&Directions<C>>) -> bool {
+        // A step with just one direction is also held on its diagonals, if the motion allows it
+        let cardinal = directions
+            .filter(|_| self.leniency.diagonals_count_as_cardinals)
+            .and_then(|directions| {
+                let mut dirs = step.iter().filter(|ctrl| directions.contains(ctrl));
+                match (dirs.next(), dirs.next()) {
+                    (Some(dir), None) => Some((directions, dir)),
+                    _ => None,
+                }
+            });
+        self.controls.iter().all(|ctrl| {
+            let is_held = held.contains(ctrl);
+            step.contains(ctrl) == is_held
+                || cardinal
+                    .is_some_and(|(directions, dir)| is_held && directions.perpendicular(dir, ctrl))
+        })
+    }
+
+    /// How many frames the step can come after the one before it.
+    fn window(&self, step: &MotionStep<C>) -> u32 {
+        self.leniency.max_gap.unwrap_or(step.window)
+    }
+}
+
+/// Watches the controls for timed sequences, like the quarter-circle-forward + punch of a fighting game.
+///
+/// Update it once a frame with a handler, after updating the handler. It keeps a buffer of the last few
+/// frames of held controls, and reports each motion on the frame its last step is done.
+///
+/// A step is done on the frame the controls it asks for become held. Only the controls used somewhere in
+/// the motion count, so a motion made of directions and Punch doesn't care if Kick is held too, but Down
+/// still held when Forward + Punch is pressed doesn't count as Forward + Punch. Each step has to come within
+/// its window of the step before it, and the whole motion within the buffer. A [`MotionLeniency`] loosens
+/// this up for one motion.
+///
+/// Steps can use [Forward and Back](MotionInput) instead of left and right, so one motion works whichever
+/// way the player is facing. Tell the matcher the [`Directions`] and keep its
+/// [facing](Self::set_facing) up to date.
+///
+/// ```rust
+/// # use puppetmaster::{MotionMatcher, MotionStep, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![
+///     ('s', "down"),
+///     ('d', "forward"),
+///     ('j', "punch"),
+/// ]);
+/// let mut motions = MotionMatcher::new();
+/// motions.add_motion(
+///     "fireball",
+///     [
+///         MotionStep::new(["down"], 0),
+///         MotionStep::new(["down", "forward"], 8),
+///         MotionStep::new(["forward", "punch"], 8),
+///     ],
+/// );
+///
+/// for held in [vec!['s'], vec!['s'], vec!['s', 'd'], vec!['d'], vec!['d']] {
+///     input_handler.update(held);
+///     assert!(motions.update(&input_handler).is_empty());
+/// }
+/// input_handler.update(vec!['d', 'j']);
+/// assert_eq!(motions.update(&input_handler), ["fireball"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MotionMatcher<C, M> {
+    motions: Vec<Motion<C, M>>,
+    directions: Option<Directions<C>>,
+    facing: Facing,
+    /// The controls held on each recent frame, oldest first
+    history: VecDeque<(FrameNumber, Vec<C>)>,
+    buffer_len: u32,
+    /// If frames have fallen out of the history, so the oldest one's frame before it is unknown
+    trimmed: bool,
+    /// The motions done this frame
+    matched: Vec<M>,
+}
+
+impl<C: Hash + Eq + Clone, M: Clone> MotionMatcher<C, M> {
+    /// Create a matcher with no motions, remembering 30 frames.
+    pub fn new() -> Self {
+        Self {
+            motions: Vec::new(),
+            directions: None,
+            facing: Facing::default(),
+            history: VecDeque::new(),
+            buffer_len: DEFAULT_BUFFER_LEN,
+            trimmed: false,
+            matched: Vec::new(),
+        }
+    }
+
+    /// Start watching for a motion, reported by the given name when it's done.
+    ///
+    /// Motions done on the same frame are reported in the order they were added, so add the ones your game
+    /// should prefer first, like a dragon punch before the fireball it starts with. Motions without any
+    /// steps are never done.
+    pub fn add_motion(&mut self, name: M, steps: impl IntoIterator<Item = MotionStep<C>>) {
+        self.add_motion_with_leniency(name, steps, MotionLeniency::default());
+    }
+
+    /// Start watching for a motion, like [`add_motion`](Self::add_motion), with its own [`MotionLeniency`].
+    pub fn add_motion_with_leniency(
+        &mut self,
+        name: M,
+        steps: impl IntoIterator<Item = MotionStep<C>>,
+        leniency: MotionLeniency,
+    ) {
+        let mut motion = Motion {
+            name,
+            steps: steps.into_iter().collect(),
+            leniency,
+            resolved: None,
+            controls: Vec::new(),
+        };
+        motion.resolve(self.directions.as_ref(), self.facing);
+        self.motions.push(motion);
+    }
+
+    /// Tell the matcher which controls are the four directions, or pass `None` to forget them. Diagonals
+    /// only [count as cardinals](MotionLeniency::diagonals_count_as_cardinals), and motions with
+    /// [Forward and Back](MotionInput) are only done, once it knows.
+    pub fn set_directions(&mut self, directions: Option<Directions<C>>) {
+        self.directions = directions;
+        self.resolve();
+    }
+
+    /// Get the controls the matcher uses as the four directions, if it knows them.
+    pub fn directions(&self) -> Option<&Directions<C>> {
+        self.directions.as_ref()
+    }
+
+    /// Set which way the player is facing, so Forward and Back in the motions point the right way.
+    ///
+    /// Motions are matched with the facing on the frame their last step is done, so turning around halfway
+    /// through one, like when jumping over the opponent, reads the whole thing the new way.
+    pub fn set_facing(&mut self, facing: Facing) {
+        self.facing = facing;
+        self.resolve();
+    }
+
+    /// Get which way the player is facing.
+    pub fn facing(&self) -> Facing {
+        self.facing
+    }
+
+    /// Work out the controls of every motion's steps again, after the directions or facing changed.
+    fn resolve(&mut self) {
+        for motion in self.motions.iter_mut() {
+            motion.resolve(self.directions.as_ref(), self.facing);
+        }
+    }
+
+    /// Stop watching for every motion.
+    pub fn clear_motions(&mut self) {
+        self.motions.clear();
+    }
+
+    /// Set how many frames the matcher remembers. A whole motion has to fit in this many frames.
+    pub fn set_buffer_len(&mut self, frames: u32) {
+        self.buffer_len = frames;
+        self.trim();
+    }
+
+    /// Get how many frames the matcher remembers.
+    pub fn buffer_len(&self) -> u32 {
+        self.buffer_len
+    }
+
+    /// Forget every frame seen so far, like after a round ends, so no motion can be finished with steps
+    /// from before.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.trimmed = false;
+        self.matched.clear();
+    }
+
+    /// Look at the handler's controls for this frame, and return the motions done on it. Call this once a
+    /// frame, after updating the handler; calling it again on the same frame looks at the frame again.
+    ///
+    /// If the handler's frame is earlier than the last one the matcher saw, like after restoring an older
+    /// [`SavedState`](crate::SavedState), the matcher starts over as if it had been [reset](Self::reset).
+    pub fn update(&mut self, handler: &impl InputHandler<C = C>) -> &[M] {
+        let frame = handler.frame();
+        let held = handler.all_pressed().cloned().collect::<Vec<_>>();
+        match self.history.back() {
+            Some(&(last, _)) if last == frame => {
+                self.history.pop_back();
+            }
+            // The handler went back in time, like after restoring an older state, so what came before is
+            // from a different timeline.
+            Some(&(last, _)) if last > frame => {
+                self.history.clear();
+                self.trimmed = false;
+            }
+            _ => {}
+        }
+        self.history.push_back((frame, held));
+        self.trim();
+
+        self.matched.clear();
+        for motion in self.motions.iter() {
+            if self.done(motion) {
+                self.matched.push(motion.name.clone());
+            }
+        }
+        &self.matched
+    }
+
+    /// Get the motions done on the last frame the matcher saw.
+    pub fn matched(&self) -> &[M] {
+        &self.matched
+    }
+
+    /// Forget frames that have fallen out of the buffer.
+    fn trim(&mut self) {
+        let Some(&(newest, _)) = self.history.back() else {
+            return;
+        };
+        while self.history.front().is_some_and(|(frame, _)| {
+            newest.saturating_sub(*frame) > self.buffer_len as FrameNumber
+        }) {
+            self.history.pop_front();
+            self.trimmed = true;
+        }
+    }
+
+    /// Return if the step was done on the frame at `idx` in the history.
+    fn step_done(&self, motion: &Motion<C, M>, step: &[C], idx: usize) -> bool {
+        let before = match idx.checked_sub(1) {
+            Some(prev) => self.history[prev].1.as_slice(),
+            None if self.trimmed => return false,
+            None => &[],
+        };
+        let directions = self.directions.as_ref();
+        motion.holds(step, &self.history[idx].1, directions)
+            && !motion.holds(step, before, directions)
+    }
+
+    /// Count the stray presses between the frames at `from` and `to` in the history: controls of the
+    /// motion, or directions, pressed in between that aren't in either step.
+    fn dirty(&self, motion: &Motion<C, M>, steps: [&[C]; 2], from: usize, to: usize) -> usize {
+        (from + 1..to)
+            .map(|i| {
+                let (held, before) = (&self.history[i].1, &self.history[i - 1].1);
+                held.iter()
+                    .filter(|ctrl| !before.contains(ctrl))
+                    .filter(|ctrl| {
+                        motion.controls.contains(ctrl)
+                            || self
+                                .directions
+                                .as_ref()
+                                .is_some_and(|dirs| dirs.contains(ctrl))
+                    })
+                    .filter(|ctrl| steps.iter().all(|step| !step.contains(ctrl)))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Return if the motion's last step was done this frame, with every step before it done in time.
+    fn done(&self, motion: &Motion<C, M>) -> bool {
+        let Some(steps) = &motion.resolved else {
+            return false;
+        };
+        let Some(last) = steps.len().checked_sub(1) else {
+            return false;
+        };
+        let mut idx = self.history.len() - 1;
+        if !self.step_done(motion, &steps[last], idx) {
+            return false;
+        }
+        let mut dirty = 0;
+        // Going backwards, taking the latest frame each step was done on leaves the most room for the
+        // steps before it.
+        for step in (0..last).rev() {
+            let window = motion.window(&motion.steps[step + 1]);
+            let after = self.history[idx].0;
+            let found = (0..idx)
+                .rev()
+                .take_while(|&i| after.saturating_sub(self.history[i].0) <= window as FrameNumber)
+                .find(|&i| self.step_done(motion, &steps[step], i));
+            let Some(found) = found else {
+                return false;
+            };
+            dirty += self.dirty(motion, [&steps[step], &steps[step + 1]], found, idx);
+            if motion
+                .leniency
+                .max_dirty
+                .is_some_and(|max| dirty > max as usize)
+            {
+                return false;
+            }
+            idx = found;
+        }
+        true
+    }
+}
+
+impl<C: Hash + Eq + Clone, M: Clone> Default for MotionMatcher<C, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,106 @@
+use std::hash::Hash;
+
+use crate::InputSnapshot;
+
+/// Every player's controls for one frame, for asking questions about all of them at once.
+///
+/// Local multiplayer games usually give each player their own input handler. Shared UI like a pause
+/// button, skipping a cutscene, or a vote cares about everyone, so gather up each player's
+/// [snapshot](InputSnapshot) each frame and ask this instead of looping over the handlers. Players are
+/// numbered in the order the snapshots were given, starting from 0.
+///
+/// ```rust
+/// # use puppetmaster::{Players, PollingInputHandler};
+/// let bindings = vec![("start", "pause"), ("a", "skip")];
+/// let mut handlers = vec![
+///     PollingInputHandler::new_with_controls(bindings.clone()),
+///     PollingInputHandler::new_with_controls(bindings.clone()),
+///     PollingInputHandler::new_with_controls(bindings),
+/// ];
+/// handlers[0].update(vec!["a"]);
+/// handlers[1].update(vec!["start"]);
+/// handlers[2].update(vec!["a"]);
+///
+/// let players = Players::new(handlers.iter().map(|handler| handler.snapshot()));
+/// assert!(players.any_player_clicked("pause"));
+/// // Skip the cutscene once everyone's voted to
+/// assert_eq!(players.players_holding("skip"), vec![0, 2]);
+/// assert!(!players.all_players_down("skip"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Players<C> {
+    snapshots: Vec<InputSnapshot<C>>,
+}
+
+impl<C: Hash + Eq + Clone> Players<C> {
+    /// Gather up each player's snapshot, in player order.
+    pub fn new(snapshots: impl IntoIterator<Item = InputSnapshot<C>>) -> Self {
+        Self {
+            snapshots: snapshots.into_iter().collect(),
+        }
+    }
+
+    /// Get how many players there are.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Return if there aren't any players.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Get one player's snapshot.
+    pub fn get(&self, player: usize) -> Option<&InputSnapshot<C>> {
+        self.snapshots.get(player)
+    }
+
+    /// Return if any player clicked the control this frame.
+    pub fn any_player_clicked(&self, ctrl: C) -> bool {
+        self.snapshots
+            .iter()
+            .any(|snapshot| snapshot.clicked(ctrl.clone()))
+    }
+
+    /// Return if any player is holding the control down.
+    pub fn any_player_down(&self, ctrl: C) -> bool {
+        self.snapshots
+            .iter()
+            .any(|snapshot| snapshot.down(ctrl.clone()))
+    }
+
+    /// Return if every player is holding the control down. This is false if there aren't any players.
+    pub fn all_players_down(&self, ctrl: C) -> bool {
+        !self.is_empty()
+            && self
+                .snapshots
+                .iter()
+                .all(|snapshot| snapshot.down(ctrl.clone()))
+    }
+
+    /// Get the first player who clicked the control this frame, like to find out who paused the game.
+    pub fn first_player_clicked(&self, ctrl: C) -> Option<usize> {
+        self.snapshots
+            .iter()
+            .position(|snapshot| snapshot.clicked(ctrl.clone()))
+    }
+
+    /// Get every player who clicked the control this frame.
+    pub fn players_clicked(&self, ctrl: C) -> Vec<usize> {
+        self.players_where(|snapshot| snapshot.clicked(ctrl.clone()))
+    }
+
+    /// Get every player who's holding the control down.
+    pub fn players_holding(&self, ctrl: C) -> Vec<usize> {
+        self.players_where(|snapshot| snapshot.down(ctrl.clone()))
+    }
+
+    fn players_where(&self, mut pred: impl FnMut(&InputSnapshot<C>) -> bool) -> Vec<usize> {
+        self.snapshots
+            .iter()
+            .enumerate()
+            .filter(|(_, snapshot)| pred(snapshot))
+            .map(|(player, _)| player)
+            .collect()
+    }
+}
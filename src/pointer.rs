@@ -0,0 +1,174 @@
+use std::{fmt, sync::Arc};
+
+/// A rectangle, for checking where the pointer is.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    /// The left edge of the rectangle.
+    pub x: f32,
+    /// The top edge of the rectangle.
+    pub y: f32,
+    /// How wide the rectangle is.
+    pub width: f32,
+    /// How tall the rectangle is.
+    pub height: f32,
+}
+
+impl Rect {
+    /// Create a new `Rect` from its top-left corner and size.
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Return if the point is inside the rectangle.
+    pub fn contains(&self, (x, y): (f32, f32)) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+type TransformFn = dyn Fn((f32, f32)) -> (f32, f32) + Send + Sync;
+
+/// Turns window coordinates into the coordinates the game wants the pointer in.
+#[derive(Clone)]
+pub(crate) struct PointerTransform(Arc<TransformFn>);
+
+impl PointerTransform {
+    pub fn new(transform: impl Fn((f32, f32)) -> (f32, f32) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(transform))
+    }
+}
+
+impl fmt::Debug for PointerTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PointerTransform")
+    }
+}
+
+/// Where the pointer is.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PointerState {
+    /// The position most recently reported, in window coordinates
+    reported: Option<(f32, f32)>,
+    transform: Option<PointerTransform>,
+    /// The position as of the last tick, after the transform
+    position: Option<(f32, f32)>,
+    /// The position as of the tick before that
+    previous: Option<(f32, f32)>,
+}
+
+impl PointerState {
+    /// Move forward a frame, picking up the latest reported position.
+    pub fn update(&mut self) {
+        self.previous = self.position;
+        self.position = match &self.transform {
+            Some(PointerTransform(transform)) => self.reported.map(|pos| transform(pos)),
+            None => self.reported,
+        };
+    }
+
+    pub fn report(&mut self, position: Option<(f32, f32)>) {
+        self.reported = position;
+    }
+
+    pub fn set_transform(&mut self, transform: Option<PointerTransform>) {
+        self.transform = transform;
+    }
+
+    pub fn position(&self) -> Option<(f32, f32)> {
+        self.position
+    }
+
+    pub fn delta(&self) -> (f32, f32) {
+        match (self.position, self.previous) {
+            (Some((x, y)), Some((px, py))) => (x - px, y - py),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// If the pointer moved, appeared, or left during the last tick.
+    pub fn moved(&self) -> bool {
+        self.position != self.previous
+    }
+
+    pub fn is_in(&self, rect: Rect) -> bool {
+        self.position.is_some_and(|pos| rect.contains(pos))
+    }
+
+    pub fn entered(&self, rect: Rect) -> bool {
+        self.is_in(rect) && !self.previous.is_some_and(|pos| rect.contains(pos))
+    }
+
+    pub fn exited(&self, rect: Rect) -> bool {
+        !self.is_in(rect) && self.previous.is_some_and(|pos| rect.contains(pos))
+    }
+}
+
+/// A drag of the pointer, from where the control was pressed to where the pointer is now.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Drag {
+    /// Where the pointer was when the control was pressed.
+    pub start: (f32, f32),
+    /// Where the pointer is now, or where it was when the drag ended.
+    pub position: (f32, f32),
+}
+
+impl Drag {
+    /// Get how far the pointer has moved since the drag started.
+    pub fn delta(&self) -> (f32, f32) {
+        (
+            self.position.0 - self.start.0,
+            self.position.1 - self.start.1,
+        )
+    }
+}
+
+/// Where a draggable control is in telling clicks from drags.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum DragState {
+    /// Not pressed.
+    #[default]
+    Idle,
+    /// Pressed, but the pointer hasn't moved far enough to be a drag yet.
+    Pressed(Drag),
+    /// Pressed and dragging.
+    Dragging(Drag),
+    /// Released this frame without having dragged.
+    Tapped,
+    /// Released this frame at the end of a drag.
+    Ended(Drag),
+}
+
+impl DragState {
+    /// Move forward a frame, given if the control is down and where the pointer is.
+    pub fn update(&mut self, down: bool, pointer: Option<(f32, f32)>, threshold: f32) {
+        *self = match (*self, down) {
+            (DragState::Pressed(mut drag) | DragState::Dragging(mut drag), true) => {
+                if let Some(pointer) = pointer {
+                    drag.position = pointer;
+                }
+                let (dx, dy) = drag.delta();
+                if matches!(self, DragState::Dragging(_)) || dx.hypot(dy) > threshold {
+                    DragState::Dragging(drag)
+                } else {
+                    DragState::Pressed(drag)
+                }
+            }
+            (DragState::Pressed(_), false) => DragState::Tapped,
+            (DragState::Dragging(drag), false) => DragState::Ended(drag),
+            // It can't start without knowing where the pointer is
+            (_, true) => match pointer {
+                Some(pointer) => DragState::Pressed(Drag {
+                    start: pointer,
+                    position: pointer,
+                }),
+                None => DragState::Idle,
+            },
+            (_, false) => DragState::Idle,
+        };
+    }
+}
@@ -1,8 +1,9 @@
 use std::hash::Hash;
 
-use ahash::AHashMap;
 use itertools::Itertools;
 
+use crate::{controls::Controls, handler::impl_input_handler, Recorder, SavedState, UpdateSummary};
+
 /// Input handler for an polling-based game engine.
 ///
 /// Use this when your game engine provides inputs via an poll system.
@@ -63,10 +64,8 @@ use itertools::Itertools;
 /// `I` is the type of your inputs, and `C` is the type of your controls.
 #[derive(Clone, Debug)]
 pub struct PollingInputHandler<I, C> {
-    /// Maps inputs to the controls they activate
-    control_config: AHashMap<I, C>,
-    /// How long each control has been pressed
-    control_time: AHashMap<C, u32>,
+    /// The bindings, settings, and state of the controls
+    controls: Controls<I, C>,
 }
 
 impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> PollingInputHandler<I, C> {
@@ -80,10 +79,8 @@ impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> PollingInputHandler<I, C> {
     /// If two entries in the iterator have the same input, the first one will be clobbered
     /// and the second one will remain.
     pub fn new_with_controls(map: impl IntoIterator<Item = (I, C)>) -> Self {
-        let control_config = map.into_iter().collect();
         Self {
-            control_config,
-            control_time: AHashMap::new(),
+            controls: Controls::with_config(map.into_iter().collect()),
         }
     }
     /// Manually unpress all inputs.
@@ -91,60 +88,239 @@ impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> PollingInputHandler<I, C> {
     /// Note you should *not* have to call this at the beginning of your loop. (In fact, if you do,
     /// your inputs will never be pressed.)
     pub fn clear_inputs(&mut self) {
-        self.control_time.clear();
+        self.controls.changed();
+        self.controls.states.clear();
+        self.controls.raw_inputs.clear();
+    }
+
+    /// Call this when the window loses focus. This releases everything; the controls are released at the next
+    /// update.
+    ///
+    /// Most platforms don't report inputs correctly while the game is in the background, so without this a
+    /// control can look held until the window comes back.
+    pub fn on_focus_lost(&mut self) {
+        self.controls.changed();
+        self.controls.states.focus_lost();
     }
 
     /// Update the input handler, giving it the inputs that are currently pressed this frame.
     ///
     /// You MUST CALL THIS FIRST THING in your game loop.
     /// Otherwise things won't get updated correctly.
-    pub fn update(&mut self, pressed_inputs: impl IntoIterator<Item = I>) {
+    ///
+    /// Returns a summary of what changed, so you can skip work on frames where nothing did.
+    pub fn update(&mut self, pressed_inputs: impl IntoIterator<Item = I>) -> UpdateSummary {
+        let pressed_inputs = pressed_inputs.into_iter().collect_vec();
+        self.update_borrowed(&pressed_inputs)
+    }
+
+    /// Update the input handler, giving it references to the inputs that are currently pressed this frame.
+    ///
+    /// This is the same as [`update`](Self::update), but it lets you pass something like a `&HashSet<I>` your
+    /// game engine owns without cloning it every frame.
+    pub fn update_borrowed<'a>(
+        &mut self,
+        pressed_inputs: impl IntoIterator<Item = &'a I>,
+    ) -> UpdateSummary
+    where
+        I: 'a,
+    {
+        self.controls.changed();
+        let pressed_inputs = pressed_inputs.into_iter().collect_vec();
+        let filtered = self.controls.filters.apply(pressed_inputs.iter().copied());
+        let pressed_inputs = match &filtered {
+            Some(filtered) => filtered.iter().collect_vec(),
+            None => pressed_inputs,
+        };
         // We want to logical-OR any keypresses into one control.
         // (We collect to a vec because we probably won't be pressing more than 3-4 keys per frame, and I bet the O(n) lookup doesn't
         // get good until then.)
         let pressed_controls = pressed_inputs
-            .into_iter()
-            .flat_map(|input| {
-                let _ = &self;
-                self.control_config.get(&input)
+            .iter()
+            .flat_map(|&input| {
+                self.controls
+                    .control_config
+                    .get(self.controls.input_remap.get(input).unwrap_or(input))
             })
             .cloned()
             .collect_vec();
-        for ctrl in self.control_config.values() {
-            if pressed_controls.contains(ctrl) {
-                *self.control_time.entry(ctrl.clone()).or_default() += 1;
-            } else {
-                self.control_time.insert(ctrl.clone(), 0);
-            }
+        self.controls.axes.update();
+        self.tick_controls(&pressed_controls);
+        let (control_config, input_remap) =
+            (&self.controls.control_config, &self.controls.input_remap);
+        self.controls
+            .raw_inputs
+            .observe(&pressed_inputs, self.controls.states.frame(), |input| {
+                control_config.contains_key(input_remap.get(input).unwrap_or(input))
+            });
+        self.controls.states.notify_listeners();
+        self.controls.states.summary()
+    }
+
+    fn tick_controls(&mut self, pressed_controls: &[C]) {
+        self.controls.states.count_inputs(pressed_controls);
+        // Several inputs can map to the same control; make sure each control only ticks once.
+        self.controls
+            .states
+            .tick(self.controls.control_config.values().unique(), |ctrl| {
+                pressed_controls.contains(ctrl)
+            });
+    }
+
+    /// Start or stop recording inputs that are pressed but aren't mapped to any control. This is off by default.
+    pub fn set_track_unmapped(&mut self, track: bool) {
+        self.controls.raw_inputs.set_track_unmapped(track);
+    }
+
+    /// Return if the handler is recording unmapped inputs.
+    pub fn tracks_unmapped(&self) -> bool {
+        self.controls.raw_inputs.tracks_unmapped()
+    }
+
+    /// Get the inputs that were pressed this frame but aren't mapped to any control, if
+    /// [tracking](Self::set_track_unmapped) is on.
+    ///
+    /// Use this to capture a new binding, or to tell the player they pressed something that doesn't do
+    /// anything.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("space", "jump")]);
+    /// input_handler.set_track_unmapped(true);
+    ///
+    /// input_handler.update(vec!["space", "k"]);
+    /// assert_eq!(input_handler.unmapped_inputs(), ["k"]);
+    /// ```
+    pub fn unmapped_inputs(&self) -> &[I] {
+        self.controls.raw_inputs.unmapped()
+    }
+
+    /// Save the bindings, settings, and which inputs are held down, so the handler can be restored after a
+    /// hot reload. See [`SavedState`] for what's included.
+    pub fn save_state(&self) -> SavedState<I, C> {
+        SavedState {
+            version: SavedState::<I, C>::VERSION,
+            bindings: self
+                .controls
+                .control_config
+                .iter()
+                .map(|(input, ctrl)| (input.clone(), ctrl.clone()))
+                .collect(),
+            remap: self
+                .controls
+                .input_remap
+                .iter()
+                .map(|(from, to)| (from.clone(), to.clone()))
+                .collect(),
+            timing: *self.controls.states.timing(),
+            control_settings: self.controls.states.control_settings(),
+            axes: self.controls.axes.save(),
+            frame: self.controls.states.frame(),
+            held_inputs: self.controls.raw_inputs.held().cloned().collect(),
+            pressed_controls: self.controls.states.pressed_times(),
+            toggled_on: self.controls.states.toggled_on(),
         }
     }
 
-    /// Return the number of frames the given control has been pressed for
-    pub fn press_time(&self, ctrl: C) -> u32 {
-        self.control_time.get(&ctrl).copied().unwrap_or_default()
+    /// Restore a state saved with [`save_state`](Self::save_state), replacing the bindings, settings, and
+    /// what's held down.
+    ///
+    /// Returns `false` and leaves the handler alone if the state was saved in a different
+    /// [version](SavedState::VERSION) of the format.
+    pub fn restore_state(&mut self, state: SavedState<I, C>) -> bool {
+        if state.version != SavedState::<I, C>::VERSION {
+            return false;
+        }
+        self.controls.changed();
+        self.controls.control_config = state.bindings.into_iter().collect();
+        self.controls.input_remap = state.remap.into_iter().collect();
+        self.controls.states.set_timing(state.timing);
+        self.controls
+            .states
+            .restore_control_settings(state.control_settings);
+        self.controls.axes.restore(state.axes);
+        self.controls
+            .states
+            .restore(state.frame, state.pressed_controls, state.toggled_on);
+        self.controls.raw_inputs.restore_held(state.held_inputs);
+        self.controls.raw_inputs.forget_last();
+        true
     }
 
-    /// Return if this control is held down (ie, the corresponding input has been pressed for 1 or more frames).
-    pub fn down(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) >= 1
+    /// Record the inputs held down this frame, with the bindings and everything else not applied yet. Call
+    /// this once per frame, after updating. See [`Recorder`] for the difference between recording inputs and
+    /// controls.
+    pub fn record_inputs(&self, recorder: &mut Recorder<I>) {
+        recorder.record(self.controls.raw_inputs.held().cloned());
     }
 
-    /// Return if this control is up.
-    pub fn up(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) == 0
+    /// Update the handler from recorded controls instead of its inputs, holding down exactly the controls given.
+    /// Call this instead of `update`, with a frame from a recording made by
+    /// [`record_controls`](Controls::record_controls).
+    ///
+    /// The controls were already worked out when they were recorded, so toggles, debouncing, long presses,
+    /// and everything else that turns inputs into controls are skipped.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{PollingInputHandler, Recorder};
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("c", "crouch")]);
+    /// input_handler.set_toggle("crouch", true);
+    ///
+    /// let mut recorder = Recorder::new();
+    /// for held in [vec!["c"], vec![], vec![], vec!["c"], vec![]] {
+    ///     input_handler.update(held);
+    ///     input_handler.record_controls(&mut recorder);
+    /// }
+    /// let recording = recorder.finish().unwrap();
+    ///
+    /// // Play it back into a handler with no bindings at all
+    /// let mut replay_handler = PollingInputHandler::<(), _>::new();
+    /// let crouching = recording
+    ///     .held_frames()
+    ///     .map(|held| {
+    ///         replay_handler.replay_controls(held);
+    ///         replay_handler.down("crouch")
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(crouching, vec![true, true, true, false, false]);
+    /// ```
+    pub fn replay_controls(&mut self, held: impl IntoIterator<Item = C>) -> UpdateSummary {
+        self.controls.changed();
+        self.controls.states.tick_replay(held);
+        self.controls.states.notify_listeners();
+        self.controls.states.summary()
     }
 
-    /// Return if this control was *clicked* down this frame (ie, the corresponding input was *just* pressed this frame).
-    pub fn clicked(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) == 1
+    /// Give the handler the value of every axis input, like sticks and triggers, from -1 to 1 (or 0 to 1 for
+    /// triggers). Call this each frame before updating. Axis inputs that aren't given are at 0.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let mut input_handler = PollingInputHandler::new_with_controls(vec![("space", "jump")]);
+    /// input_handler.axis_config_mut().insert("left_stick_x", "steer");
+    /// input_handler.set_axis_deadzone("steer", 0.25);
+    ///
+    /// input_handler.set_axis_inputs(vec![("left_stick_x", 0.625)]);
+    /// input_handler.update(vec!["space"]);
+    /// assert_eq!(input_handler.value("steer"), 0.5);
+    /// assert!(input_handler.clicked("jump"));
+    ///
+    /// // Stick drift stays inside the deadzone.
+    /// input_handler.set_axis_inputs(vec![("left_stick_x", -0.1)]);
+    /// input_handler.update(vec![]);
+    /// assert_eq!(input_handler.value("steer"), 0.0);
+    /// ```
+    pub fn set_axis_inputs(&mut self, values: impl IntoIterator<Item = (I, f32)>) {
+        self.controls.axes.set_inputs(values);
     }
 }
 
+impl_input_handler!(PollingInputHandler);
+
 impl<I, C> Default for PollingInputHandler<I, C> {
     fn default() -> Self {
         Self {
-            control_config: AHashMap::new(),
-            control_time: AHashMap::new(),
+            controls: Controls::default(),
         }
     }
 }
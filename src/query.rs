@@ -1,8 +1,9 @@
 use std::hash::Hash;
 
-use ahash::AHashMap;
 use itertools::Itertools;
 
+use crate::{controls::Controls, handler::impl_input_handler, Recorder, SavedState, UpdateSummary};
+
 /// Input handler for an query-based game engine.
 ///
 /// Use this when your game engine provides inputs via a function you can call to
@@ -49,7 +50,7 @@ use itertools::Itertools;
 /// let ctx = get_context();
 ///
 /// loop {     
-///     input_handler.update(|input| ctx.is_key_pressed(input));
+///     input_handler.update(|input| ctx.is_key_pressed(*input));
 ///
 ///     // Now do game logic ...
 ///     if input_handler.down(Control::Left) {
@@ -68,10 +69,8 @@ use itertools::Itertools;
 /// `I` is the type of your inputs, and `C` is the type of your controls.
 #[derive(Clone, Debug)]
 pub struct QueryInputHandler<I, C> {
-    /// Maps inputs to the controls they activate
-    control_config: AHashMap<I, C>,
-    /// How long each control has been pressed
-    control_time: AHashMap<C, u32>,
+    /// The bindings, settings, and state of the controls
+    controls: Controls<I, C>,
 }
 
 impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> QueryInputHandler<I, C> {
@@ -85,10 +84,8 @@ impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> QueryInputHandler<I, C> {
     /// If two entries in the iterator have the same input, the first one will be clobbered
     /// and the second one will remain.
     pub fn new_with_controls(map: impl IntoIterator<Item = (I, C)>) -> Self {
-        let control_config = map.into_iter().collect();
         Self {
-            control_config,
-            control_time: AHashMap::new(),
+            controls: Controls::with_config(map.into_iter().collect()),
         }
     }
     /// Manually unpress all inputs.
@@ -96,7 +93,19 @@ impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> QueryInputHandler<I, C> {
     /// Note you should *not* have to call this at the beginning of your loop. (In fact, if you do,
     /// your inputs will never be pressed.)
     pub fn clear_inputs(&mut self) {
-        self.control_time.clear();
+        self.controls.changed();
+        self.controls.states.clear();
+        self.controls.raw_inputs.clear();
+    }
+
+    /// Call this when the window loses focus. This releases everything; the controls are released at the next
+    /// update.
+    ///
+    /// Most platforms don't report inputs correctly while the game is in the background, so without this a
+    /// control can look held until the window comes back.
+    pub fn on_focus_lost(&mut self) {
+        self.controls.changed();
+        self.controls.states.focus_lost();
     }
 
     /// Update the input handler. Give it a function that returns `true` if the given input is pressed this frame,
@@ -105,58 +114,158 @@ impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> QueryInputHandler<I, C> {
     /// Depending on your grame engine's architecture, you might need some kind of `Context` struct to query the state
     /// from. In this case, pass it a closure that borrows the `Context`.
     ///
+    /// The input is passed by reference, so inputs that are expensive to clone aren't cloned every frame.
+    ///
     /// You MUST CALL THIS FIRST THING in your game loop.
     /// Otherwise things won't get updated correctly.
-    pub fn update(&mut self, mut is_pressed: impl FnMut(I) -> bool) {
+    ///
+    /// Returns a summary of what changed, so you can skip work on frames where nothing did.
+    pub fn update(&mut self, mut is_pressed: impl FnMut(&I) -> bool) -> UpdateSummary {
+        self.controls.changed();
         // We want to logical-OR any keypresses into one control.
         // (We collect to a vec because we probably won't be pressing more than 3-4 keys per frame, and I bet the O(n) lookup doesn't
         // get good until then.)
-        let pressed_controls = self
-            .control_config
-            .iter()
-            .filter_map(|(input, ctrl)| {
-                if is_pressed(input.clone()) {
-                    Some(ctrl.clone())
-                } else {
-                    None
-                }
-            })
-            .collect_vec();
-        for ctrl in self.control_config.values() {
-            if pressed_controls.contains(ctrl) {
-                *self.control_time.entry(ctrl.clone()).or_default() += 1;
+        //
+        // Once a control is known to be pressed we skip querying the rest of its inputs, because querying
+        // might be expensive on the engine's end (FFI, syscalls, etc).
+        //
+        // With remapping, an input is pressed if it's pressed itself (and not remapped to something else),
+        // or if any input remapped to it is pressed.
+        let mut pressed_controls = Vec::new();
+        let mut pressed_inputs = Vec::new();
+        for (input, ctrl) in self.controls.control_config.iter() {
+            // Controls that retrigger need to know about all their inputs, though.
+            if pressed_controls.contains(ctrl) && !self.controls.states.retriggers(ctrl) {
+                continue;
+            }
+            let pressed = if !self.controls.input_remap.contains_key(input) && is_pressed(input) {
+                Some(input)
             } else {
-                self.control_time.insert(ctrl.clone(), 0);
+                self.controls
+                    .input_remap
+                    .iter()
+                    .find(|&(from, to)| to == input && is_pressed(from))
+                    .map(|(from, _)| from)
+            };
+            if let Some(pressed) = pressed {
+                pressed_controls.push(ctrl.clone());
+                pressed_inputs.push(pressed);
             }
         }
+        let filtered = self.controls.filters.apply(pressed_inputs.iter().copied());
+        if let Some(filtered) = &filtered {
+            pressed_inputs = filtered.iter().collect();
+            pressed_controls = pressed_inputs
+                .iter()
+                .filter_map(|&input| {
+                    self.controls
+                        .control_config
+                        .get(self.controls.input_remap.get(input).unwrap_or(input))
+                })
+                .cloned()
+                .collect();
+        }
+        self.controls.axes.update();
+        self.controls.states.count_inputs(&pressed_controls);
+        // Several inputs can map to the same control; make sure each control only ticks once.
+        self.controls
+            .states
+            .tick(self.controls.control_config.values().unique(), |ctrl| {
+                pressed_controls.contains(ctrl)
+            });
+        // Everything this handler sees is mapped
+        self.controls
+            .raw_inputs
+            .observe(&pressed_inputs, self.controls.states.frame(), |_| true);
+        self.controls.states.notify_listeners();
+        self.controls.states.summary()
     }
 
-    /// Return the number of frames the given control has been pressed for.
-    pub fn press_time(&self, ctrl: C) -> u32 {
-        self.control_time.get(&ctrl).copied().unwrap_or_default()
+    /// Save the bindings, settings, and which inputs are held down, so the handler can be restored after a
+    /// hot reload. See [`SavedState`] for what's included.
+    pub fn save_state(&self) -> SavedState<I, C> {
+        SavedState {
+            version: SavedState::<I, C>::VERSION,
+            bindings: self
+                .controls
+                .control_config
+                .iter()
+                .map(|(input, ctrl)| (input.clone(), ctrl.clone()))
+                .collect(),
+            remap: self
+                .controls
+                .input_remap
+                .iter()
+                .map(|(from, to)| (from.clone(), to.clone()))
+                .collect(),
+            timing: *self.controls.states.timing(),
+            control_settings: self.controls.states.control_settings(),
+            axes: self.controls.axes.save(),
+            frame: self.controls.states.frame(),
+            held_inputs: self.controls.raw_inputs.held().cloned().collect(),
+            pressed_controls: self.controls.states.pressed_times(),
+            toggled_on: self.controls.states.toggled_on(),
+        }
     }
 
-    /// Return if this control is held down (ie, the corresponding input has been pressed for 1 or more frames).
-    pub fn down(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) >= 1
+    /// Restore a state saved with [`save_state`](Self::save_state), replacing the bindings, settings, and
+    /// what's held down.
+    ///
+    /// Returns `false` and leaves the handler alone if the state was saved in a different
+    /// [version](SavedState::VERSION) of the format.
+    pub fn restore_state(&mut self, state: SavedState<I, C>) -> bool {
+        if state.version != SavedState::<I, C>::VERSION {
+            return false;
+        }
+        self.controls.changed();
+        self.controls.control_config = state.bindings.into_iter().collect();
+        self.controls.input_remap = state.remap.into_iter().collect();
+        self.controls.states.set_timing(state.timing);
+        self.controls
+            .states
+            .restore_control_settings(state.control_settings);
+        self.controls.axes.restore(state.axes);
+        self.controls
+            .states
+            .restore(state.frame, state.pressed_controls, state.toggled_on);
+        self.controls.raw_inputs.restore_held(state.held_inputs);
+        self.controls.raw_inputs.forget_last();
+        true
     }
 
-    /// Return if this control is up.
-    pub fn up(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) == 0
+    /// Record the inputs held down this frame, with the bindings and everything else not applied yet. Call
+    /// this once per frame, after updating. See [`Recorder`] for the difference between recording inputs and
+    /// controls.
+    pub fn record_inputs(&self, recorder: &mut Recorder<I>) {
+        recorder.record(self.controls.raw_inputs.held().cloned());
     }
 
-    /// Return if this control was *clicked* down this frame (ie, the corresponding input was *just* pressed this frame).
-    pub fn clicked(&self, ctrl: C) -> bool {
-        self.press_time(ctrl) == 1
+    /// Update the handler from recorded controls instead of its inputs, holding down exactly the controls given.
+    /// Call this instead of `update`, with a frame from a recording made by
+    /// [`record_controls`](Controls::record_controls).
+    ///
+    /// The controls were already worked out when they were recorded, so toggles, debouncing, long presses,
+    /// and everything else that turns inputs into controls are skipped.
+    pub fn replay_controls(&mut self, held: impl IntoIterator<Item = C>) -> UpdateSummary {
+        self.controls.changed();
+        self.controls.states.tick_replay(held);
+        self.controls.states.notify_listeners();
+        self.controls.states.summary()
+    }
+
+    /// Ask for the value of every bound axis input, like sticks and triggers, from -1 to 1 (or 0 to 1 for
+    /// triggers). Call this each frame before updating.
+    pub fn query_axes(&mut self, value: impl FnMut(&I) -> f32) {
+        self.controls.axes.query(value);
     }
 }
 
+impl_input_handler!(QueryInputHandler);
+
 impl<I, C> Default for QueryInputHandler<I, C> {
     fn default() -> Self {
         Self {
-            control_config: AHashMap::new(),
-            control_time: AHashMap::new(),
+            controls: Controls::default(),
         }
     }
 }
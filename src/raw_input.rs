@@ -0,0 +1,123 @@
+use std::hash::Hash;
+
+use ahash::AHashSet;
+
+use crate::FrameNumber;
+
+/// What a handler has seen of its raw inputs, before they're mapped to controls.
+#[derive(Clone, Debug)]
+pub(crate) struct RawInputs<I> {
+    /// Inputs that were held as of the last frame
+    held: AHashSet<I>,
+    /// The input most recently pressed, and the frame it was pressed on
+    last: Option<(I, FrameNumber)>,
+    /// If unmapped inputs should be recorded
+    track_unmapped: bool,
+    /// Inputs pressed this frame that aren't mapped to a control
+    unmapped: Vec<I>,
+    /// If waiting for any input, the first one pressed since it started waiting
+    waiting: Option<Option<I>>,
+}
+
+impl<I: Hash + Eq + Clone> RawInputs<I> {
+    /// Record the inputs held this frame, for handlers that don't see presses and releases directly.
+    pub fn observe<'a>(
+        &mut self,
+        inputs: &[&'a I],
+        frame: FrameNumber,
+        is_mapped: impl Fn(&I) -> bool,
+    ) where
+        I: 'a,
+    {
+        self.new_frame();
+        self.held.retain(|input| inputs.contains(&input));
+        for &input in inputs {
+            if !self.held.contains(input) {
+                self.held.insert(input.clone());
+                self.pressed(input.clone(), frame, is_mapped(input));
+            }
+        }
+    }
+
+    /// Forget about last frame's unmapped inputs.
+    pub fn new_frame(&mut self) {
+        self.unmapped.clear();
+    }
+
+    /// Record an input being pressed.
+    pub fn pressed(&mut self, input: I, frame: FrameNumber, mapped: bool) {
+        if self.track_unmapped && !mapped && !self.unmapped.contains(&input) {
+            self.unmapped.push(input.clone());
+        }
+        if let Some(first @ None) = &mut self.waiting {
+            *first = Some(input.clone());
+        }
+        self.last = Some((input, frame));
+    }
+
+    pub fn last(&self) -> Option<(&I, FrameNumber)> {
+        self.last.as_ref().map(|(input, frame)| (input, *frame))
+    }
+
+    pub fn wait_for_any(&mut self) {
+        self.waiting = Some(None);
+    }
+
+    pub fn stop_waiting(&mut self) {
+        self.waiting = None;
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        self.waiting.is_some()
+    }
+
+    pub fn any_pressed(&self) -> Option<&I> {
+        self.waiting.as_ref().and_then(Option::as_ref)
+    }
+
+    pub fn set_track_unmapped(&mut self, track: bool) {
+        self.track_unmapped = track;
+        if !track {
+            self.unmapped.clear();
+        }
+    }
+
+    pub fn tracks_unmapped(&self) -> bool {
+        self.track_unmapped
+    }
+
+    pub fn unmapped(&self) -> &[I] {
+        &self.unmapped
+    }
+
+    pub fn held(&self) -> impl Iterator<Item = &I> + '_ {
+        self.held.iter()
+    }
+
+    /// Pretend the inputs were already held as of the last frame, so they don't count as pressed.
+    pub fn restore_held(&mut self, held: impl IntoIterator<Item = I>) {
+        self.held = held.into_iter().collect();
+    }
+
+    /// Forget which input was pressed last, like when the frame count goes back to an earlier frame.
+    pub fn forget_last(&mut self) {
+        self.last = None;
+    }
+
+    /// Forget which inputs are held, so they count as pressed again next frame.
+    pub fn clear(&mut self) {
+        self.held.clear();
+    }
+}
+
+impl<I> Default for RawInputs<I> {
+    fn default() -> Self {
+        Self {
+            held: AHashSet::new(),
+            last: None,
+            track_unmapped: false,
+            unmapped: Vec::new(),
+            waiting: None,
+        }
+    }
+}
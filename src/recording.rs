@@ -0,0 +1,436 @@
+use std::{collections::VecDeque, fmt, hash::Hash};
+
+use ahash::AHashSet;
+
+/// One change in a [`Recording`]: what was pressed and released on a frame.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedChange<T> {
+    /// How many frames after the previous change this happened. For the first change, this is counted
+    /// from the first frame of the recording, so a change on the very first frame waits 0.
+    pub wait: u64,
+    /// What was pressed on this frame.
+    pub pressed: Vec<T>,
+    /// What was released on this frame.
+    pub released: Vec<T>,
+}
+
+/// Recorded input, stored as only the frames where something changed.
+///
+/// Frames where nothing is pressed or released take up no space at all, so even long sessions with
+/// sparse input stay tiny. It's all public fields and plain collections, so you can serialize it however
+/// you like. Make one with a [`Recorder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording<T> {
+    /// What was already held down when the recording starts.
+    pub start: Vec<T>,
+    /// Every change, in order.
+    pub changes: VecDeque<RecordedChange<T>>,
+    /// How many frames long the recording is.
+    pub frames: u64,
+}
+
+impl<T> Recording<T> {
+    /// Create a new, empty recording.
+    pub fn new() -> Self {
+        Self {
+            start: Vec::new(),
+            changes: VecDeque::new(),
+            frames: 0,
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> Recording<T> {
+    /// Play the recording back, getting what was held down on each frame.
+    ///
+    /// This is exactly what a [`PollingInputHandler`](crate::PollingInputHandler) wants each update.
+    pub fn held_frames(&self) -> impl Iterator<Item = Vec<T>> + '_ {
+        let mut held = self.start.iter().cloned().collect::<AHashSet<_>>();
+        let mut changes = self.changes.iter().peekable();
+        // How many frames until the next change
+        let mut wait = changes.peek().map(|change| change.wait);
+        (0..self.frames).map(move |_| {
+            if wait == Some(0) {
+                let change = changes.next().unwrap();
+                for released in change.released.iter() {
+                    held.remove(released);
+                }
+                held.extend(change.pressed.iter().cloned());
+                wait = changes.peek().map(|change| change.wait);
+            }
+            wait = wait.map(|wait| wait.saturating_sub(1));
+            held.iter().cloned().collect()
+        })
+    }
+
+    /// Go through the recording a frame at a time, getting the change on each frame, if there is one.
+    ///
+    /// This is for playing back recorded inputs into an [`EventInputHandler`](crate::EventInputHandler)
+    /// with [`on_recorded_change`](crate::EventInputHandler::on_recorded_change). Anything held down at the
+    /// [`start`](Self::start) isn't included, so press those first.
+    pub fn frame_changes(&self) -> impl Iterator<Item = Option<&RecordedChange<T>>> + '_ {
+        let mut changes = self.changes.iter().peekable();
+        let mut wait = changes.peek().map(|change| change.wait);
+        (0..self.frames).map(move |_| {
+            let change = if wait == Some(0) {
+                let change = changes.next();
+                wait = changes.peek().map(|change| change.wait);
+                change
+            } else {
+                None
+            };
+            wait = wait.map(|wait| wait.saturating_sub(1));
+            change
+        })
+    }
+
+    /// Cut frames off the beginning so it's at most `frames` long. The changes that are cut off are folded into
+    /// [`start`](Self::start).
+    pub fn trim_start(&mut self, frames: u64) {
+        let Some(mut excess) = self.frames.checked_sub(frames).filter(|&excess| excess > 0) else {
+            return;
+        };
+        let mut held = self.start.drain(..).collect::<AHashSet<_>>();
+        while let Some(change) = self.changes.front() {
+            if change.wait >= excess {
+                break;
+            }
+            let change = self.changes.pop_front().unwrap();
+            excess -= change.wait;
+            for released in change.released.iter() {
+                held.remove(released);
+            }
+            held.extend(change.pressed);
+        }
+        if let Some(change) = self.changes.front_mut() {
+            change.wait -= excess;
+        }
+        self.start = held.into_iter().collect();
+        self.frames = frames;
+    }
+}
+
+impl<T> Default for Recording<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Records what's held down each frame into a [`Recording`], or streams it out as it happens.
+///
+/// Call [`record`](Self::record) once per frame with everything held down. The handlers can do this for
+/// you, at either of two layers:
+///
+/// - `record_inputs` records the raw inputs. Replays of these go through the bindings again, so they're
+///   good for input displays and for checking what the player physically did. Play them back by giving
+///   the frames to a handler's `update` (or to
+///   [`EventInputHandler::on_recorded_change`](crate::EventInputHandler::on_recorded_change)).
+/// - `record_controls` records the controls. Replays of these don't care what device or bindings were used,
+///   so they're good for deterministic playback of a game. Play them back with `replay_controls` instead
+///   of `update`.
+///
+/// There are three ways to keep what's recorded:
+///
+/// - [`Recorder::new`] keeps the whole recording in memory.
+/// - [`Recorder::rolling`] only keeps the last few frames, for things like an always-on "send the last 60
+///   seconds with this bug report" button.
+/// - [`Recorder::streaming`] hands each change to a function as it happens and keeps nothing, so a
+///   multi-hour session can go straight to a file.
+///
+/// ```rust
+/// # use puppetmaster::Recorder;
+/// let mut recorder = Recorder::rolling(600);
+/// for frame in 0..10_000 {
+///     // Tap "a" every 100 frames
+///     let held = if frame % 100 == 0 { vec!["a"] } else { vec![] };
+///     recorder.record(held);
+/// }
+///
+/// let recording = recorder.recording().unwrap();
+/// assert_eq!(recording.frames, 600);
+/// // Only the presses and releases are kept: two changes per tap.
+/// assert_eq!(recording.changes.len(), 12);
+///
+/// let frames = recording.held_frames().collect::<Vec<_>>();
+/// assert_eq!(frames.len(), 600);
+/// assert_eq!(frames[0], vec!["a"]);
+/// assert!(frames[1].is_empty());
+/// ```
+pub struct Recorder<T> {
+    /// What was held down last frame
+    held: AHashSet<T>,
+    /// How many frames it's been since the last change
+    since_change: u64,
+    storage: Storage<T>,
+}
+
+enum Storage<T> {
+    Memory {
+        recording: Recording<T>,
+        /// How many frames to keep, if not all of them
+        limit: Option<u64>,
+    },
+    Streaming(Box<dyn FnMut(RecordedChange<T>) + Send>),
+}
+
+impl<T: Hash + Eq + Clone> Recorder<T> {
+    /// Create a recorder that keeps everything it records.
+    pub fn new() -> Self {
+        Self::with_storage(Storage::Memory {
+            recording: Recording::new(),
+            limit: None,
+        })
+    }
+
+    /// Create a recorder that only keeps the last `frames` frames.
+    pub fn rolling(frames: u64) -> Self {
+        Self::with_storage(Storage::Memory {
+            recording: Recording::new(),
+            limit: Some(frames),
+        })
+    }
+
+    /// Create a recorder that passes each change to `sink` as soon as it's recorded, and doesn't keep any of
+    /// them.
+    ///
+    /// Frames where nothing changes don't call `sink`; they're counted in the next change's
+    /// [`wait`](RecordedChange::wait). Everything the recorder sees is streamed, so the first change has
+    /// whatever was held down on the first frame.
+    pub fn streaming(sink: impl FnMut(RecordedChange<T>) + Send + 'static) -> Self {
+        Self::with_storage(Storage::Streaming(Box::new(sink)))
+    }
+
+    fn with_storage(storage: Storage<T>) -> Self {
+        Self {
+            held: AHashSet::new(),
+            since_change: 0,
+            storage,
+        }
+    }
+
+    /// Record one frame, given everything that's held down on it.
+    pub fn record(&mut self, held: impl IntoIterator<Item = T>) {
+        let held = held.into_iter().collect::<AHashSet<_>>();
+        let pressed = held.difference(&self.held).cloned().collect::<Vec<_>>();
+        let released = self.held.difference(&held).cloned().collect::<Vec<_>>();
+        self.held = held;
+
+        let mut change = (!pressed.is_empty() || !released.is_empty()).then(|| RecordedChange {
+            wait: self.since_change,
+            pressed,
+            released,
+        });
+        if change.is_some() {
+            self.since_change = 1;
+        } else {
+            self.since_change += 1;
+        }
+
+        match &mut self.storage {
+            Storage::Memory { recording, limit } => {
+                if let (Some(change), true) = (&mut change, recording.changes.is_empty()) {
+                    // The last change might have been trimmed off, so count from the start
+                    change.wait = recording.frames;
+                }
+                recording.changes.extend(change);
+                recording.frames += 1;
+                if let Some(limit) = *limit {
+                    recording.trim_start(limit);
+                }
+            }
+            Storage::Streaming(sink) => {
+                if let Some(change) = change {
+                    sink(change);
+                }
+            }
+        }
+    }
+
+    /// Get what's been recorded so far. Returns `None` for a [streaming](Self::streaming) recorder.
+    pub fn recording(&self) -> Option<&Recording<T>> {
+        match &self.storage {
+            Storage::Memory { recording, .. } => Some(recording),
+            Storage::Streaming(_) => None,
+        }
+    }
+
+    /// Stop recording, and get what was recorded. Returns `None` for a [streaming](Self::streaming) recorder.
+    pub fn finish(self) -> Option<Recording<T>> {
+        match self.storage {
+            Storage::Memory { recording, .. } => Some(recording),
+            Storage::Streaming(_) => None,
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> Default for Recorder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Recorder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Recorder");
+        debug.field("held", &self.held);
+        match &self.storage {
+            Storage::Memory { recording, limit } => {
+                debug.field("recording", recording).field("limit", limit)
+            }
+            Storage::Streaming(_) => debug.field("streaming", &true),
+        };
+        debug.finish()
+    }
+}
+
+/// Plays a [`Recording`] back at any speed, for slow motion and fast forward.
+///
+/// Call [`next_frame`](Self::next_frame) once per game frame to get what's held down on it, and give that
+/// to a handler's `update` (or `replay_controls`, for recorded controls). At half speed each recorded frame
+/// is shown for two game frames, and at double speed two recorded frames go by each game frame; any speed
+/// works, like anything from 0.25 to 4.
+///
+/// Speeds that aren't whole numbers never drop or duplicate a click. Slowing down only repeats what's held,
+/// which doesn't click anything again. When speeding up, something pressed and released within one game
+/// frame stays down for that frame and is let go the next, so quick taps still click. If something would be
+/// pressed again in the same game frame it was let go, which would swallow the second click, playback waits
+/// for the next game frame and catches up after.
+///
+/// ```rust
+/// # use puppetmaster::{Recorder, Replay};
+/// let mut recorder = Recorder::new();
+/// // Two one-frame taps with a one-frame gap
+/// for held in [vec!["a"], vec![], vec!["a"], vec![], vec![], vec![], vec![], vec![]] {
+///     recorder.record(held);
+/// }
+///
+/// let mut replay = Replay::new(recorder.finish().unwrap());
+/// replay.set_speed(4.0);
+/// let mut frames = Vec::new();
+/// while let Some(held) = replay.next_frame() {
+///     frames.push(held);
+/// }
+/// // Both taps still show up as separate presses
+/// assert_eq!(frames, vec![vec!["a"], vec![], vec!["a"], vec![]]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Replay<T> {
+    recording: Recording<T>,
+    speed: f64,
+    paused: bool,
+    /// How many recorded frames should have been played by now
+    target: f64,
+    /// How many recorded frames have been played
+    played: u64,
+    /// The index of the next change to play, and the recorded frame it's on
+    next_change: Option<(usize, u64)>,
+    /// What's held down
+    held: AHashSet<T>,
+    /// What was pressed and released in the last game frame, and still has to be let go
+    releasing: Vec<T>,
+}
+
+impl<T: Hash + Eq + Clone> Replay<T> {
+    /// Start playing a recording back at normal speed.
+    pub fn new(recording: Recording<T>) -> Self {
+        let next_change = recording.changes.front().map(|change| (0, change.wait));
+        Self {
+            held: recording.start.iter().cloned().collect(),
+            recording,
+            speed: 1.0,
+            paused: false,
+            target: 0.0,
+            played: 0,
+            next_change,
+            releasing: Vec::new(),
+        }
+    }
+
+    /// Set how many recorded frames go by per game frame. Negative speeds count as 0.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.max(0.0);
+    }
+
+    /// Get how many recorded frames go by per game frame.
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Pause or unpause playback. While paused, each game frame holds down the same things as the last.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Return if playback is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Get how many recorded frames have been played.
+    pub fn position(&self) -> u64 {
+        self.played
+    }
+
+    /// Return if the whole recording has been played.
+    pub fn is_finished(&self) -> bool {
+        self.played >= self.recording.frames && self.releasing.is_empty()
+    }
+
+    /// Get the recording being played.
+    pub fn recording(&self) -> &Recording<T> {
+        &self.recording
+    }
+
+    /// Move forward a game frame, and get what's held down on it. Returns `None` once the recording is over.
+    pub fn next_frame(&mut self) -> Option<Vec<T>> {
+        if self.is_finished() {
+            return None;
+        }
+        if !self.paused {
+            self.target += self.speed;
+        }
+
+        let mut released = AHashSet::new();
+        for input in self.releasing.drain(..) {
+            self.held.remove(&input);
+            released.insert(input);
+        }
+        let mut pressed = AHashSet::new();
+        while (self.played as f64) < self.target && self.played < self.recording.frames {
+            if let Some((idx, frame)) = self.next_change.filter(|&(_, frame)| frame == self.played)
+            {
+                let change = &self.recording.changes[idx];
+                if change
+                    .pressed
+                    .iter()
+                    .any(|input| released.contains(input) || self.releasing.contains(input))
+                {
+                    // Pressing it again this game frame would swallow the click, so wait for the next one
+                    break;
+                }
+                for input in change.released.iter() {
+                    if pressed.contains(input) {
+                        self.releasing.push(input.clone());
+                    } else {
+                        self.held.remove(input);
+                        released.insert(input.clone());
+                    }
+                }
+                for input in change.pressed.iter() {
+                    self.held.insert(input.clone());
+                    pressed.insert(input.clone());
+                }
+                self.next_change = self
+                    .recording
+                    .changes
+                    .get(idx + 1)
+                    .map(|next| (idx + 1, frame + next.wait));
+            }
+            self.played += 1;
+        }
+
+        Some(self.held.iter().cloned().collect())
+    }
+}
@@ -0,0 +1,115 @@
+use std::{fmt, sync::Arc};
+
+use ahash::AHashMap;
+
+/// A handle to a control in a [`ControlRegistry`].
+///
+/// It's small and `Copy`, so use it as the control type of a handler, just like a control enum. Handles
+/// from one registry don't mean anything to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ControlId(u32);
+
+impl ControlId {
+    /// Get the handle's index. Handles are numbered in the order they were registered, starting from 0.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for ControlId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Controls defined at runtime by name, for games whose controls come from data files or mods instead of an
+/// enum in the code.
+///
+/// Register each control's name to get a [`ControlId`] for it, then use the IDs as a handler's controls.
+/// Looking up an ID by name is a hash lookup, so do it once when the data is loaded and keep the IDs around
+/// for queries.
+///
+/// ```rust
+/// # use puppetmaster::{ControlRegistry, PollingInputHandler};
+/// // Loaded from a mod's data files ...
+/// let bindings = vec![("space", "jump"), ("e", "cast_fireball")];
+///
+/// let mut registry = ControlRegistry::new();
+/// let mut input_handler = PollingInputHandler::new_with_controls(registry.register_bindings(bindings));
+///
+/// let fireball = registry.get("cast_fireball").unwrap();
+/// input_handler.update(vec!["e"]);
+/// assert!(input_handler.clicked(fireball));
+/// assert_eq!(registry.name(fireball), Some("cast_fireball"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ControlRegistry {
+    /// Each control's name, by index
+    names: Vec<Arc<str>>,
+    /// Each control's ID, by name
+    ids: AHashMap<Arc<str>, ControlId>,
+}
+
+impl ControlRegistry {
+    /// Create a new, empty `ControlRegistry`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a control, and get its ID. Registering a name that's already registered gives the same ID.
+    pub fn register(&mut self, name: &str) -> ControlId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = ControlId(
+            self.names
+                .len()
+                .try_into()
+                .expect("registered more than u32::MAX controls"),
+        );
+        let name: Arc<str> = name.into();
+        self.names.push(Arc::clone(&name));
+        self.ids.insert(name, id);
+        id
+    }
+
+    /// Register every control named in a list of bindings, and turn the names into IDs. The result can be
+    /// passed straight to a handler's `new_with_controls`.
+    pub fn register_bindings<'a, I>(
+        &mut self,
+        bindings: impl IntoIterator<Item = (I, &'a str)>,
+    ) -> Vec<(I, ControlId)> {
+        bindings
+            .into_iter()
+            .map(|(input, name)| (input, self.register(name)))
+            .collect()
+    }
+
+    /// Get the ID of the control with the given name, if it's registered.
+    pub fn get(&self, name: &str) -> Option<ControlId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Get the name of the control with the given ID, if it's from this registry.
+    pub fn name(&self, id: ControlId) -> Option<&str> {
+        self.names.get(id.index()).map(|name| &**name)
+    }
+
+    /// Get how many controls are registered.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Return if no controls are registered.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Iterate over every control's ID and name, in the order they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = (ControlId, &str)> + '_ {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (ControlId(idx as u32), &**name))
+    }
+}
@@ -0,0 +1,136 @@
+use std::hash::Hash;
+
+use ahash::AHashMap;
+
+use crate::{EventInputHandler, EventSender, InputSnapshot, UpdateSummary};
+
+/// An [`EventInputHandler`] wrapped up to live in an ECS resource slot.
+///
+/// ECS schedulers hand out resources either by shared or by exclusive reference.
+/// This wrapper is set up so everything besides the per-frame update works through a shared reference:
+///
+/// - Feeding it input events (`on_input_down` and friends) takes `&self`. The events are queued
+///   internally, so any number of systems can feed it at once.
+/// - Querying controls takes `&self`, and sees the state as of the last update.
+/// - [`update`](Self::update) takes `&mut self`. Call it once at the frame boundary, before any system queries controls.
+///
+/// `InputResource` is `Send + Sync` as long as your inputs and controls are.
+///
+/// ```rust
+/// # use puppetmaster::InputResource;
+/// let mut resource = InputResource::new_with_controls(vec![('w', "up")]);
+///
+/// // In an input-gathering system with shared access ...
+/// fn gather(resource: &InputResource<char, &str>) {
+///     resource.on_input_down('w');
+/// }
+/// gather(&resource);
+///
+/// // At the frame boundary, with exclusive access ...
+/// resource.update();
+///
+/// // In gameplay systems with shared access ...
+/// assert!(resource.clicked("up"));
+/// ```
+#[derive(Debug)]
+pub struct InputResource<I, C> {
+    handler: EventInputHandler<I, C>,
+    sender: EventSender<I>,
+}
+
+impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> InputResource<I, C> {
+    /// Wrap up an existing `EventInputHandler`.
+    pub fn new(mut handler: EventInputHandler<I, C>) -> Self {
+        let sender = handler.sender();
+        Self { handler, sender }
+    }
+
+    /// Create a new `InputResource` with the given mapping of inputs to controls.
+    ///
+    /// If two entries in the iterator have the same input, the first one will be clobbered
+    /// and the second one will remain.
+    pub fn new_with_controls(map: impl IntoIterator<Item = (I, C)>) -> Self {
+        Self::new(EventInputHandler::new_with_controls(map))
+    }
+
+    /// Call this function when your game engine gives you a `KeyDown` event.
+    pub fn on_input_down(&self, input: I) {
+        self.sender.on_input_down(input);
+    }
+
+    /// Call this function when your game engine gives you a `KeyUp` event.
+    pub fn on_input_up(&self, input: I) {
+        self.sender.on_input_up(input);
+    }
+
+    /// Call this function when your game engine gives you an input event, with `pressed` being `true` for
+    /// a `KeyDown` event and `false` for a `KeyUp` event.
+    pub fn on_input_event(&self, input: I, pressed: bool) {
+        self.sender.on_input_event(input, pressed);
+    }
+
+    /// Get a handle that can feed input events into this resource from anywhere, including other threads.
+    pub fn sender(&self) -> EventSender<I> {
+        self.sender.clone()
+    }
+
+    /// Process all the input events received since the last update.
+    ///
+    /// Call this once per frame at the frame boundary, before any system queries controls.
+    ///
+    /// Returns a summary of what changed, like [`EventInputHandler::update`].
+    pub fn update(&mut self) -> UpdateSummary {
+        self.handler.update()
+    }
+
+    /// Return the number of frames the given control has been pressed for
+    pub fn press_time(&self, ctrl: C) -> u32 {
+        self.handler.press_time(ctrl)
+    }
+
+    /// Return if this control is held down (ie, the corresponding input has been pressed for 1 or more frames).
+    pub fn down(&self, ctrl: C) -> bool {
+        self.handler.down(ctrl)
+    }
+
+    /// Return if this control is up.
+    pub fn up(&self, ctrl: C) -> bool {
+        self.handler.up(ctrl)
+    }
+
+    /// Return if this control was *clicked* down this frame (ie, the corresponding input was *just* pressed this frame).
+    pub fn clicked(&self, ctrl: C) -> bool {
+        self.handler.clicked(ctrl)
+    }
+
+    /// Return an iterator over all the controls that are currently held down.
+    pub fn all_pressed(&self) -> impl Iterator<Item = &C> + '_ {
+        self.handler.all_pressed()
+    }
+
+    /// Take a read-only snapshot of the state of all the controls, which can be shared across threads.
+    pub fn snapshot(&self) -> InputSnapshot<C> {
+        self.handler.snapshot()
+    }
+
+    /// Get the mapping of inputs to controls.
+    pub fn control_config(&self) -> &AHashMap<I, C> {
+        self.handler.control_config()
+    }
+
+    /// Get the wrapped handler.
+    pub fn handler(&self) -> &EventInputHandler<I, C> {
+        &self.handler
+    }
+
+    /// Get mutable access to the wrapped handler, for rebinding and such.
+    pub fn handler_mut(&mut self) -> &mut EventInputHandler<I, C> {
+        &mut self.handler
+    }
+}
+
+impl<I: Hash + Eq + Clone, C: Hash + Eq + Clone> Default for InputResource<I, C> {
+    fn default() -> Self {
+        Self::new(EventInputHandler::default())
+    }
+}
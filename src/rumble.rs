@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+/// A rumble effect for a game controller to play.
+///
+/// Controllers have two rumble motors: a heavy low-frequency one, usually in the left grip, and a light
+/// high-frequency one, usually in the right. Mixing the two gives different feels, like a low rumble for
+/// an explosion and a high buzz for a footstep.
+///
+/// Request rumble with `rumble` on the handlers. The handler only queues requests up; the engine adapters
+/// drain the queue and play them (the `sdl2` adapter's `SdlAdapter::rumble` does), or you
+/// can drain it yourself with `drain_rumble`.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use puppetmaster::{PollingInputHandler, Rumble, RumbleRequest};
+/// let mut input_handler = PollingInputHandler::<(), ()>::new();
+///
+/// // The player got hit. Rumble every controller.
+/// let hit = Rumble::new(0.8, 0.3, Duration::from_millis(200));
+/// input_handler.rumble(None, hit);
+///
+/// let requests = input_handler.drain_rumble().collect::<Vec<_>>();
+/// assert_eq!(requests, vec![RumbleRequest { device: None, rumble: hit }]);
+/// assert_eq!(input_handler.drain_rumble().count(), 0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rumble {
+    /// How hard the low-frequency motor rumbles, from 0 to 1.
+    pub low_frequency: f32,
+    /// How hard the high-frequency motor rumbles, from 0 to 1.
+    pub high_frequency: f32,
+    /// How long to rumble for.
+    pub duration: Duration,
+}
+
+impl Rumble {
+    /// Create a new `Rumble`.
+    pub fn new(low_frequency: f32, high_frequency: f32, duration: Duration) -> Self {
+        Self {
+            low_frequency,
+            high_frequency,
+            duration,
+        }
+    }
+
+    /// A rumble that stops whatever the controller is playing.
+    pub fn stop() -> Self {
+        Self::default()
+    }
+}
+
+/// A [`Rumble`] waiting to be played, and which controller to play it on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RumbleRequest {
+    /// The controller to rumble, by the ID the engine gives it, or `None` to rumble every controller.
+    ///
+    /// With SDL this is the controller's instance ID.
+    pub device: Option<u32>,
+    /// The rumble to play.
+    pub rumble: Rumble,
+}
+
+/// One of a game controller's two triggers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TriggerSide {
+    /// The left trigger (L2).
+    Left,
+    /// The right trigger (R2).
+    Right,
+}
+
+/// How an adaptive trigger, like the DualSense's, should feel to pull.
+///
+/// Positions along the trigger's pull go from 0 (let go) to 1 (pulled all the way). Strengths also go from
+/// 0 to 1. Effects stay on until they're replaced, so set [`TriggerEffect::Off`] when the player puts the
+/// bow away.
+///
+/// Set effects with `set_trigger_effect` on the handlers. Like [`Rumble`], the handler only queues them
+/// up; the engine adapters drain the queue and apply them (the `sdl2` adapter's `SdlAdapter::rumble`
+/// does), or you can drain it yourself with `drain_trigger_effects`. Controllers without adaptive
+/// triggers ignore them.
+///
+/// ```rust
+/// # use puppetmaster::{PollingInputHandler, TriggerEffect, TriggerEffectRequest, TriggerSide};
+/// let mut input_handler = PollingInputHandler::<(), ()>::new();
+///
+/// // Drawing a bow gets harder halfway through the pull.
+/// let bow = TriggerEffect::Resistance { start: 0.5, strength: 0.8 };
+/// input_handler.set_trigger_effect(Some(0), TriggerSide::Right, bow);
+///
+/// let requests = input_handler.drain_trigger_effects().collect::<Vec<_>>();
+/// assert_eq!(
+///     requests,
+///     vec![TriggerEffectRequest { device: Some(0), trigger: TriggerSide::Right, effect: bow }]
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TriggerEffect {
+    /// No effect; the trigger pulls freely.
+    #[default]
+    Off,
+    /// The trigger resists being pulled past `start`, all the way to the end.
+    Resistance {
+        /// Where the resistance starts.
+        start: f32,
+        /// How hard it resists.
+        strength: f32,
+    },
+    /// The trigger resists hard between `start` and `end`, then gives way, like the break in a gun's
+    /// trigger.
+    TriggerStop {
+        /// Where the stop starts.
+        start: f32,
+        /// Where the trigger gives way.
+        end: f32,
+    },
+    /// The trigger vibrates once it's pulled past `start`.
+    Vibration {
+        /// Where the vibration starts.
+        start: f32,
+        /// How fast it vibrates, in hertz.
+        frequency: f32,
+        /// How hard it vibrates.
+        strength: f32,
+    },
+}
+
+/// A [`TriggerEffect`] waiting to be applied, and which controller and trigger to apply it to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriggerEffectRequest {
+    /// The controller, by the ID the engine gives it, or `None` for every controller.
+    ///
+    /// With SDL this is the controller's instance ID.
+    pub device: Option<u32>,
+    /// Which trigger the effect is for.
+    pub trigger: TriggerSide,
+    /// The effect.
+    pub effect: TriggerEffect,
+}
@@ -0,0 +1,150 @@
+use crate::{
+    AutoRepeat, AxisOverride, Charge, Chord, Confirmation, Debounce, DualAxis, FrameNumber,
+    LongPress, MinHold, RateLimit, TimingSettings, VirtualAxis,
+};
+
+/// Everything an input handler needs to pick up where it left off, as plain data.
+///
+/// This is for hot reloading: save the handler's state before the game's code is reloaded, and restore it
+/// into a fresh handler afterwards, and keys the player was holding stay held instead of all being
+/// released (and pressed again). Get one with `save_state` on any handler, and put it back with
+/// `restore_state`.
+///
+/// It holds the bindings, the [input remap](crate::Controls::input_remap_mut), the
+/// [timing settings](TimingSettings), [each control's own settings](ControlSettings) like toggles, long
+/// presses, chords, and charge moves, the [axis bindings and axis controls](SavedAxes), which inputs and
+/// controls are held down, and which toggles are switched on. It's all public fields and plain collections,
+/// so you can serialize it however you like, or with serde using the `serde` feature. Macros, scanning,
+/// staged presses, listeners, and the other features that aren't listed in [`ControlSettings`] aren't
+/// included; set those up again after reloading.
+///
+/// ```rust
+/// # use puppetmaster::PollingInputHandler;
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("d", "right")]);
+/// input_handler.update(vec!["d"]);
+/// input_handler.update(vec!["d"]);
+/// let saved = input_handler.save_state();
+///
+/// // ... the game's code is reloaded ...
+///
+/// let mut input_handler = PollingInputHandler::new();
+/// assert!(input_handler.restore_state(saved));
+/// input_handler.update(vec!["d"]);
+/// // The player never let go, so it's still the same press.
+/// assert!(!input_handler.clicked("right"));
+/// assert_eq!(input_handler.press_time("right"), 3);
+/// ```
+///
+/// With the `serde` feature, the state can be written out as it is, and so can a handler's bindings on
+/// their own, so they can live in your game's settings file.
+///
+/// ```rust
+/// # #[cfg(feature = "serde")] {
+/// # use puppetmaster::{PollingInputHandler, SavedState};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("space".to_owned(), "jump".to_owned())]);
+/// input_handler.update(vec!["space".to_owned()]);
+///
+/// let json = serde_json::to_string(&input_handler.save_state()).unwrap();
+/// let saved: SavedState<String, String> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(saved, input_handler.save_state());
+///
+/// let bindings = serde_json::to_string(input_handler.control_config()).unwrap();
+/// assert_eq!(bindings, r#"{"space":"jump"}"#);
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedState<I, C> {
+    /// Which version of this format the state was saved in. See [`SavedState::VERSION`].
+    pub version: u32,
+    /// The mapping of inputs to controls.
+    pub bindings: Vec<(I, C)>,
+    /// The input remap, from each input to the input it acts as.
+    pub remap: Vec<(I, I)>,
+    /// The handler's timing settings.
+    pub timing: TimingSettings,
+    /// The settings controls have of their own.
+    pub control_settings: ControlSettings<C>,
+    /// The axis bindings and axis controls.
+    pub axes: SavedAxes<I, C>,
+    /// Which update the handler was on.
+    pub frame: FrameNumber,
+    /// The raw inputs that were held down.
+    pub held_inputs: Vec<I>,
+    /// The controls that were down, and how many frames they'd been down for.
+    pub pressed_controls: Vec<(C, u32)>,
+    /// The toggle controls that were switched on, and if an input for each was still held down.
+    pub toggled_on: Vec<(C, bool)>,
+}
+
+impl<I, C> SavedState<I, C> {
+    /// The version of the format this build of the crate saves, and the only one it can restore.
+    ///
+    /// This goes up whenever the fields change, so a state saved by an older build is turned down instead
+    /// of being misread.
+    pub const VERSION: u32 = 3;
+}
+
+/// The settings a handler's controls have of their own, as part of a [`SavedState`].
+///
+/// The timing settings override the handler's [`TimingSettings`]. A control listed with `None` has that
+/// setting turned off, like after `set_debounce(ctrl, None)`, and a control that isn't listed uses the
+/// handler's [`TimingSettings`]. The rest are only set for the controls listed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControlSettings<C> {
+    /// Each control's own [`Debounce`].
+    pub debounce: Vec<(C, Option<Debounce>)>,
+    /// Each control's own [`Confirmation`].
+    pub confirmation: Vec<(C, Option<Confirmation>)>,
+    /// Each control's own [`MinHold`].
+    pub min_hold: Vec<(C, Option<MinHold>)>,
+    /// Each control's own turbo period.
+    pub turbo: Vec<(C, Option<u32>)>,
+    /// Each control's own [`AutoRepeat`].
+    pub auto_repeat: Vec<(C, Option<AutoRepeat>)>,
+    /// Each control's own [`RateLimit`].
+    pub rate_limit: Vec<(C, Option<RateLimit>)>,
+    /// Each control's own tap window.
+    pub tap_window: Vec<(C, u32)>,
+    /// Each control's own hold threshold.
+    pub hold_threshold: Vec<(C, Option<u32>)>,
+    /// The controls that toggle on and off with each press.
+    pub toggles: Vec<C>,
+    /// The controls that stay down after being tapped.
+    pub sticky: Vec<C>,
+    /// The controls that are only down for the frame they're pressed on.
+    pub click_only: Vec<C>,
+    /// How many frames after clicking each control with a lockout ignores new presses.
+    pub lockout: Vec<(C, u32)>,
+    /// The controls that keep working while the game is paused.
+    pub pause_exempt: Vec<C>,
+    /// Each control's [`LongPress`].
+    pub long_press: Vec<(C, LongPress<C>)>,
+    /// Each [`Charge`], by the control it clicks.
+    pub charge: Vec<(C, Charge<C>)>,
+    /// Each [`Chord`], by the control it presses.
+    pub chord: Vec<(C, Chord<C>)>,
+}
+
+/// A handler's axis bindings and axis controls, as part of a [`SavedState`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedAxes<I, C> {
+    /// The mapping of axis inputs to axis controls.
+    pub bindings: Vec<(I, C)>,
+    /// Each axis control's deadzone.
+    pub deadzones: Vec<(C, f32)>,
+    /// Each axis control's sensitivity.
+    pub sensitivities: Vec<(C, f32)>,
+    /// The axis controls that are flipped.
+    pub inverted: Vec<C>,
+    /// The [axis overrides](AxisOverride), with the axis context each one is for.
+    pub overrides: Vec<(C, C, AxisOverride)>,
+    /// The axis context in use, if there was one.
+    pub context: Option<C>,
+    /// Axis controls made from two digital controls.
+    pub virtual_axes: Vec<(C, VirtualAxis<C>)>,
+    /// 2D controls made from two axis controls.
+    pub dual_axes: Vec<(C, DualAxis<C>)>,
+}
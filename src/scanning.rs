@@ -0,0 +1,102 @@
+/// Settings for one-switch scanning, where a single button can reach every control.
+///
+/// While scanning is on, the handler moves its focus through `targets` on a timer. Holding the `switch`
+/// control down for `activate_after` frames presses whichever target has the focus, and it stays pressed
+/// until the switch is let go. Then the scan starts over from the first target.
+///
+/// Your game draws the focus (see `scan_focus` on the handlers); otherwise it doesn't need to know scanning
+/// is happening. The targets don't need to have any inputs bound to them.
+///
+/// ```rust
+/// # use puppetmaster::{PollingInputHandler, Scanning};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("switch", "switch")]);
+/// input_handler.set_scanning(Some(Scanning {
+///     switch: "switch",
+///     targets: vec!["jump", "shoot"],
+///     interval: 30,
+///     activate_after: 10,
+/// }));
+///
+/// // Wait for the focus to move to "shoot" ...
+/// for _ in 0..30 {
+///     input_handler.update(vec![]);
+/// }
+/// assert_eq!(input_handler.scan_focus(), Some(&"shoot"));
+///
+/// // ... then hold the switch to shoot.
+/// for _ in 0..10 {
+///     input_handler.update(vec!["switch"]);
+/// }
+/// assert!(input_handler.clicked("shoot"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Scanning<C> {
+    /// The control that activates the focused target.
+    pub switch: C,
+    /// The controls to scan through, in order.
+    pub targets: Vec<C>,
+    /// How many frames the focus stays on each target. 0 counts as 1.
+    pub interval: u32,
+    /// How many frames the switch has to be held to activate the focused target. 0 counts as 1.
+    pub activate_after: u32,
+}
+
+/// Where a handler is in its scan.
+#[derive(Clone, Debug)]
+pub(crate) struct ScanState<C> {
+    pub settings: Scanning<C>,
+    /// Index of the focused target
+    focus: usize,
+    /// How many frames the focus has been on the current target
+    timer: u32,
+    /// How many frames the switch has been held
+    hold_time: u32,
+}
+
+impl<C: PartialEq> ScanState<C> {
+    pub fn new(settings: Scanning<C>) -> Self {
+        Self {
+            settings,
+            focus: 0,
+            timer: 0,
+            hold_time: 0,
+        }
+    }
+
+    /// Move forward a frame, given if the switch is held. Returns the target to press this frame, if any.
+    pub fn update(&mut self, switch_held: bool) -> Option<&C> {
+        if switch_held {
+            self.hold_time += 1;
+        } else {
+            if self.hold_time >= self.settings.activate_after.max(1) {
+                // A target was just activated, so start the scan over
+                self.focus = 0;
+                self.timer = 0;
+            } else {
+                self.timer += 1;
+                if self.timer >= self.settings.interval.max(1) {
+                    self.timer = 0;
+                    self.focus = (self.focus + 1) % self.settings.targets.len().max(1);
+                }
+            }
+            self.hold_time = 0;
+        }
+
+        if self.hold_time >= self.settings.activate_after.max(1) {
+            self.focus()
+        } else {
+            None
+        }
+    }
+
+    pub fn focus(&self) -> Option<&C> {
+        self.settings.targets.get(self.focus)
+    }
+
+    /// Let go of the switch.
+    pub fn reset(&mut self) {
+        self.focus = 0;
+        self.timer = 0;
+        self.hold_time = 0;
+    }
+}
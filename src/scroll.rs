@@ -0,0 +1,158 @@
+/// Controls that click as the mouse wheel or trackpad scrolls, once per `detent`.
+///
+/// Trackpads report scrolling in lots of tiny fractional steps, and some mice do too. Mapping each of those
+/// to a click would fire far too often, so instead the scrolling is added up, and the control clicks each time
+/// it's scrolled another `detent` lines in that direction. If the player scrolls several detents in one frame,
+/// the clicks are spread over the next few frames so none are lost.
+///
+/// Leave a direction as `None` if you don't need it.
+///
+/// ```rust
+/// # use puppetmaster::{PollingInputHandler, ScrollControls};
+/// let mut input_handler = PollingInputHandler::<(), _>::new();
+/// input_handler.set_scroll_controls(Some(ScrollControls {
+///     up: Some("next_weapon"),
+///     down: Some("prev_weapon"),
+///     left: None,
+///     right: None,
+///     detent: 1.0,
+/// }));
+///
+/// // A trackpad scrolls a little at a time ...
+/// input_handler.add_scroll((0.0, 0.6));
+/// input_handler.update(vec![]);
+/// assert!(!input_handler.clicked("next_weapon"));
+///
+/// input_handler.add_scroll((0.0, 0.6));
+/// input_handler.update(vec![]);
+/// assert!(input_handler.clicked("next_weapon"));
+/// assert_eq!(input_handler.scroll(), (0.0, 0.6));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollControls<C> {
+    /// The control to click when scrolling up (positive `y`).
+    pub up: Option<C>,
+    /// The control to click when scrolling down (negative `y`).
+    pub down: Option<C>,
+    /// The control to click when scrolling left (negative `x`).
+    pub left: Option<C>,
+    /// The control to click when scrolling right (positive `x`).
+    pub right: Option<C>,
+    /// How many lines of scrolling make one click. Usually one notch of a mouse wheel is one line.
+    pub detent: f32,
+}
+
+/// How much has been scrolled, and the clicks it's owed.
+#[derive(Clone, Debug)]
+pub(crate) struct ScrollState<C> {
+    /// Scrolling reported since the last tick
+    pending: (f32, f32),
+    /// Scrolling during the last tick
+    delta: (f32, f32),
+    controls: Option<ScrollControls<C>>,
+    /// Scrolling that hasn't added up to a whole detent yet
+    remainder: (f32, f32),
+    /// Clicks owed to the up, down, left, and right controls
+    owed: [u32; 4],
+    /// Which of those controls were held last tick
+    held: [bool; 4],
+}
+
+impl<C> ScrollState<C> {
+    pub fn add(&mut self, (x, y): (f32, f32)) {
+        self.pending.0 += x;
+        self.pending.1 += y;
+    }
+
+    /// Move forward a frame. Returns the controls to hold this frame.
+    pub fn update(&mut self) -> impl Iterator<Item = &C> + '_ {
+        self.delta = std::mem::take(&mut self.pending);
+        if let Some(controls) = &self.controls {
+            let detent = controls.detent.max(f32::EPSILON);
+            self.remainder.0 += self.delta.0;
+            self.remainder.1 += self.delta.1;
+            for (remainder, [pos, neg]) in [
+                (&mut self.remainder.1, [0, 1]),
+                (&mut self.remainder.0, [3, 2]),
+            ] {
+                while *remainder >= detent {
+                    *remainder -= detent;
+                    self.owed[pos] += 1;
+                }
+                while *remainder <= -detent {
+                    *remainder += detent;
+                    self.owed[neg] += 1;
+                }
+            }
+            for (owed, held) in self.owed.iter_mut().zip(self.held.iter_mut()) {
+                // Let go for a frame between clicks, so each one is a fresh press
+                *held = !*held && *owed > 0;
+                if *held {
+                    *owed -= 1;
+                }
+            }
+        }
+
+        let controls = self.controls.as_ref();
+        let held = self.held;
+        [
+            controls.and_then(|c| c.up.as_ref()),
+            controls.and_then(|c| c.down.as_ref()),
+            controls.and_then(|c| c.left.as_ref()),
+            controls.and_then(|c| c.right.as_ref()),
+        ]
+        .into_iter()
+        .zip(held)
+        .filter_map(|(control, held)| control.filter(|_| held))
+    }
+
+    pub fn delta(&self) -> (f32, f32) {
+        self.delta
+    }
+
+    /// Every control that can be clicked by scrolling.
+    pub fn controls(&self) -> impl Iterator<Item = &C> + '_ {
+        self.controls.iter().flat_map(|controls| {
+            [
+                &controls.up,
+                &controls.down,
+                &controls.left,
+                &controls.right,
+            ]
+            .into_iter()
+            .flatten()
+        })
+    }
+
+    pub fn set_controls(&mut self, controls: Option<ScrollControls<C>>) {
+        self.controls = controls;
+        self.remainder = (0.0, 0.0);
+        self.owed = [0; 4];
+        self.held = [false; 4];
+    }
+
+    pub fn settings(&self) -> Option<&ScrollControls<C>> {
+        self.controls.as_ref()
+    }
+
+    /// Forget about any scrolling that hasn't turned into clicks yet.
+    pub fn reset(&mut self) {
+        self.pending = (0.0, 0.0);
+        self.remainder = (0.0, 0.0);
+        self.owed = [0; 4];
+        self.held = [false; 4];
+    }
+}
+
+impl<C> Default for ScrollState<C> {
+    fn default() -> Self {
+        Self {
+            pending: (0.0, 0.0),
+            delta: (0.0, 0.0),
+            controls: None,
+            remainder: (0.0, 0.0),
+            owed: [0; 4],
+            held: [false; 4],
+        }
+    }
+}
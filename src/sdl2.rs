@@ -0,0 +1,340 @@
+//! Glue for feeding [`sdl2`] events into an [`EventInputHandler`].
+//!
+//! Enable this with the `sdl2` feature.
+//!
+//! ```rust,ignore
+//! let sdl = sdl2::init()?;
+//! let mut adapter = SdlAdapter::new(Some(sdl.game_controller()?));
+//! let mut input_handler = EventInputHandler::new_with_controls(vec![
+//!     (SdlInput::Key(Scancode::Space), Control::Jump),
+//!     (SdlInput::Controller(Button::A), Control::Jump),
+//! ]);
+//!
+//! let mut event_pump = sdl.event_pump()?;
+//! loop {
+//!     for event in event_pump.poll_iter() {
+//!         adapter.event(&mut input_handler, &event);
+//!     }
+//!     input_handler.update();
+//!
+//!     // Now do game logic ...
+//!
+//!     adapter.rumble(&mut input_handler);
+//! }
+//! ```
+
+use std::hash::Hash;
+
+use ::sdl2::{
+    controller::{Button, GameController},
+    event::{Event, WindowEvent},
+    keyboard::{Keycode, Scancode},
+    mouse::{MouseButton, MouseWheelDirection},
+    GameControllerSubsystem,
+};
+use ahash::AHashMap;
+
+use crate::{EventInputHandler, ImeEvent, Rumble, TextEdit, TriggerEffect, TriggerSide};
+
+/// Anything SDL treats as a button.
+///
+/// Keys are scancodes, so bindings stay in the same place regardless of the keyboard layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SdlInput {
+    /// A key on the keyboard.
+    Key(Scancode),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// One tick of the mouse wheel upwards.
+    WheelUp,
+    /// One tick of the mouse wheel downwards.
+    WheelDown,
+    /// One tick of the mouse wheel to the left.
+    WheelLeft,
+    /// One tick of the mouse wheel to the right.
+    WheelRight,
+    /// A button on any game controller.
+    Controller(Button),
+}
+
+impl From<Scancode> for SdlInput {
+    fn from(key: Scancode) -> Self {
+        SdlInput::Key(key)
+    }
+}
+
+impl From<MouseButton> for SdlInput {
+    fn from(button: MouseButton) -> Self {
+        SdlInput::Mouse(button)
+    }
+}
+
+impl From<Button> for SdlInput {
+    fn from(button: Button) -> Self {
+        SdlInput::Controller(button)
+    }
+}
+
+/// Feeds SDL events into an [`EventInputHandler`].
+///
+/// Buttons from all connected controllers are merged together. A controller button counts as held as long as
+/// any controller is holding it, and when a controller is unplugged, whatever it was holding is released.
+pub struct SdlAdapter {
+    /// If this is present, controllers are opened when they're plugged in.
+    controller_subsystem: Option<GameControllerSubsystem>,
+    /// Controllers we've opened, by instance ID. SDL stops sending their events once they're dropped.
+    controllers: AHashMap<u32, GameController>,
+    /// Buttons each controller is holding down, by instance ID
+    held_buttons: AHashMap<u32, Vec<Button>>,
+}
+
+impl SdlAdapter {
+    /// Create a new `SdlAdapter`.
+    ///
+    /// SDL only sends events for controllers that have been opened. If you pass the game controller subsystem,
+    /// the adapter opens controllers as they're plugged in. Otherwise you'll have to open them yourself.
+    pub fn new(controller_subsystem: Option<GameControllerSubsystem>) -> Self {
+        Self {
+            controller_subsystem,
+            controllers: AHashMap::new(),
+            held_buttons: AHashMap::new(),
+        }
+    }
+
+    /// Feed an SDL event into the handler.
+    ///
+    /// Mouse motion sets the handler's [pointer position](EventInputHandler::set_pointer), and IME composition
+    /// is passed on to [`on_ime`](EventInputHandler::on_ime) (SDL sends the finished text as ordinary text
+    /// input). When the window loses focus, all inputs are released (SDL won't report the key-up events for
+    /// keys released while unfocused). If the game has several windows, presses in windows other than the
+    /// [focused one](EventInputHandler::set_focused_window) are ignored.
+    pub fn event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<SdlInput, C>,
+        event: &Event,
+    ) {
+        match *event {
+            Event::KeyDown {
+                window_id,
+                scancode,
+                keycode,
+                ..
+            } => {
+                if let Some(edit) = keycode.and_then(text_edit) {
+                    handler.on_text_edit(edit);
+                }
+                if let Some(scancode) = scancode {
+                    handler.on_window_input_event(window_id.into(), SdlInput::Key(scancode), true);
+                }
+            }
+            Event::TextInput { ref text, .. } => handler.on_text(text),
+            Event::TextEditing {
+                ref text,
+                start,
+                length,
+                ..
+            } => handler.on_ime(ImeEvent::Preedit {
+                text: text.clone(),
+                cursor: Some((
+                    byte_index(text, start),
+                    byte_index(text, start.saturating_add(length)),
+                )),
+            }),
+            Event::KeyUp {
+                window_id,
+                scancode: Some(scancode),
+                ..
+            } => handler.on_window_input_event(window_id.into(), SdlInput::Key(scancode), false),
+            Event::MouseMotion { x, y, .. } => handler.set_pointer(Some((x as f32, y as f32))),
+            Event::Window {
+                win_event: WindowEvent::Leave,
+                ..
+            } => handler.set_pointer(None),
+            Event::MouseButtonDown {
+                window_id,
+                mouse_btn,
+                ..
+            } => handler.on_window_input_event(window_id.into(), SdlInput::Mouse(mouse_btn), true),
+            Event::MouseButtonUp {
+                window_id,
+                mouse_btn,
+                ..
+            } => handler.on_window_input_event(window_id.into(), SdlInput::Mouse(mouse_btn), false),
+            Event::MouseWheel {
+                x, y, direction, ..
+            } => {
+                let (x, y) = match direction {
+                    MouseWheelDirection::Flipped => (-x, -y),
+                    _ => (x, y),
+                };
+                handler.add_scroll((x as f32, y as f32));
+                let input = if y > 0 {
+                    SdlInput::WheelUp
+                } else if y < 0 {
+                    SdlInput::WheelDown
+                } else if x > 0 {
+                    SdlInput::WheelRight
+                } else if x < 0 {
+                    SdlInput::WheelLeft
+                } else {
+                    return;
+                };
+                handler.on_input_down(input);
+                handler.on_input_up(input);
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                if !self.is_held_by_any(button) {
+                    handler.on_device_input_down(which, SdlInput::Controller(button));
+                }
+                let held = self.held_buttons.entry(which).or_default();
+                if !held.contains(&button) {
+                    held.push(button);
+                }
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                if let Some(held) = self.held_buttons.get_mut(&which) {
+                    held.retain(|b| *b != button);
+                }
+                if !self.is_held_by_any(button) {
+                    handler.on_input_up(SdlInput::Controller(button));
+                }
+            }
+            Event::ControllerDeviceAdded { which, .. } => {
+                if let Some(subsystem) = &self.controller_subsystem {
+                    // `which` is the joystick index here, not the instance ID
+                    if let Ok(controller) = subsystem.open(which) {
+                        self.controllers
+                            .insert(controller.instance_id(), controller);
+                    }
+                }
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(&which);
+                for button in self.held_buttons.remove(&which).unwrap_or_default() {
+                    if !self.is_held_by_any(button) {
+                        handler.on_input_up(SdlInput::Controller(button));
+                    }
+                }
+            }
+            Event::Window {
+                window_id,
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                if handler.focused_window() == Some(window_id.into()) {
+                    handler.set_focused_window(None);
+                }
+                handler.on_focus_lost();
+                self.held_buttons.clear();
+            }
+            Event::Window {
+                window_id,
+                win_event: WindowEvent::FocusGained,
+                ..
+            } => {
+                handler.set_focused_window(Some(window_id.into()));
+                handler.on_focus_gained();
+            }
+            _ => {}
+        }
+    }
+
+    /// Play the rumble the handler has [queued up](EventInputHandler::rumble) on the controllers the adapter
+    /// has open, and apply the [trigger effects](EventInputHandler::set_trigger_effect) to the ones with
+    /// adaptive triggers. Call this once per frame, after your game logic.
+    ///
+    /// Requests for controllers that aren't open are dropped, as are any the controller can't play. Trigger
+    /// effects are only sent to DualSense controllers.
+    pub fn rumble<C: Hash + Eq + Clone>(&mut self, handler: &mut EventInputHandler<SdlInput, C>) {
+        for request in handler.drain_rumble() {
+            for (id, controller) in self.controllers.iter_mut() {
+                if request.device.is_none_or(|device| device == *id) {
+                    let Rumble {
+                        low_frequency,
+                        high_frequency,
+                        duration,
+                    } = request.rumble;
+                    // Not every controller can rumble, and there's nothing to do about it if it can't
+                    let _ = controller.set_rumble(
+                        motor_strength(low_frequency),
+                        motor_strength(high_frequency),
+                        duration.as_millis().try_into().unwrap_or(u32::MAX),
+                    );
+                }
+            }
+        }
+        for request in handler.drain_trigger_effects() {
+            let packet = dualsense_trigger_packet(request.trigger, request.effect);
+            for (id, controller) in self.controllers.iter_mut() {
+                if request.device.is_none_or(|device| device == *id) {
+                    // Only DualSense controllers understand this packet, and SDL refuses it for anything else
+                    let _ = controller.send_effect(&packet);
+                }
+            }
+        }
+    }
+
+    fn is_held_by_any(&self, button: Button) -> bool {
+        self.held_buttons
+            .values()
+            .any(|held| held.contains(&button))
+    }
+}
+
+/// Build the effect packet SDL sends to a DualSense to set one trigger's effect.
+///
+/// This uses the simple effect modes that SDL's own controller test program uses.
+fn dualsense_trigger_packet(trigger: TriggerSide, effect: TriggerEffect) -> [u8; 47] {
+    // What fraction of the pull, or strength, as a byte
+    let byte = |amount: f32| (amount.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let params = match effect {
+        TriggerEffect::Off => [0x05, 0, 0, 0],
+        TriggerEffect::Resistance { start, strength } => [0x01, byte(start), byte(strength), 0],
+        TriggerEffect::TriggerStop { start, end } => [0x02, byte(start), byte(end), 0],
+        TriggerEffect::Vibration {
+            start,
+            frequency,
+            strength,
+        } => [
+            0x06,
+            frequency.clamp(0.0, 255.0).round() as u8,
+            byte(strength),
+            byte(start),
+        ],
+    };
+    let mut packet = [0; 47];
+    // Which trigger's effect to change, and where in the packet it goes
+    let (flag, offset) = match trigger {
+        TriggerSide::Right => (0x04, 10),
+        TriggerSide::Left => (0x08, 21),
+    };
+    packet[0] = flag;
+    packet[offset..offset + params.len()].copy_from_slice(&params);
+    packet
+}
+
+/// The text-entry edit a key makes, if it's an editing key.
+fn text_edit(key: Keycode) -> Option<TextEdit> {
+    match key {
+        Keycode::BACKSPACE => Some(TextEdit::Backspace),
+        Keycode::DELETE => Some(TextEdit::Delete),
+        Keycode::LEFT => Some(TextEdit::Left),
+        Keycode::RIGHT => Some(TextEdit::Right),
+        Keycode::HOME => Some(TextEdit::Home),
+        Keycode::END => Some(TextEdit::End),
+        _ => None,
+    }
+}
+
+/// Turn SDL's index in characters into a byte index into the text.
+fn byte_index(text: &str, chars: i32) -> usize {
+    let chars = usize::try_from(chars).unwrap_or(0);
+    text.char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(idx, _)| idx)
+}
+
+/// Turn a rumble strength from 0 to 1 into what SDL wants.
+fn motor_strength(strength: f32) -> u16 {
+    (strength.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+}
@@ -0,0 +1,206 @@
+use std::{hash::Hash, sync::Arc};
+
+use ahash::AHashMap;
+
+use crate::control_state::ControlState;
+
+/// A read-only snapshot of the state of all the controls and axis controls in an input handler.
+///
+/// Get one by calling `snapshot` on any input handler. It's cheap to clone and can be sent
+/// and shared across threads, so parallel game systems can all query the controls at once
+/// while the main thread keeps updating the handler itself.
+///
+/// The snapshot doesn't change when the handler is updated; take a new one each frame.
+///
+/// ```rust
+/// # use puppetmaster::PollingInputHandler;
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![('w', "up"), ('s', "down")]);
+/// input_handler.update(vec!['w']);
+///
+/// let snapshot = input_handler.snapshot();
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| assert!(snapshot.clicked("up")));
+///     scope.spawn(|| assert!(snapshot.up("down")));
+/// });
+/// ```
+#[derive(Debug)]
+pub struct InputSnapshot<C> {
+    /// The state of each control
+    states: Arc<AHashMap<C, ControlState>>,
+    /// The value of each axis control
+    values: Arc<AHashMap<C, f32>>,
+    /// How long each released control had been up for, for the ones that had been released
+    release_times: Arc<AHashMap<C, u32>>,
+    /// The value of each 2D control
+    vectors: Arc<AHashMap<C, (f32, f32)>>,
+}
+
+impl<C: Hash + Eq> InputSnapshot<C> {
+    pub(crate) fn new(states: AHashMap<C, ControlState>) -> Self {
+        Self {
+            states: Arc::new(states),
+            values: Arc::default(),
+            release_times: Arc::default(),
+            vectors: Arc::default(),
+        }
+    }
+
+    /// Add the value of each axis control.
+    pub(crate) fn with_values(mut self, values: AHashMap<C, f32>) -> Self {
+        self.values = Arc::new(values);
+        self
+    }
+
+    /// Add how long each released control has been up for.
+    pub(crate) fn with_release_times(mut self, release_times: AHashMap<C, u32>) -> Self {
+        self.release_times = Arc::new(release_times);
+        self
+    }
+
+    /// Add the value of each 2D control.
+    pub(crate) fn with_vectors(mut self, vectors: AHashMap<C, (f32, f32)>) -> Self {
+        self.vectors = Arc::new(vectors);
+        self
+    }
+
+    /// Return the number of frames the given control had been pressed for.
+    pub fn press_time(&self, ctrl: C) -> u32 {
+        self.states.get(&ctrl).map_or(0, |state| state.time)
+    }
+
+    /// Return if this control was held down.
+    pub fn down(&self, ctrl: C) -> bool {
+        self.press_time(ctrl) >= 1
+    }
+
+    /// Return if this control was up.
+    pub fn up(&self, ctrl: C) -> bool {
+        self.press_time(ctrl) == 0
+    }
+
+    /// Return if this control was *clicked* down on the frame the snapshot was taken.
+    pub fn clicked(&self, ctrl: C) -> bool {
+        self.states.get(&ctrl).is_some_and(|state| state.clicked)
+    }
+
+    /// Return the number of frames the given control had been up for since it was released, counting the frame
+    /// it was released on. This is 0 if the control was down, or had never been released.
+    pub fn release_time(&self, ctrl: C) -> u32 {
+        self.release_times.get(&ctrl).copied().unwrap_or(0)
+    }
+
+    /// Return if this control was released on the frame the snapshot was taken.
+    pub fn just_released(&self, ctrl: C) -> bool {
+        self.release_time(ctrl) == 1
+    }
+
+    /// Return the value of an axis control, from -1 to 1. This is 0 if nothing was bound to it.
+    pub fn value(&self, ctrl: C) -> f32 {
+        self.values.get(&ctrl).copied().unwrap_or(0.0)
+    }
+
+    /// Return the value of a 2D control, from its two axis controls. This is `(0.0, 0.0)` if it isn't a
+    /// [`DualAxis`](crate::DualAxis).
+    pub fn vector(&self, ctrl: C) -> (f32, f32) {
+        self.vectors.get(&ctrl).copied().unwrap_or((0.0, 0.0))
+    }
+
+    /// Return an iterator over all the controls that were held down.
+    pub fn all_pressed(&self) -> impl Iterator<Item = &C> + '_ {
+        self.states
+            .iter()
+            .filter_map(|(ctrl, state)| if state.time >= 1 { Some(ctrl) } else { None })
+    }
+
+    /// Merge two snapshots into one, as if either one could press any control.
+    ///
+    /// This is for copilot-style play, where two people on two devices (each with their own input handler)
+    /// control the same character. A control is down if it's down in either snapshot, and its press time
+    /// is the longer of the two. It only counts as clicked if the snapshot that clicked it has been holding
+    /// it the longest, so the second person pressing a button that's already held doesn't click it again.
+    ///
+    /// A control that's up in both counts as released for however long it's been up in the snapshot that
+    /// released it last.
+    ///
+    /// An axis control's value is whichever of the two is furthest from the middle, the same way a handler
+    /// picks between several axis inputs bound to one axis control, and a 2D control's value is whichever is
+    /// longer. If they're just as far, this snapshot's value wins.
+    ///
+    /// ```rust
+    /// # use puppetmaster::PollingInputHandler;
+    /// let bindings = vec![("a", "jump"), ("b", "crouch")];
+    /// let mut player = PollingInputHandler::new_with_controls(bindings.clone());
+    /// let mut copilot = PollingInputHandler::new_with_controls(bindings);
+    ///
+    /// player.update(vec!["a"]);
+    /// copilot.update(vec!["b"]);
+    /// let merged = player.snapshot().merge(&copilot.snapshot());
+    /// assert!(merged.clicked("jump") && merged.clicked("crouch"));
+    ///
+    /// player.update(vec!["a"]);
+    /// copilot.update(vec!["a", "b"]);
+    /// let merged = player.snapshot().merge(&copilot.snapshot());
+    /// assert_eq!(merged.press_time("jump"), 2);
+    /// assert!(!merged.clicked("jump"));
+    ///
+    /// player.axis_config_mut().insert("stick_x", "walk");
+    /// copilot.axis_config_mut().insert("stick_x", "walk");
+    /// player.set_axis_inputs(vec![("stick_x", 0.25)]);
+    /// copilot.set_axis_inputs(vec![("stick_x", -0.5)]);
+    /// player.update(vec![]);
+    /// copilot.update(vec![]);
+    /// let merged = player.snapshot().merge(&copilot.snapshot());
+    /// assert_eq!(merged.value("walk"), -0.5);
+    /// ```
+    pub fn merge(&self, other: &Self) -> Self
+    where
+        C: Clone,
+    {
+        let mut states = (*self.states).clone();
+        for (ctrl, theirs) in other.states.iter() {
+            let ours = states.entry(ctrl.clone()).or_default();
+            *ours = ControlState {
+                time: ours.time.max(theirs.time),
+                clicked: (ours.clicked && ours.time >= theirs.time)
+                    || (theirs.clicked && theirs.time >= ours.time),
+            };
+        }
+        let mut values = (*self.values).clone();
+        for (ctrl, theirs) in other.values.iter() {
+            let ours = values.entry(ctrl.clone()).or_default();
+            if theirs.abs() > ours.abs() {
+                *ours = *theirs;
+            }
+        }
+        let mut release_times = (*self.release_times).clone();
+        for (ctrl, &theirs) in other.release_times.iter() {
+            release_times
+                .entry(ctrl.clone())
+                .and_modify(|ours| *ours = (*ours).min(theirs))
+                .or_insert(theirs);
+        }
+        release_times.retain(|ctrl, _| states.get(ctrl).is_none_or(|state| state.time == 0));
+        let mut vectors = (*self.vectors).clone();
+        for (ctrl, &(x, y)) in other.vectors.iter() {
+            let ours = vectors.entry(ctrl.clone()).or_default();
+            if x.hypot(y) > ours.0.hypot(ours.1) {
+                *ours = (x, y);
+            }
+        }
+        Self::new(states)
+            .with_values(values)
+            .with_release_times(release_times)
+            .with_vectors(vectors)
+    }
+}
+
+impl<C> Clone for InputSnapshot<C> {
+    fn clone(&self) -> Self {
+        Self {
+            states: Arc::clone(&self.states),
+            values: Arc::clone(&self.values),
+            release_times: Arc::clone(&self.release_times),
+            vectors: Arc::clone(&self.vectors),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Glue for reading [Steam Input](https://partner.steamgames.com/doc/features/steam_controller) actions
+//! through [`steamworks`].
+//!
+//! Enable this with the `steamworks` feature.
+//!
+//! Steam Input does its own binding: players map their controllers to your game's *actions* in Steam's
+//! configurator, and you read the actions back by name. This feeds those actions into a
+//! [`PollingInputHandler`] as [`SteamAction`] inputs, so your game code checks controls the same way whether
+//! they came from Steam Input or from a keyboard.
+//!
+//! ```rust,ignore
+//! let (client, _single) = steamworks::Client::init()?;
+//! let mut adapter = SteamInputAdapter::new(
+//!     client.input(),
+//!     ["jump", "fire"],
+//!     ["move"],
+//! );
+//! adapter.set_action_set("InGameControls");
+//!
+//! let mut input_handler = PollingInputHandler::new_with_controls(vec![
+//!     (SteamAction::new("jump"), Control::Jump),
+//!     (SteamAction::new("fire"), Control::Shoot),
+//! ]);
+//!
+//! loop {
+//!     adapter.update(&mut input_handler);
+//!
+//!     // Now do game logic ...
+//!     let (move_x, move_y) = adapter.analog("move").unwrap_or_default();
+//! }
+//! ```
+
+use std::hash::Hash;
+
+use ::steamworks::Input;
+
+use crate::PollingInputHandler;
+
+/// Steam's handles for actions and action sets are all plain integers
+type Handle = u64;
+
+/// A Steam Input digital action, by the name it has in your action manifest.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SteamAction(pub String);
+
+impl SteamAction {
+    /// Make an action input from its name.
+    pub fn new(name: impl Into<String>) -> Self {
+        SteamAction(name.into())
+    }
+}
+
+/// Reads Steam Input actions each frame and feeds them to a [`PollingInputHandler`].
+///
+/// Actions from all connected controllers are merged together: an action is held as long as any controller
+/// is holding it.
+pub struct SteamInputAdapter {
+    input: Input,
+    /// Digital actions to read, and their handles (`0` until Steam knows about them)
+    digital_actions: Vec<(SteamAction, Handle)>,
+    /// Analog actions to read, their handles, and their most recent value
+    analog_actions: Vec<(String, Handle, (f32, f32))>,
+    /// The name of the action set to activate on every controller, and its handle
+    action_set: Option<(String, Handle)>,
+}
+
+impl SteamInputAdapter {
+    /// Create a new `SteamInputAdapter`, reading the given digital and analog actions.
+    ///
+    /// This initializes Steam Input, with the adapter running its frames.
+    pub fn new(
+        input: Input,
+        digital_actions: impl IntoIterator<Item = impl Into<String>>,
+        analog_actions: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        input.init(true);
+        Self {
+            input,
+            digital_actions: digital_actions
+                .into_iter()
+                .map(|name| (SteamAction::new(name), 0))
+                .collect(),
+            analog_actions: analog_actions
+                .into_iter()
+                .map(|name| (name.into(), 0, (0.0, 0.0)))
+                .collect(),
+            action_set: None,
+        }
+    }
+
+    /// Activate the action set with the given name on every controller, starting next update.
+    ///
+    /// Use this when your game switches between sets of controls, like walking around versus driving a car.
+    pub fn set_action_set(&mut self, name: impl Into<String>) {
+        self.action_set = Some((name.into(), 0));
+    }
+
+    /// Get the name of the active action set, if one's been set.
+    pub fn action_set(&self) -> Option<&str> {
+        self.action_set.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    /// Read the actions from Steam and update the input handler. Call this at the top of your game loop,
+    /// instead of calling [`PollingInputHandler::update`] yourself.
+    pub fn update<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut PollingInputHandler<SteamAction, C>,
+    ) {
+        self.input.run_frame();
+        self.resolve_handles();
+
+        let controllers = self.input.get_connected_controllers();
+        if let Some((_, set)) = self.action_set {
+            for &controller in &controllers {
+                self.input.activate_action_set_handle(controller, set);
+            }
+        }
+
+        for (_, handle, value) in self.analog_actions.iter_mut() {
+            *value = controllers
+                .iter()
+                .map(|&controller| self.input.get_analog_action_data(controller, *handle))
+                .filter(|data| data.bActive)
+                .map(|data| (data.x, data.y))
+                .max_by(|a, b| (a.0.hypot(a.1)).total_cmp(&b.0.hypot(b.1)))
+                .unwrap_or_default();
+        }
+
+        let input = &self.input;
+        let pressed = self
+            .digital_actions
+            .iter()
+            .filter(|(_, handle)| {
+                controllers.iter().any(|&controller| {
+                    let data = input.get_digital_action_data(controller, *handle);
+                    data.bActive && data.bState
+                })
+            })
+            .map(|(action, _)| action);
+        handler.update_borrowed(pressed);
+    }
+
+    /// Get the most recent value of an analog action, as `(x, y)`.
+    ///
+    /// If several controllers are using it, the one pushed the furthest wins. Returns `None` if the adapter
+    /// isn't reading an action with that name.
+    pub fn analog(&self, name: &str) -> Option<(f32, f32)> {
+        self.analog_actions
+            .iter()
+            .find(|(action, _, _)| action == name)
+            .map(|(_, _, value)| *value)
+    }
+
+    /// Look up any handles Steam didn't know about yet. Handles are `0` until the action manifest is loaded.
+    fn resolve_handles(&mut self) {
+        for (action, handle) in self.digital_actions.iter_mut() {
+            if *handle == 0 {
+                *handle = self.input.get_digital_action_handle(&action.0);
+            }
+        }
+        for (name, handle, _) in self.analog_actions.iter_mut() {
+            if *handle == 0 {
+                *handle = self.input.get_analog_action_handle(name);
+            }
+        }
+        if let Some((name, handle)) = &mut self.action_set {
+            if *handle == 0 {
+                *handle = self.input.get_action_set_handle(name);
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+/// Settings for navigating menus with an analog stick, turning the stick into up, down, left, and right
+/// clicks like a d-pad's.
+///
+/// Pushing the stick past `press_threshold` clicks the control for that direction once. If it's held there,
+/// the control clicks again after `delay` frames, then every `repeat` frames, so players can scroll through
+/// long lists. The stick has to come back inside `release_threshold` before it counts as let go, so a stick
+/// hovering right around the threshold doesn't stutter, and slow drift doesn't scroll the menu.
+///
+/// Give the handler the stick's position each frame with `set_navigation_stick`. Positive `y` is up, like
+/// [`ScrollControls`](crate::ScrollControls); if your engine's sticks are the other way around, flip them.
+/// The direction controls don't need any inputs bound to them, so a d-pad can be bound to the same controls.
+///
+/// ```rust
+/// # use puppetmaster::{PollingInputHandler, StickNavigation};
+/// let mut input_handler = PollingInputHandler::<(), _>::new();
+/// input_handler.set_stick_navigation(Some(StickNavigation {
+///     up: Some("menu_up"),
+///     down: Some("menu_down"),
+///     left: None,
+///     right: None,
+///     press_threshold: 0.5,
+///     release_threshold: 0.3,
+///     delay: 10,
+///     repeat: 4,
+/// }));
+///
+/// input_handler.set_navigation_stick((0.0, -0.9));
+/// input_handler.update(vec![]);
+/// assert!(input_handler.clicked("menu_down"));
+///
+/// // The stick drifts back a little, but not past the release threshold, so it doesn't click again.
+/// input_handler.set_navigation_stick((0.0, -0.4));
+/// input_handler.update(vec![]);
+/// input_handler.set_navigation_stick((0.0, -0.6));
+/// input_handler.update(vec![]);
+/// assert!(!input_handler.clicked("menu_down"));
+///
+/// // Holding it repeats after the delay.
+/// let mut clicks = 0;
+/// for _ in 0..20 {
+///     input_handler.update(vec![]);
+///     clicks += input_handler.clicked("menu_down") as u32;
+/// }
+/// assert_eq!(clicks, 4);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct StickNavigation<C> {
+    /// The control to click when the stick is pushed up (positive `y`).
+    pub up: Option<C>,
+    /// The control to click when the stick is pushed down (negative `y`).
+    pub down: Option<C>,
+    /// The control to click when the stick is pushed left (negative `x`).
+    pub left: Option<C>,
+    /// The control to click when the stick is pushed right (positive `x`).
+    pub right: Option<C>,
+    /// How far the stick has to be pushed in a direction, from 0 to 1, to click.
+    pub press_threshold: f32,
+    /// How far back the stick has to come before it's let go. This should be less than `press_threshold`.
+    pub release_threshold: f32,
+    /// How many frames the stick has to be held before it starts repeating. Values below 2 count as 2.
+    pub delay: u32,
+    /// How many frames apart the repeats are. Values below 2 count as 2, so each click is a fresh press.
+    pub repeat: u32,
+}
+
+/// Which way the navigation stick is pushed, and for how long.
+#[derive(Clone, Debug)]
+pub(crate) struct StickNavigationState<C> {
+    pub settings: StickNavigation<C>,
+    /// The latest position of the stick
+    stick: (f32, f32),
+    /// The direction the stick is held in, as an index into up, down, left, right
+    direction: Option<usize>,
+    /// How many frames the stick has been held in that direction
+    held_for: u32,
+}
+
+impl<C> StickNavigationState<C> {
+    pub fn new(settings: StickNavigation<C>) -> Self {
+        Self {
+            settings,
+            stick: (0.0, 0.0),
+            direction: None,
+            held_for: 0,
+        }
+    }
+
+    pub fn set_stick(&mut self, stick: (f32, f32)) {
+        self.stick = stick;
+    }
+
+    /// Move forward a frame. Returns the control to click this frame, if any.
+    pub fn update(&mut self) -> Option<&C> {
+        let (x, y) = self.stick;
+        // How far the stick is pushed in each direction
+        let pushed = [y, -y, -x, x];
+
+        let held = self
+            .direction
+            .filter(|&direction| pushed[direction] >= self.settings.release_threshold);
+        let direction = held.or_else(|| {
+            (0..4)
+                .filter(|&direction| pushed[direction] >= self.settings.press_threshold)
+                .max_by(|&a, &b| pushed[a].total_cmp(&pushed[b]))
+        });
+        if direction == self.direction {
+            self.held_for = self.held_for.saturating_add(1);
+        } else {
+            self.held_for = 0;
+        }
+        self.direction = direction;
+
+        // Let go for at least a frame between clicks, so each one is a fresh press
+        let (delay, repeat) = (self.settings.delay.max(2), self.settings.repeat.max(2));
+        let click = self.held_for == 0
+            || (self.held_for >= delay && (self.held_for - delay).is_multiple_of(repeat));
+        let settings = &self.settings;
+        direction.filter(|_| click).and_then(|direction| {
+            [
+                &settings.up,
+                &settings.down,
+                &settings.left,
+                &settings.right,
+            ][direction]
+                .as_ref()
+        })
+    }
+
+    /// Every control that can be clicked by the stick.
+    pub fn controls(&self) -> impl Iterator<Item = &C> + '_ {
+        [
+            &self.settings.up,
+            &self.settings.down,
+            &self.settings.left,
+            &self.settings.right,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    pub fn reset(&mut self) {
+        self.direction = None;
+        self.held_for = 0;
+    }
+}
@@ -0,0 +1,235 @@
+use std::{fmt::Write, hash::Hash};
+
+use ahash::AHashMap;
+
+/// How to show one input to the player.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputLabel {
+    /// The kind of device the input is on, like "Keyboard" or "Gamepad". Each kind gets its own column.
+    pub device: String,
+    /// What to call the input, like "Space" or "A".
+    pub label: String,
+}
+
+/// One control in a [`ControlsSummary`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControlSummary<C> {
+    /// The control.
+    pub control: C,
+    /// What to call the control, like "Jump".
+    pub name: String,
+    /// Which group the control goes in, like "Movement". Can be empty.
+    pub category: String,
+    /// Every input that presses the control right now, sorted by device and then label. Empty if the control
+    /// isn't bound to anything.
+    pub bindings: Vec<InputLabel>,
+}
+
+impl<C> ControlSummary<C> {
+    /// Get the labels of the inputs bound to the control on one kind of device.
+    pub fn bindings_for<'a>(&'a self, device: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.bindings
+            .iter()
+            .filter(move |input| input.device == device)
+            .map(|input| input.label.as_str())
+    }
+}
+
+/// A list of controls and what they're bound to, for help screens, manuals, and "Controls" pages in pause
+/// menus that always match the live bindings.
+///
+/// Get one from a handler with `export_controls_summary`. It's plain data, so draw it however you like, or
+/// use [`to_markdown`](Self::to_markdown) or [`to_plain_text`](Self::to_plain_text).
+///
+/// ```rust
+/// # use puppetmaster::{InputLabel, PollingInputHandler};
+/// let input_handler =
+///     PollingInputHandler::new_with_controls(vec![("space", "jump"), ("a", "left"), ("btn_south", "jump")]);
+///
+/// let summary = input_handler.export_controls_summary(
+///     ["left", "jump", "pause"],
+///     |ctrl| {
+///         let name = ctrl[..1].to_uppercase() + &ctrl[1..];
+///         let category = if *ctrl == "pause" { "Menus" } else { "Movement" };
+///         (name, category.to_owned())
+///     },
+///     |input| match input.strip_prefix("btn_") {
+///         Some(button) => InputLabel { device: "Gamepad".to_owned(), label: button.to_uppercase() },
+///         None => InputLabel { device: "Keyboard".to_owned(), label: input.to_uppercase() },
+///     },
+/// );
+///
+/// assert_eq!(
+///     summary.to_markdown(),
+///     "\
+/// ### Movement
+///
+/// | Control | Gamepad | Keyboard |
+/// | --- | --- | --- |
+/// | Left |  | A |
+/// | Jump | SOUTH | SPACE |
+///
+/// ### Menus
+///
+/// | Control | Gamepad | Keyboard |
+/// | --- | --- | --- |
+/// | Pause |  |  |
+/// "
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControlsSummary<C> {
+    /// Every control, in the order they were asked for.
+    pub controls: Vec<ControlSummary<C>>,
+}
+
+impl<C: Hash + Eq + Clone> ControlsSummary<C> {
+    /// Summarize the given controls, taking remapped inputs into account the same way the handlers do.
+    pub(crate) fn new<I: Hash + Eq>(
+        control_config: &AHashMap<I, C>,
+        input_remap: &AHashMap<I, I>,
+        controls: impl IntoIterator<Item = C>,
+        mut describe_control: impl FnMut(&C) -> (String, String),
+        mut describe_input: impl FnMut(&I) -> InputLabel,
+    ) -> Self {
+        let mut bindings = AHashMap::<&C, Vec<InputLabel>>::new();
+        // Inputs that are remapped act like the input they're remapped to, not themselves.
+        let direct = control_config
+            .iter()
+            .filter(|(input, _)| !input_remap.contains_key(*input));
+        let remapped = input_remap
+            .iter()
+            .filter_map(|(from, to)| control_config.get(to).map(|ctrl| (from, ctrl)));
+        for (input, ctrl) in direct.chain(remapped) {
+            bindings
+                .entry(ctrl)
+                .or_default()
+                .push(describe_input(input));
+        }
+
+        let controls = controls
+            .into_iter()
+            .map(|control| {
+                let (name, category) = describe_control(&control);
+                let mut bindings = bindings.remove(&control).unwrap_or_default();
+                bindings.sort();
+                bindings.dedup();
+                ControlSummary {
+                    control,
+                    name,
+                    category,
+                    bindings,
+                }
+            })
+            .collect();
+        Self { controls }
+    }
+}
+
+impl<C> ControlsSummary<C> {
+    /// Get every category, in the order they first show up.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories = Vec::new();
+        for control in self.controls.iter() {
+            if !categories.contains(&control.category.as_str()) {
+                categories.push(control.category.as_str());
+            }
+        }
+        categories
+    }
+
+    /// Get every kind of device that has something bound to it, sorted.
+    pub fn devices(&self) -> Vec<&str> {
+        let mut devices = self
+            .controls
+            .iter()
+            .flat_map(|control| control.bindings.iter().map(|input| input.device.as_str()))
+            .collect::<Vec<_>>();
+        devices.sort_unstable();
+        devices.dedup();
+        devices
+    }
+
+    /// Get the controls in a category, in order.
+    pub fn in_category<'a>(
+        &'a self,
+        category: &'a str,
+    ) -> impl Iterator<Item = &'a ControlSummary<C>> + 'a {
+        self.controls
+            .iter()
+            .filter(move |control| control.category == category)
+    }
+
+    /// Render the summary as Markdown: a table for each category, with a column for each kind of device.
+    /// Controls with an empty category go in a table without a heading.
+    pub fn to_markdown(&self) -> String {
+        let devices = self.devices();
+        let mut out = String::new();
+        for category in self.categories() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            if !category.is_empty() {
+                writeln!(out, "## {}\n", markdown_cell(category)).unwrap();
+            }
+            out.push_str("| Control |");
+            for device in devices.iter() {
+                write!(out, " {} |", markdown_cell(device)).unwrap();
+            }
+            out.push_str("\n| --- |");
+            out.push_str(&" --- |".repeat(devices.len()));
+            out.push('\n');
+            for control in self.in_category(category) {
+                write!(out, "| {} |", markdown_cell(&control.name)).unwrap();
+                for device in devices.iter() {
+                    let inputs = control.bindings_for(device).collect::<Vec<_>>().join(", ");
+                    if inputs.is_empty() {
+                        out.push_str("  |");
+                    } else {
+                        write!(out, " {} |", markdown_cell(&inputs)).unwrap();
+                    }
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Render the summary as plain text: each category on its own line, then a line for each control in it.
+    pub fn to_plain_text(&self) -> String {
+        let mut out = String::new();
+        for category in self.categories() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            let indent = if category.is_empty() {
+                ""
+            } else {
+                writeln!(out, "{}", category).unwrap();
+                "  "
+            };
+            for control in self.in_category(category) {
+                let inputs = control
+                    .bindings
+                    .iter()
+                    .map(|input| format!("{} ({})", input.label, input.device))
+                    .collect::<Vec<_>>();
+                let inputs = if inputs.is_empty() {
+                    "unbound".to_owned()
+                } else {
+                    inputs.join(", ")
+                };
+                writeln!(out, "{}{}: {}", indent, control.name, inputs).unwrap();
+            }
+        }
+        out
+    }
+}
+
+/// Escape the characters that would break a Markdown table.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
@@ -0,0 +1,280 @@
+/// Text the player is typing, while a handler is in text-entry mode.
+///
+/// ```rust
+/// # use puppetmaster::{PollingInputHandler, TextEdit};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![
+///     ("w", "up"),
+///     ("esc", "cancel"),
+/// ]);
+/// input_handler.set_text_entry_passthrough("cancel", true);
+///
+/// // The player opens the chat box ...
+/// input_handler.begin_text_entry();
+/// // ... and your engine reports typing.
+/// input_handler.on_text("wow!");
+/// input_handler.on_text_edit(TextEdit::Backspace);
+/// input_handler.update(vec!["w"]);
+/// assert_eq!(input_handler.text_entry().unwrap().text(), "wow");
+/// // Typing doesn't move the player ...
+/// assert!(input_handler.up("up"));
+///
+/// // ... but they can still cancel.
+/// input_handler.update(vec!["esc"]);
+/// assert!(input_handler.clicked("cancel"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TextEntry {
+    text: String,
+    /// Byte index of the cursor, always on a char boundary
+    cursor: usize,
+    /// Text the IME is composing, which isn't part of `text` yet
+    composition: String,
+    /// Where the IME's cursor or selection is in `composition`, as byte indices
+    composition_cursor: Option<(usize, usize)>,
+}
+
+impl TextEntry {
+    /// Get the text typed so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get where the cursor is, as a byte index into the [text](Self::text).
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Get the text an IME is in the middle of composing, which is empty if it isn't composing anything.
+    ///
+    /// This isn't part of the [text](Self::text) until the player commits it. Draw it at the cursor, usually
+    /// underlined.
+    pub fn composition(&self) -> &str {
+        &self.composition
+    }
+
+    /// Get where the IME's cursor (or selection, if the two differ) is in the
+    /// [composition](Self::composition), as byte indices. `None` means the IME wants the cursor hidden.
+    pub fn composition_cursor(&self) -> Option<(usize, usize)> {
+        self.composition_cursor
+    }
+
+    /// Replace the text, and put the cursor at the end. Use this to start editing something that's
+    /// already been typed, like the player's current name.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.text.len();
+    }
+
+    /// Type the text at the cursor. Control characters are ignored, because editing keys are
+    /// [`TextEdit`]s.
+    fn insert(&mut self, text: &str) {
+        for ch in text.chars().filter(|ch| !ch.is_control()) {
+            self.text.insert(self.cursor, ch);
+            self.cursor += ch.len_utf8();
+        }
+    }
+
+    fn ime(&mut self, event: ImeEvent) {
+        match event {
+            ImeEvent::Preedit { text, cursor } => {
+                self.composition = text;
+                self.composition_cursor = cursor;
+            }
+            ImeEvent::Commit(text) => {
+                self.composition.clear();
+                self.composition_cursor = None;
+                self.insert(&text);
+            }
+            ImeEvent::Cancel => {
+                self.composition.clear();
+                self.composition_cursor = None;
+            }
+        }
+    }
+
+    fn edit(&mut self, edit: TextEdit) {
+        match edit {
+            TextEdit::Backspace => {
+                if let Some(ch) = self.text[..self.cursor].chars().next_back() {
+                    self.cursor -= ch.len_utf8();
+                    self.text.remove(self.cursor);
+                }
+            }
+            TextEdit::Delete => {
+                if self.cursor < self.text.len() {
+                    self.text.remove(self.cursor);
+                }
+            }
+            TextEdit::Left => {
+                if let Some(ch) = self.text[..self.cursor].chars().next_back() {
+                    self.cursor -= ch.len_utf8();
+                }
+            }
+            TextEdit::Right => {
+                if let Some(ch) = self.text[self.cursor..].chars().next() {
+                    self.cursor += ch.len_utf8();
+                }
+            }
+            TextEdit::Home => self.cursor = 0,
+            TextEdit::End => self.cursor = self.text.len(),
+        }
+    }
+}
+
+/// An editing key pressed during text entry.
+///
+/// Engines report these as ordinary key presses, so the engine adapters pass them on with
+/// `on_text_edit`; if you don't use an adapter, call it yourself for your engine's keys (including
+/// key repeats, so holding backspace works).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextEdit {
+    /// Delete the character before the cursor.
+    Backspace,
+    /// Delete the character after the cursor.
+    Delete,
+    /// Move the cursor one character left.
+    Left,
+    /// Move the cursor one character right.
+    Right,
+    /// Move the cursor to the start of the text.
+    Home,
+    /// Move the cursor to the end of the text.
+    End,
+}
+
+/// Something an input method editor did during text entry.
+///
+/// IMEs let players type languages like Chinese and Japanese by composing each piece of text over several
+/// keystrokes before committing it. Engines report what the IME is doing as events; the engine adapters pass
+/// them on with `on_ime`.
+///
+/// While the IME is composing, and on any frame it did something, every control is ignored, even ones that
+/// [pass through](crate::Controls::set_text_entry_passthrough) text entry. So the escape key that
+/// cancels a composition won't close the chat box too.
+///
+/// ```rust
+/// # use puppetmaster::{ImeEvent, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![("esc", "close_chat")]);
+/// input_handler.set_text_entry_passthrough("close_chat", true);
+/// input_handler.begin_text_entry();
+///
+/// input_handler.on_ime(ImeEvent::Preedit {
+///     text: "にほ".to_string(),
+///     cursor: Some((6, 6)),
+/// });
+/// input_handler.update(vec![]);
+/// assert_eq!(input_handler.text_entry().unwrap().composition(), "にほ");
+///
+/// // Escape cancels the composition, and doesn't close the chat.
+/// input_handler.on_ime(ImeEvent::Cancel);
+/// input_handler.update(vec!["esc"]);
+/// assert!(input_handler.up("close_chat"));
+///
+/// input_handler.on_ime(ImeEvent::Commit("日本".to_string()));
+/// input_handler.update(vec![]);
+/// assert_eq!(input_handler.text_entry().unwrap().text(), "日本");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ImeEvent {
+    /// The IME's in-progress text changed. Empty text means it isn't composing anything anymore.
+    Preedit {
+        /// The text being composed.
+        text: String,
+        /// Where the IME's cursor (or selection) is in the text, as byte indices, or `None` to hide it.
+        cursor: Option<(usize, usize)>,
+    },
+    /// The IME finished composing, and this text should be typed.
+    Commit(String),
+    /// The IME threw its in-progress text away, or was turned off.
+    Cancel,
+}
+
+/// Something typed, waiting for the next tick.
+#[derive(Clone, Debug)]
+enum Typed {
+    Text(String),
+    Edit(TextEdit),
+    Ime(ImeEvent),
+}
+
+/// Text entry, if it's on, and what's been typed since the last tick.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TextState {
+    entry: Option<TextEntry>,
+    typed: Vec<Typed>,
+    /// If the IME was busy during the last tick
+    composing: bool,
+    /// If anything was typed during the last tick
+    changed: bool,
+}
+
+impl TextState {
+    /// Apply everything typed since the last tick.
+    pub fn update(&mut self) {
+        self.changed = false;
+        let Some(entry) = &mut self.entry else {
+            self.typed.clear();
+            self.composing = false;
+            return;
+        };
+        self.composing = !entry.composition.is_empty();
+        for typed in self.typed.drain(..) {
+            self.changed = true;
+            match typed {
+                Typed::Text(text) => entry.insert(&text),
+                Typed::Edit(edit) => entry.edit(edit),
+                Typed::Ime(event) => {
+                    self.composing = true;
+                    entry.ime(event);
+                }
+            }
+        }
+        self.composing |= !entry.composition.is_empty();
+    }
+
+    /// If the IME was doing something during the last tick, so controls should be ignored.
+    pub fn composing(&self) -> bool {
+        self.composing
+    }
+
+    /// If anything was typed during the last tick.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    pub fn on_ime(&mut self, event: ImeEvent) {
+        if self.entry.is_some() {
+            self.typed.push(Typed::Ime(event));
+        }
+    }
+
+    pub fn on_text(&mut self, text: &str) {
+        if self.entry.is_some() {
+            self.typed.push(Typed::Text(text.to_owned()));
+        }
+    }
+
+    pub fn on_edit(&mut self, edit: TextEdit) {
+        if self.entry.is_some() {
+            self.typed.push(Typed::Edit(edit));
+        }
+    }
+
+    pub fn begin(&mut self) {
+        self.entry.get_or_insert_with(TextEntry::default);
+    }
+
+    pub fn end(&mut self) -> Option<String> {
+        self.typed.clear();
+        self.composing = false;
+        self.entry.take().map(|entry| entry.text)
+    }
+
+    pub fn entry(&self) -> Option<&TextEntry> {
+        self.entry.as_ref()
+    }
+
+    pub fn entry_mut(&mut self) -> Option<&mut TextEntry> {
+        self.entry.as_mut()
+    }
+}
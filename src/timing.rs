@@ -0,0 +1,267 @@
+use std::time::Duration;
+
+/// How soon after being released a control can be pressed again.
+///
+/// Presses that come sooner than this are ignored. Worn-out buttons, some accessibility switches, and
+/// players with tremors can all register one press as several; this filters the extras out.
+///
+/// See [`EventInputHandler::set_debounce`](crate::Controls::set_debounce).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Debounce {
+    /// Ignore presses within this many frames of the release.
+    Frames(u32),
+    /// Ignore presses within this much time of the release.
+    Duration(Duration),
+}
+
+/// How many frames in a row a control's inputs have to be pressed (or released) before the control is.
+///
+/// This suppresses accidental brushes against a button and taps caused by tremors. A control that's
+/// waiting for confirmation keeps its old state, so [`clicked`](crate::Controls::clicked) fires
+/// on the frame the press is confirmed.
+///
+/// See [`EventInputHandler::set_confirmation`](crate::Controls::set_confirmation).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Confirmation {
+    /// How many frames in a row the inputs need to be pressed. 0 and 1 both mean the press counts right away.
+    pub press: u32,
+    /// How many frames in a row the inputs need to be released. 0 and 1 both mean the release counts right away.
+    pub release: u32,
+}
+
+/// How long a control's inputs have to be held before the press counts at all.
+///
+/// Shorter presses are ignored, which filters out accidental touches on sensitive triggers and touchpads.
+/// [`clicked`](crate::Controls::clicked) fires when the threshold is crossed, not when the input
+/// went down.
+///
+/// See [`EventInputHandler::set_min_hold`](crate::Controls::set_min_hold).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MinHold {
+    /// The inputs have to be held for this many frames. 0 and 1 both mean the press counts right away.
+    Frames(u32),
+    /// The inputs have to be held for this long.
+    Duration(Duration),
+}
+
+/// How long a control has to be held to confirm something, for "hold X to delete save" prompts.
+///
+/// Unlike [`MinHold`], this doesn't change when the control counts as pressed; it tracks how far the
+/// player is toward confirming, so you can draw a progress bar. Letting go early cancels it.
+///
+/// See [`EventInputHandler::set_hold_confirm`](crate::Controls::set_hold_confirm).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HoldConfirm {
+    /// The control has to be held for this many frames.
+    Frames(u32),
+    /// The control has to be held for this long.
+    Duration(Duration),
+}
+
+/// How often a control is allowed to click.
+///
+/// Clicks beyond the limit are dropped, which keeps players from spamming chat messages and emotes, and
+/// blunts macros and autoclickers in online games. The control still goes down and up as usual; only
+/// [`clicked`](crate::Controls::clicked) (and the `Pressed` event) is held back.
+///
+/// See [`EventInputHandler::set_rate_limit`](crate::Controls::set_rate_limit).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RateLimit {
+    /// How many clicks are allowed in each `period`.
+    pub clicks: u32,
+    /// The window of time the clicks are counted over.
+    pub period: Duration,
+}
+
+impl RateLimit {
+    /// Allow this many clicks per second.
+    pub fn per_second(clicks: u32) -> Self {
+        Self {
+            clicks,
+            period: Duration::from_secs(1),
+        }
+    }
+}
+
+/// How a held control clicks again, like a key held down in a text box: once after a delay, then over and
+/// over at a steady rate.
+///
+/// Unlike turbo, the first repeat waits longer than the rest, so a quick press only clicks once. This is
+/// what players expect from menus and text fields.
+///
+/// See [`PollingInputHandler::set_auto_repeat`](crate::Controls::set_auto_repeat).
+///
+/// ```rust
+/// # use puppetmaster::{AutoRepeat, PollingInputHandler};
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![('s', "menu_down")]);
+/// input_handler.set_auto_repeat("menu_down", Some(AutoRepeat { delay: 10, interval: 3 }));
+///
+/// let clicks = (0..20)
+///     .filter(|_| {
+///         input_handler.update(vec!['s']);
+///         input_handler.clicked("menu_down")
+///     })
+///     .count();
+/// // Once when it's pressed, then 10 frames later, then every 3 frames.
+/// assert_eq!(clicks, 5);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AutoRepeat {
+    /// How many frames after the press the first repeat comes. 0 counts as 1.
+    pub delay: u32,
+    /// How many frames apart the repeats after that are. 0 counts as 1.
+    pub interval: u32,
+}
+
+impl AutoRepeat {
+    /// Return if a control held for `time` frames repeats on this frame.
+    pub(crate) fn repeats_on(&self, time: u32) -> bool {
+        let first = self.delay.max(1) + 1;
+        time >= first && (time - first).is_multiple_of(self.interval.max(1))
+    }
+}
+
+/// Which clock a timer measured in time, rather than frames, runs on.
+///
+/// Game time runs at the handler's [time scale](crate::Controls::set_time_scale), so it slows down
+/// in slow motion and stops during hitstop. Real time always runs at the speed of the wall clock. Timers
+/// counted in frames always count every update, whatever the time scale.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Clock {
+    /// Run on game time, scaled by the time scale. This is the default.
+    #[default]
+    Game,
+    /// Run on real time, ignoring the time scale.
+    Real,
+}
+
+/// Which clock each of a handler's time-based timers runs on. Everything runs on game time by default.
+///
+/// Apply these with `set_timer_clocks` (like
+/// [`EventInputHandler::set_timer_clocks`](crate::Controls::set_timer_clocks)).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimerClocks {
+    /// The clock for [`Debounce::Duration`]. Worn-out switches bounce in real time, so you might want
+    /// [`Clock::Real`] here.
+    pub debounce: Clock,
+    /// The clock for [`MinHold::Duration`].
+    pub min_hold: Clock,
+    /// The clock for [`HoldConfirm::Duration`].
+    pub hold_confirm: Clock,
+    /// The clock for [`RateLimit`]'s period.
+    pub rate_limit: Clock,
+}
+
+/// All the timing-related settings for an input handler's controls, in one place.
+///
+/// Apply these to a handler with `set_timing` (like [`EventInputHandler::set_timing`](crate::Controls::set_timing)).
+/// They're used for every control that hasn't been given its own setting with `set_debounce` and friends.
+/// Setting a control's own setting to `None` turns it off for that control; `clear_debounce` and friends
+/// put it back on these.
+///
+/// Keeping them together makes it easy to save them with the rest of a player's settings, and to offer
+/// accessibility presets like "relaxed timing" that change everything at once.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use puppetmaster::{Debounce, PollingInputHandler, TapOrHold, TimingSettings};
+/// let relaxed = TimingSettings {
+///     debounce: Some(Debounce::Duration(Duration::from_millis(150))),
+///     tap_window: 25,
+///     hold_threshold: Some(20),
+///     ..TimingSettings::default()
+/// };
+///
+/// let mut input_handler = PollingInputHandler::new_with_controls(vec![('a', "attack")]);
+/// input_handler.set_timing(relaxed);
+/// assert_eq!(input_handler.debounce("attack"), relaxed.debounce);
+/// assert_eq!(input_handler.tap_window("attack"), 25);
+///
+/// input_handler.update(vec!['a']);
+/// input_handler.update(vec![]);
+/// assert_eq!(input_handler.tap_or_hold("attack"), Some(TapOrHold::Tap));
+///
+/// input_handler.set_debounce("attack", None);
+/// assert_eq!(input_handler.debounce("attack"), None);
+/// input_handler.clear_debounce("attack");
+/// assert_eq!(input_handler.debounce("attack"), relaxed.debounce);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TimingSettings {
+    /// How soon after being released a control can be pressed again. See [`Debounce`].
+    pub debounce: Option<Debounce>,
+    /// How many frames in a row a control's inputs have to agree before it changes. See [`Confirmation`].
+    pub confirmation: Option<Confirmation>,
+    /// How long a control's inputs have to be held before the press counts. See [`MinHold`].
+    pub min_hold: Option<MinHold>,
+    /// How many frames apart a held control clicks again, if it should.
+    pub turbo: Option<u32>,
+    /// How a held control clicks again after a delay, if it should. See [`AutoRepeat`].
+    pub auto_repeat: Option<AutoRepeat>,
+    /// How often a control is allowed to click. See [`RateLimit`].
+    pub rate_limit: Option<RateLimit>,
+    /// How many frames apart presses can be to count as one burst of taps, for double-taps and the like.
+    /// This is 15 by default.
+    pub tap_window: u32,
+    /// How many frames a press has to be held for to count as a hold rather than a tap, if taps should be
+    /// told from holds. See `set_hold_threshold` on the handlers.
+    pub hold_threshold: Option<u32>,
+    /// How many frames a press of a control in a [`Chord`](crate::Chord) waits for the rest of the chord
+    /// before it goes through on its own. This is 0 by default, so presses are never held back.
+    pub chord_delay: u32,
+}
+
+impl Default for TimingSettings {
+    fn default() -> Self {
+        Self {
+            debounce: None,
+            confirmation: None,
+            min_hold: None,
+            turbo: None,
+            auto_repeat: None,
+            rate_limit: None,
+            tap_window: 15,
+            hold_threshold: None,
+            chord_delay: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repeats(repeat: AutoRepeat, frames: u32) -> Vec<u32> {
+        (1..=frames)
+            .filter(|&time| repeat.repeats_on(time))
+            .collect()
+    }
+
+    #[test]
+    fn auto_repeat_waits_for_the_delay() {
+        let repeat = AutoRepeat {
+            delay: 10,
+            interval: 3,
+        };
+        assert_eq!(repeats(repeat, 20), vec![11, 14, 17, 20]);
+    }
+
+    #[test]
+    fn auto_repeat_zero_counts_as_one() {
+        let repeat = AutoRepeat {
+            delay: 0,
+            interval: 0,
+        };
+        assert_eq!(repeats(repeat, 5), vec![2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,316 @@
+use std::fmt::{self, Write};
+
+use ahash::AHashMap;
+use itertools::Itertools;
+
+use crate::{Chord, ControlId, ControlRegistry, GhostingModel};
+
+/// Check a keybinding config file against the controls the game has, and load the bindings that are fine.
+///
+/// The config has one binding per line, as `input = control`. Blank lines and lines starting with `#` are
+/// skipped. `parse_input` turns the text on the left into an input, and the control on the right has to be
+/// registered in `controls`. Several inputs can be bound to one control, but binding one input to two
+/// controls is a conflict, and the first binding wins.
+///
+/// This is meant for checking binding files outside the game, like ones players share or the ones you ship,
+/// in a build script or a small tool of your own. The game can load its config with it too, and show players
+/// what was wrong with theirs.
+///
+/// ```rust
+/// # use puppetmaster::{validate_bindings, ControlRegistry, IssueKind};
+/// let mut controls = ControlRegistry::new();
+/// let jump = controls.register("jump");
+/// controls.register("crouch");
+///
+/// let config = "
+/// ## Player bindings
+/// space = jump
+/// w = jump
+/// s = crouch
+/// s = jump
+/// q = dance
+/// 7 = crouch
+/// ";
+/// let report = validate_bindings(config, &controls, |input| {
+///     input.chars().all(|c| c.is_ascii_alphabetic()).then(|| input.to_owned())
+/// });
+///
+/// assert!(!report.is_ok());
+/// assert_eq!(report.bindings[0], ("space".to_owned(), jump));
+/// assert_eq!(report.bindings.len(), 3);
+/// assert_eq!(report.issues[0].kind, IssueKind::Conflict { input: "s".to_owned(), first_line: 5 });
+/// assert_eq!(
+///     report.machine_readable(),
+///     "6\tconflict\ts\n7\tunknown-control\tdance\n8\tbad-input\t7\n",
+/// );
+/// ```
+pub fn validate_bindings<I>(
+    config: &str,
+    controls: &ControlRegistry,
+    mut parse_input: impl FnMut(&str) -> Option<I>,
+) -> ValidationReport<I> {
+    let mut report = ValidationReport {
+        bindings: Vec::new(),
+        issues: Vec::new(),
+        sources: Vec::new(),
+    };
+    // Which line each input was first bound on
+    let mut bound_on = AHashMap::new();
+
+    for (idx, line) in config.lines().enumerate() {
+        let line_number = idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut issue = |kind| {
+            report.issues.push(ValidationIssue {
+                line: line_number,
+                kind,
+            })
+        };
+
+        let Some((input_text, control_text)) = line.split_once('=') else {
+            issue(IssueKind::Malformed);
+            continue;
+        };
+        let (input_text, control_text) = (input_text.trim(), control_text.trim());
+
+        let Some(ctrl) = controls.get(control_text) else {
+            issue(IssueKind::UnknownControl {
+                control: control_text.to_owned(),
+            });
+            continue;
+        };
+        let Some(input) = parse_input(input_text) else {
+            issue(IssueKind::BadInput {
+                input: input_text.to_owned(),
+            });
+            continue;
+        };
+        match bound_on.get(input_text) {
+            Some(&(first_line, first_ctrl)) if first_ctrl != ctrl => issue(IssueKind::Conflict {
+                input: input_text.to_owned(),
+                first_line,
+            }),
+            // Binding the same thing twice is harmless
+            Some(_) => {}
+            None => {
+                bound_on.insert(input_text, (line_number, ctrl));
+                report.bindings.push((input, ctrl));
+                report.sources.push((line_number, input_text.to_owned()));
+            }
+        }
+    }
+
+    report
+}
+
+/// What [`validate_bindings`] found in a config.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationReport<I> {
+    /// Every binding that was fine, in the order they were in the config. This can go straight to a
+    /// handler's `new_with_controls`.
+    pub bindings: Vec<(I, ControlId)>,
+    /// Everything wrong with the config, in the order it was found.
+    pub issues: Vec<ValidationIssue>,
+    /// The line each binding is on, and its input as written
+    sources: Vec<(usize, String)>,
+}
+
+impl<I> ValidationReport<I> {
+    /// Return if nothing was wrong with the config. [Warnings](IssueKind::is_warning) don't count.
+    pub fn is_ok(&self) -> bool {
+        self.issues.iter().all(|issue| issue.kind.is_warning())
+    }
+
+    /// Warn about [chords](Chord) whose bindings might not work on cheap keyboards, according to `model`.
+    /// See [`GhostingModel`].
+    ///
+    /// Every way of pressing each chord with the inputs bound to its controls is checked. If some of them
+    /// ghost, the first one that does gets an [`IssueKind::Ghosting`] warning, on the line of the last
+    /// binding it uses. Chords with a control that isn't bound to anything are skipped.
+    ///
+    /// ```rust
+    /// # use puppetmaster::{validate_bindings, Chord, ControlRegistry, IssueKind, KeyMatrix};
+    /// let mut controls = ControlRegistry::new();
+    /// let (up, left, fire) = (controls.register("up"), controls.register("left"), controls.register("fire"));
+    ///
+    /// let config = "
+    /// w = up
+    /// a = left
+    /// s = fire
+    /// ";
+    /// let mut report = validate_bindings(config, &controls, |input| input.chars().next());
+    /// let matrix = KeyMatrix::new([('w', (0, 1)), ('a', (1, 0)), ('s', (1, 1))]);
+    /// let chords = [Chord { controls: vec![up, left, fire] }];
+    /// report.check_ghosting(&chords, &matrix);
+    ///
+    /// // It's only a warning, so the bindings can still be used.
+    /// assert!(report.is_ok());
+    /// assert_eq!(report.issues[0].line, 4);
+    /// assert_eq!(
+    ///     report.issues[0].kind,
+    ///     IssueKind::Ghosting { inputs: vec!["w".to_owned(), "a".to_owned(), "s".to_owned()], unavoidable: true },
+    /// );
+    /// ```
+    pub fn check_ghosting<'a>(
+        &mut self,
+        chords: impl IntoIterator<Item = &'a Chord<ControlId>>,
+        model: &impl GhostingModel<I>,
+    ) {
+        for chord in chords {
+            // The bindings that could press each control in the chord
+            let choices = chord
+                .controls
+                .iter()
+                .map(|ctrl| {
+                    self.bindings
+                        .iter()
+                        .positions(|(_, bound)| bound == ctrl)
+                        .collect_vec()
+                })
+                .collect_vec();
+            if choices.is_empty() || choices.iter().any(Vec::is_empty) {
+                continue;
+            }
+            let mut first_ghost = None;
+            let mut all_ghost = true;
+            for combo in choices.into_iter().multi_cartesian_product() {
+                let inputs = combo.iter().map(|&idx| &self.bindings[idx].0).collect_vec();
+                if model.ghosts(&inputs) {
+                    first_ghost.get_or_insert(combo);
+                } else {
+                    all_ghost = false;
+                }
+            }
+            if let Some(combo) = first_ghost {
+                let line = combo
+                    .iter()
+                    .map(|&idx| self.sources[idx].0)
+                    .max()
+                    .unwrap_or(0);
+                self.issues.push(ValidationIssue {
+                    line,
+                    kind: IssueKind::Ghosting {
+                        inputs: combo
+                            .iter()
+                            .map(|&idx| self.sources[idx].1.clone())
+                            .collect(),
+                        unavoidable: all_ghost,
+                    },
+                });
+            }
+        }
+    }
+
+    /// Write the issues out for other programs to read: one line per issue, with the line number, the
+    /// [issue's code](IssueKind::code), and the input or control it's about, separated by tabs.
+    pub fn machine_readable(&self) -> String {
+        let mut out = String::new();
+        for issue in self.issues.iter() {
+            let subject = match &issue.kind {
+                IssueKind::Malformed => "",
+                IssueKind::UnknownControl { control } => control,
+                IssueKind::BadInput { input } | IssueKind::Conflict { input, .. } => input,
+                IssueKind::Ghosting { inputs, .. } => &inputs.join("+"),
+            };
+            // Writing to a string can't fail
+            let _ = writeln!(out, "{}\t{}\t{}", issue.line, issue.kind.code(), subject);
+        }
+        out
+    }
+}
+
+/// Something wrong with one line of a config.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ValidationIssue {
+    /// The line it's on, starting from 1.
+    pub line: usize,
+    /// What's wrong with it.
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            IssueKind::Malformed => write!(f, "expected `input = control`"),
+            IssueKind::UnknownControl { control } => {
+                write!(f, "there's no control named `{}`", control)
+            }
+            IssueKind::BadInput { input } => write!(f, "`{}` isn't an input", input),
+            IssueKind::Conflict { input, first_line } => write!(
+                f,
+                "`{}` is already bound to another control on line {}",
+                input, first_line
+            ),
+            IssueKind::Ghosting {
+                inputs,
+                unavoidable,
+            } => {
+                let inputs = inputs.iter().map(|input| format!("`{}`", input)).join(", ");
+                write!(
+                    f,
+                    "{} might not work held together on some keyboards",
+                    inputs
+                )?;
+                if *unavoidable {
+                    write!(f, ", and there's no other way to press them")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The kinds of things that can be wrong with a line of a config.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IssueKind {
+    /// The line isn't `input = control`.
+    Malformed,
+    /// The control isn't registered.
+    UnknownControl {
+        /// The control's name, as written.
+        control: String,
+    },
+    /// The input couldn't be parsed.
+    BadInput {
+        /// The input, as written.
+        input: String,
+    },
+    /// The input was already bound to a different control. The binding on this line is dropped.
+    Conflict {
+        /// The input, as written.
+        input: String,
+        /// The line it was first bound on.
+        first_line: usize,
+    },
+    /// Holding a chord's inputs together might not work on cheap keyboards. This is only a warning; the
+    /// binding is kept. See [`ValidationReport::check_ghosting`].
+    Ghosting {
+        /// The inputs, as written.
+        inputs: Vec<String>,
+        /// If every way of pressing the chord has this problem.
+        unavoidable: bool,
+    },
+}
+
+impl IssueKind {
+    /// Get a short, stable name for this kind of issue: `malformed`, `unknown-control`, `bad-input`,
+    /// `conflict`, or `ghosting`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            IssueKind::Malformed => "malformed",
+            IssueKind::UnknownControl { .. } => "unknown-control",
+            IssueKind::BadInput { .. } => "bad-input",
+            IssueKind::Conflict { .. } => "conflict",
+            IssueKind::Ghosting { .. } => "ghosting",
+        }
+    }
+
+    /// Return if this is only a warning, which doesn't stop the config from working.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, IssueKind::Ghosting { .. })
+    }
+}
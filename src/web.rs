@@ -0,0 +1,172 @@
+//! Glue for feeding browser events into an [`EventInputHandler`](crate::EventInputHandler), for games running
+//! on wasm.
+//!
+//! Enable this with the `web` feature.
+//!
+//! A [`WebAdapter`] listens for keyboard and pointer events on an element (or the whole window), and sends them
+//! through an [`EventSender`]. Keep it around for as long as you want input; dropping it removes the listeners.
+//!
+//! ```rust,no_run
+//! # use puppetmaster::{EventInputHandler, web::{WebAdapter, WebInput}};
+//! #[derive(Clone, Copy, Hash, Eq, PartialEq)]
+//! enum Control {
+//!     Up,
+//!     Shoot,
+//! }
+//!
+//! let mut input_handler = EventInputHandler::new_with_controls(vec![
+//!     (WebInput::key("KeyW"), Control::Up),
+//!     (WebInput::Pointer(0), Control::Shoot),
+//! ]);
+//! let window = web_sys::window().unwrap();
+//! let adapter = WebAdapter::new(input_handler.sender(), &window).unwrap();
+//!
+//! // Then, in your `requestAnimationFrame` callback ...
+//! input_handler.update();
+//! ```
+
+use std::{cell::RefCell, rc::Rc};
+
+use ::wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use ::web_sys::{Event, EventTarget, KeyboardEvent, PointerEvent};
+use ahash::AHashSet;
+
+use crate::EventSender;
+
+/// Anything the browser treats as a button.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WebInput {
+    /// A key on the keyboard, by its [`code`](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/code),
+    /// like `"KeyW"` or `"Space"`.
+    ///
+    /// Codes name the physical key, so bindings stay in the same place regardless of the keyboard layout.
+    Key(String),
+    /// A mouse button, pen button, or touch, by its
+    /// [`button`](https://developer.mozilla.org/en-US/docs/Web/API/MouseEvent/button) number.
+    /// `0` is the left mouse button (and touches), `1` is the middle one, and `2` is the right one.
+    Pointer(i16),
+}
+
+impl WebInput {
+    /// Make a key input from its code.
+    pub fn key(code: impl Into<String>) -> Self {
+        WebInput::Key(code.into())
+    }
+}
+
+/// The events the adapter listens for on the target
+const TARGET_EVENTS: [&str; 4] = ["keydown", "keyup", "pointerdown", "pointerup"];
+/// The events that mean everything held should be let go of
+const RELEASE_EVENTS: [&str; 2] = ["blur", "pointercancel"];
+
+/// An event listener that's been added to a target, and the event it's listening for
+type Listener = (EventTarget, &'static str, Closure<dyn FnMut(Event)>);
+
+/// Sends browser input events to an [`EventSender`].
+///
+/// This smooths over a few of the browser's quirks:
+///
+/// - Auto-repeated `keydown`s are sent along as presses of an already-held key, so the handler's
+///   [`RepeatPolicy`](crate::RepeatPolicy) decides what to do with them.
+/// - When the page loses focus or is hidden, browsers stop sending `keyup`s, so everything held is released.
+/// - On macOS, no `keyup`s are sent for keys released while the command key is down, so releasing the command key
+///   releases everything.
+pub struct WebAdapter {
+    /// The listeners to remove on drop
+    listeners: Vec<Listener>,
+}
+
+impl WebAdapter {
+    /// Start listening for input on the given target.
+    ///
+    /// Key events only fire on focusable things, so if you pass a canvas, give it a `tabindex`.
+    /// Passing the window catches everything.
+    ///
+    /// Returns an error if the browser won't let the listeners be added.
+    pub fn new(sender: EventSender<WebInput>, target: &EventTarget) -> Result<Self, JsValue> {
+        let held = Rc::new(RefCell::new(AHashSet::new()));
+        let mut adapter = Self {
+            listeners: Vec::new(),
+        };
+
+        for kind in TARGET_EVENTS {
+            let (sender, held) = (sender.clone(), Rc::clone(&held));
+            adapter.listen(target, kind, move |event| {
+                on_input_event(&sender, &mut held.borrow_mut(), &event)
+            })?;
+        }
+
+        let window = ::web_sys::window();
+        let release_targets = window.iter().map(AsRef::<EventTarget>::as_ref);
+        for release_target in release_targets.chain([target]) {
+            for kind in RELEASE_EVENTS {
+                let (sender, held) = (sender.clone(), Rc::clone(&held));
+                adapter.listen(release_target, kind, move |_| {
+                    release_all(&sender, &mut held.borrow_mut())
+                })?;
+            }
+        }
+        if let Some(document) = window.and_then(|window| window.document()) {
+            let (sender, held) = (sender.clone(), Rc::clone(&held));
+            let hidden = document.clone();
+            adapter.listen(&document, "visibilitychange", move |_| {
+                if hidden.hidden() {
+                    release_all(&sender, &mut held.borrow_mut());
+                }
+            })?;
+        }
+
+        Ok(adapter)
+    }
+
+    fn listen(
+        &mut self,
+        target: &EventTarget,
+        kind: &'static str,
+        callback: impl FnMut(Event) + 'static,
+    ) -> Result<(), JsValue> {
+        let closure = Closure::<dyn FnMut(Event)>::new(callback);
+        target.add_event_listener_with_callback(kind, closure.as_ref().unchecked_ref())?;
+        self.listeners.push((target.clone(), kind, closure));
+        Ok(())
+    }
+}
+
+impl Drop for WebAdapter {
+    fn drop(&mut self) {
+        for (target, kind, closure) in self.listeners.drain(..) {
+            let _ =
+                target.remove_event_listener_with_callback(kind, closure.as_ref().unchecked_ref());
+        }
+    }
+}
+
+fn on_input_event(sender: &EventSender<WebInput>, held: &mut AHashSet<WebInput>, event: &Event) {
+    let (input, pressed) = if let Some(key) = event.dyn_ref::<KeyboardEvent>() {
+        let pressed = event.type_() == "keydown";
+        if !pressed && key.key() == "Meta" {
+            release_all(sender, held);
+        }
+        (WebInput::Key(key.code()), pressed)
+    } else if let Some(pointer) = event.dyn_ref::<PointerEvent>() {
+        (
+            WebInput::Pointer(pointer.button()),
+            event.type_() == "pointerdown",
+        )
+    } else {
+        return;
+    };
+
+    if pressed {
+        held.insert(input.clone());
+    } else {
+        held.remove(&input);
+    }
+    sender.on_input_event(input, pressed);
+}
+
+fn release_all(sender: &EventSender<WebInput>, held: &mut AHashSet<WebInput>) {
+    for input in held.drain() {
+        sender.on_input_up(input);
+    }
+}
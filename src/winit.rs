@@ -0,0 +1,436 @@
+//! Glue for feeding [`winit`] events into an [`EventInputHandler`].
+//!
+//! Enable this with the `winit` feature.
+//!
+//! ```rust,no_run
+//! # use puppetmaster::{EventInputHandler, winit::{WinitAdapter, WinitInput}};
+//! # use ::winit::{event::{DeviceEvent, WindowEvent}, keyboard::KeyCode};
+//! #[derive(Clone, Copy, Hash, Eq, PartialEq)]
+//! enum Control {
+//!     Jump,
+//!     Shoot,
+//! }
+//!
+//! let mut input_handler = EventInputHandler::new_with_controls(vec![
+//!     (WinitInput::Key(KeyCode::Space), Control::Jump),
+//!     (WinitInput::Mouse(::winit::event::MouseButton::Left), Control::Shoot),
+//! ]);
+//! let mut adapter = WinitAdapter::new();
+//!
+//! # let window_event: WindowEvent = WindowEvent::Focused(true);
+//! # let device_event: DeviceEvent = DeviceEvent::Added;
+//! // In `ApplicationHandler::window_event` ...
+//! adapter.window_event(&mut input_handler, &window_event);
+//!
+//! // In `ApplicationHandler::device_event` ...
+//! adapter.device_event(&device_event);
+//!
+//! // In `ApplicationHandler::about_to_wait`, before doing game logic ...
+//! input_handler.update();
+//! let (dx, dy) = adapter.take_mouse_delta();
+//! ```
+//!
+//! ## Several Keyboards
+//!
+//! Window events lump every keyboard together. To let two players share a machine with a keyboard each, use
+//! the raw key events from `ApplicationHandler::device_event` instead, which come with the ID of the keyboard
+//! they're from, and give each keyboard its own handler. Telling keyboards apart only works where the
+//! platform supports it, like Windows (Raw Input) and X11; elsewhere every keyboard has the same ID.
+//! Raw key events skip text entry and the window's focus, so keep using the window events for those.
+//!
+//! ```rust,no_run
+//! # use std::collections::HashMap;
+//! # use puppetmaster::{EventInputHandler, Lobby, winit::WinitInput};
+//! # use ::winit::event::{DeviceEvent, DeviceId};
+//! # #[derive(Clone, Copy, Hash, Eq, PartialEq)]
+//! # enum Control { Jump }
+//! # fn new_handler() -> EventInputHandler<WinitInput, Control> { EventInputHandler::new() }
+//! let mut keyboards: HashMap<DeviceId, EventInputHandler<WinitInput, Control>> = HashMap::new();
+//!
+//! # let device_id = DeviceId::dummy();
+//! # let event: DeviceEvent = DeviceEvent::Added;
+//! // In `ApplicationHandler::device_event` ...
+//! if let Some((input, pressed)) = WinitInput::from_device_event(&event) {
+//!     let handler = keyboards.entry(device_id).or_insert_with(new_handler);
+//!     handler.on_input_event(input, pressed);
+//! }
+//! ```
+//!
+//! A [`Lobby`](crate::Lobby) keyed by `DeviceId` can then hand out player slots to the keyboards.
+
+use std::hash::Hash;
+
+use ::winit::{
+    event::{
+        DeviceEvent, ElementState, Ime, MouseButton, MouseScrollDelta, RawKeyEvent, WindowEvent,
+    },
+    keyboard::{Key, KeyCode, NamedKey, PhysicalKey},
+    window::WindowId,
+};
+
+use crate::{BindingMirror, EventInputHandler, ImeEvent, TextEdit};
+
+/// An input winit can report.
+///
+/// Keys are physical keys, so bindings stay in the same place regardless of the keyboard layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WinitInput {
+    /// A key on the keyboard.
+    Key(KeyCode),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// One tick of the mouse wheel upwards.
+    WheelUp,
+    /// One tick of the mouse wheel downwards.
+    WheelDown,
+    /// One tick of the mouse wheel to the left.
+    WheelLeft,
+    /// One tick of the mouse wheel to the right.
+    WheelRight,
+}
+
+impl WinitInput {
+    /// Convert a button-like window event into an input, and whether it was pressed (`true`) or released (`false`).
+    ///
+    /// Mouse wheel ticks are reported as a press; they're released right away by [`WinitAdapter`].
+    /// Returns `None` for events that aren't button-like.
+    ///
+    /// ```rust
+    /// # use puppetmaster::winit::WinitInput;
+    /// # use ::winit::{dpi::PhysicalPosition, event::*};
+    /// let click = WindowEvent::MouseInput {
+    ///     device_id: DeviceId::dummy(),
+    ///     state: ElementState::Released,
+    ///     button: MouseButton::Right,
+    /// };
+    /// assert_eq!(
+    ///     WinitInput::from_window_event(&click),
+    ///     Some((WinitInput::Mouse(MouseButton::Right), false))
+    /// );
+    ///
+    /// let wheel = WindowEvent::MouseWheel {
+    ///     device_id: DeviceId::dummy(),
+    ///     delta: MouseScrollDelta::LineDelta(0.0, -1.0),
+    ///     phase: TouchPhase::Moved,
+    /// };
+    /// assert_eq!(WinitInput::from_window_event(&wheel), Some((WinitInput::WheelDown, true)));
+    ///
+    /// // Trackpad scrolling doesn't come in ticks.
+    /// let trackpad = WindowEvent::MouseWheel {
+    ///     device_id: DeviceId::dummy(),
+    ///     delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 3.0)),
+    ///     phase: TouchPhase::Moved,
+    /// };
+    /// assert_eq!(WinitInput::from_window_event(&trackpad), None);
+    /// assert_eq!(WinitInput::from_window_event(&WindowEvent::Focused(true)), None);
+    /// ```
+    pub fn from_window_event(event: &WindowEvent) -> Option<(Self, bool)> {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => match event.physical_key {
+                PhysicalKey::Code(code) => {
+                    Some((WinitInput::Key(code), event.state == ElementState::Pressed))
+                }
+                PhysicalKey::Unidentified(_) => None,
+            },
+            WindowEvent::MouseInput { state, button, .. } => {
+                Some((WinitInput::Mouse(*button), *state == ElementState::Pressed))
+            }
+            // Trackpads send pixel deltas, which are far too fine-grained to count as ticks
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(x, y),
+                ..
+            } => {
+                let input = if *y > 0.0 {
+                    WinitInput::WheelUp
+                } else if *y < 0.0 {
+                    WinitInput::WheelDown
+                } else if *x > 0.0 {
+                    WinitInput::WheelRight
+                } else if *x < 0.0 {
+                    WinitInput::WheelLeft
+                } else {
+                    return None;
+                };
+                Some((input, true))
+            }
+            _ => None,
+        }
+    }
+
+    /// Convert a raw key event from `ApplicationHandler::device_event` into an input, and whether it was
+    /// pressed (`true`) or released (`false`).
+    ///
+    /// These come with the ID of the keyboard they're from, so they can be used to tell keyboards apart;
+    /// see the [module docs](self#several-keyboards). The OS repeats raw key presses while a key is held,
+    /// but the handler ignores presses of inputs that are already down. Returns `None` for anything else.
+    ///
+    /// ```rust
+    /// # use puppetmaster::winit::WinitInput;
+    /// # use ::winit::{event::{DeviceEvent, ElementState, RawKeyEvent}, keyboard::*};
+    /// let key = |physical_key, state| DeviceEvent::Key(RawKeyEvent { physical_key, state });
+    ///
+    /// let press = key(PhysicalKey::Code(KeyCode::KeyW), ElementState::Pressed);
+    /// assert_eq!(WinitInput::from_device_event(&press), Some((WinitInput::Key(KeyCode::KeyW), true)));
+    /// let release = key(PhysicalKey::Code(KeyCode::KeyW), ElementState::Released);
+    /// assert_eq!(WinitInput::from_device_event(&release), Some((WinitInput::Key(KeyCode::KeyW), false)));
+    ///
+    /// // Keys winit can't name, and everything that isn't a key, are skipped.
+    /// let unknown = key(PhysicalKey::Unidentified(NativeKeyCode::Xkb(999)), ElementState::Pressed);
+    /// assert_eq!(WinitInput::from_device_event(&unknown), None);
+    /// assert_eq!(WinitInput::from_device_event(&DeviceEvent::MouseMotion { delta: (1.0, 0.0) }), None);
+    /// ```
+    pub fn from_device_event(event: &DeviceEvent) -> Option<(Self, bool)> {
+        match event {
+            DeviceEvent::Key(RawKeyEvent {
+                physical_key: PhysicalKey::Code(code),
+                state,
+            }) => Some((WinitInput::Key(*code), *state == ElementState::Pressed)),
+            _ => None,
+        }
+    }
+
+    /// A table for left-handed mode on a QWERTY-shaped keyboard.
+    ///
+    /// The left side of the keyboard moves over to the right hand, so WASD becomes IJKL with everything
+    /// around it moving along too. The number row, shift, control and alt keys move to match, and the left
+    /// and right mouse buttons swap.
+    pub fn left_handed() -> BindingMirror<Self> {
+        use KeyCode::*;
+        let keys = [
+            (Digit1, Digit7),
+            (Digit2, Digit8),
+            (Digit3, Digit9),
+            (Digit4, Digit0),
+            (Digit5, Minus),
+            (KeyQ, KeyU),
+            (KeyW, KeyI),
+            (KeyE, KeyO),
+            (KeyR, KeyP),
+            (KeyT, BracketLeft),
+            (KeyA, KeyJ),
+            (KeyS, KeyK),
+            (KeyD, KeyL),
+            (KeyF, Semicolon),
+            (KeyG, Quote),
+            (KeyZ, KeyM),
+            (KeyX, Comma),
+            (KeyC, Period),
+            (KeyV, Slash),
+            (ShiftLeft, ShiftRight),
+            (ControlLeft, ControlRight),
+            (AltLeft, AltRight),
+        ];
+        BindingMirror::new(
+            keys.into_iter()
+                .map(|(a, b)| (WinitInput::Key(a), WinitInput::Key(b)))
+                .chain([(
+                    WinitInput::Mouse(MouseButton::Left),
+                    WinitInput::Mouse(MouseButton::Right),
+                )]),
+        )
+    }
+}
+
+/// Feeds winit events into an [`EventInputHandler`], and keeps track of the mouse movement and scrolling
+/// that doesn't fit into buttons.
+#[derive(Clone, Debug, Default)]
+pub struct WinitAdapter {
+    /// Raw mouse motion since the last time it was taken
+    mouse_delta: (f64, f64),
+    /// Scrolling since the last time it was taken, in lines
+    scroll_delta: (f32, f32),
+}
+
+impl WinitAdapter {
+    /// Create a new `WinitAdapter`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a window event into the handler.
+    ///
+    /// This handles keyboard, mouse button, and mouse wheel events. Cursor movement sets the handler's
+    /// [pointer position](EventInputHandler::set_pointer), and scrolling is passed on to
+    /// [`add_scroll`](EventInputHandler::add_scroll). Typing is passed on to
+    /// [`on_text`](EventInputHandler::on_text), [`on_text_edit`](EventInputHandler::on_text_edit), and
+    /// [`on_ime`](EventInputHandler::on_ime) for text entry (winit only sends IME events once you've called
+    /// `Window::set_ime_allowed`). When the window loses focus, all inputs are released
+    /// (winit won't report the key-up events for keys released while unfocused).
+    ///
+    /// ```rust
+    /// # use puppetmaster::{EventInputHandler, winit::{WinitAdapter, WinitInput}};
+    /// # use ::winit::{dpi::PhysicalPosition, event::*};
+    /// let mut input_handler = EventInputHandler::new_with_controls(vec![
+    ///     (WinitInput::Mouse(MouseButton::Left), "shoot"),
+    ///     (WinitInput::WheelUp, "next_weapon"),
+    /// ]);
+    /// let mut adapter = WinitAdapter::new();
+    ///
+    /// adapter.window_event(&mut input_handler, &WindowEvent::MouseInput {
+    ///     device_id: DeviceId::dummy(),
+    ///     state: ElementState::Pressed,
+    ///     button: MouseButton::Left,
+    /// });
+    /// adapter.window_event(&mut input_handler, &WindowEvent::MouseWheel {
+    ///     device_id: DeviceId::dummy(),
+    ///     delta: MouseScrollDelta::LineDelta(0.0, 1.0),
+    ///     phase: TouchPhase::Moved,
+    /// });
+    /// adapter.window_event(&mut input_handler, &WindowEvent::CursorMoved {
+    ///     device_id: DeviceId::dummy(),
+    ///     position: PhysicalPosition::new(10.0, 20.0),
+    /// });
+    /// input_handler.update();
+    /// assert!(input_handler.clicked("shoot"));
+    /// assert!(input_handler.clicked("next_weapon"));
+    /// assert_eq!(input_handler.pointer_position(), Some((10.0, 20.0)));
+    /// assert_eq!(adapter.take_scroll_delta(), (0.0, 1.0));
+    ///
+    /// // The wheel tick is let go by itself, and losing focus lets go of everything else.
+    /// adapter.window_event(&mut input_handler, &WindowEvent::Focused(false));
+    /// input_handler.update();
+    /// assert!(input_handler.up("shoot") && input_handler.up("next_weapon"));
+    /// ```
+    pub fn window_event<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<WinitInput, C>,
+        event: &WindowEvent,
+    ) {
+        match event {
+            WindowEvent::Focused(false) => handler.on_focus_lost(),
+            WindowEvent::Focused(true) => handler.on_focus_gained(),
+            WindowEvent::CursorMoved { position, .. } => {
+                handler.set_pointer(Some((position.x as f32, position.y as f32)))
+            }
+            WindowEvent::CursorLeft { .. } => handler.set_pointer(None),
+            WindowEvent::Ime(ime) => match ime {
+                Ime::Preedit(text, cursor) => handler.on_ime(ImeEvent::Preedit {
+                    text: text.clone(),
+                    cursor: *cursor,
+                }),
+                Ime::Commit(text) => handler.on_ime(ImeEvent::Commit(text.clone())),
+                Ime::Disabled => handler.on_ime(ImeEvent::Cancel),
+                Ime::Enabled => {}
+            },
+            WindowEvent::KeyboardInput { event: key, .. } => {
+                if key.state == ElementState::Pressed {
+                    if let Some(text) = &key.text {
+                        handler.on_text(text);
+                    }
+                    if let Key::Named(named) = key.logical_key {
+                        if let Some(edit) = text_edit(named) {
+                            handler.on_text_edit(edit);
+                        }
+                    }
+                }
+                if let Some((input, pressed)) = WinitInput::from_window_event(event) {
+                    handler.on_input_event(input, pressed);
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let (x, y) = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => (*x, *y),
+                    // Winit doesn't know how many pixels a line is either, so just guess
+                    MouseScrollDelta::PixelDelta(pos) => (
+                        pos.x as f32 / PIXELS_PER_LINE,
+                        pos.y as f32 / PIXELS_PER_LINE,
+                    ),
+                };
+                self.scroll_delta.0 += x;
+                self.scroll_delta.1 += y;
+                handler.add_scroll((x, y));
+                if let Some((input, _)) = WinitInput::from_window_event(event) {
+                    handler.on_input_down(input);
+                    handler.on_input_up(input);
+                }
+            }
+            _ => {
+                if let Some((input, pressed)) = WinitInput::from_window_event(event) {
+                    handler.on_input_event(input, pressed);
+                }
+            }
+        }
+    }
+
+    /// Feed an event for one of several windows into the handler, for games and editors with more than one
+    /// window.
+    ///
+    /// This works like [`window_event`](Self::window_event), but keeps track of which window has
+    /// [focus](EventInputHandler::set_focused_window). Presses, pointer movement, scrolling, and typing in any
+    /// other window are ignored.
+    pub fn window_event_for<C: Hash + Eq + Clone>(
+        &mut self,
+        handler: &mut EventInputHandler<WinitInput, C>,
+        window_id: WindowId,
+        event: &WindowEvent,
+    ) {
+        let window = u64::from(window_id);
+        match event {
+            WindowEvent::Focused(true) => {
+                handler.set_focused_window(Some(window));
+                handler.on_focus_gained();
+            }
+            WindowEvent::Focused(false) => {
+                if handler.focused_window() == Some(window) {
+                    handler.set_focused_window(None);
+                    handler.on_focus_lost();
+                }
+            }
+            _ if handler
+                .focused_window()
+                .is_some_and(|focused| focused != window) =>
+            {
+                // Let releases through so nothing gets stuck down
+                if let Some((input, false)) = WinitInput::from_window_event(event) {
+                    handler.on_window_input_event(window, input, false);
+                }
+            }
+            _ => self.window_event(handler, event),
+        }
+    }
+
+    /// Feed a device event in. This tracks raw mouse motion, which is what you want for mouselook.
+    ///
+    /// ```rust
+    /// # use puppetmaster::winit::WinitAdapter;
+    /// # use ::winit::event::DeviceEvent;
+    /// let mut adapter = WinitAdapter::new();
+    /// adapter.device_event(&DeviceEvent::MouseMotion { delta: (3.0, -1.0) });
+    /// adapter.device_event(&DeviceEvent::MouseMotion { delta: (2.0, 0.5) });
+    /// assert_eq!(adapter.take_mouse_delta(), (5.0, -0.5));
+    /// assert_eq!(adapter.take_mouse_delta(), (0.0, 0.0));
+    /// ```
+    pub fn device_event(&mut self, event: &DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta } = event {
+            self.mouse_delta.0 += delta.0;
+            self.mouse_delta.1 += delta.1;
+        }
+    }
+
+    /// Return the raw mouse motion since the last time this was called, and reset it.
+    pub fn take_mouse_delta(&mut self) -> (f64, f64) {
+        std::mem::take(&mut self.mouse_delta)
+    }
+
+    /// Return how far the mouse wheel was scrolled since the last time this was called, in lines, and reset it.
+    ///
+    /// Positive `y` is upwards and positive `x` is to the right.
+    pub fn take_scroll_delta(&mut self) -> (f32, f32) {
+        std::mem::take(&mut self.scroll_delta)
+    }
+}
+
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// The text-entry edit a key makes, if it's an editing key.
+fn text_edit(key: NamedKey) -> Option<TextEdit> {
+    match key {
+        NamedKey::Backspace => Some(TextEdit::Backspace),
+        NamedKey::Delete => Some(TextEdit::Delete),
+        NamedKey::ArrowLeft => Some(TextEdit::Left),
+        NamedKey::ArrowRight => Some(TextEdit::Right),
+        NamedKey::Home => Some(TextEdit::Home),
+        NamedKey::End => Some(TextEdit::End),
+        _ => None,
+    }
+}
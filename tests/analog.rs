@@ -0,0 +1,125 @@
+use puppetmaster::{AxisOverride, PollingInputHandler};
+
+fn stick(value: f32) -> PollingInputHandler<&'static str, &'static str> {
+    let mut input_handler = PollingInputHandler::new();
+    input_handler.axis_config_mut().insert("stick_x", "steer");
+    input_handler.set_axis_inputs(vec![("stick_x", value)]);
+    input_handler.update(vec![]);
+    input_handler
+}
+
+#[test]
+fn sensitivity_and_inversion() {
+    let mut input_handler = stick(0.5);
+    input_handler.set_axis_deadzone("steer", 0.25);
+    input_handler.set_axis_sensitivity("steer", 2.0);
+    // (0.5 - 0.25) / 0.75, doubled.
+    assert!((input_handler.value("steer") - 2.0 / 3.0).abs() < 1e-6);
+
+    // Sensitivity can't push the value past the ends.
+    input_handler.set_axis_sensitivity("steer", 10.0);
+    assert_eq!(input_handler.value("steer"), 1.0);
+
+    input_handler.set_axis_inverted("steer", true);
+    assert_eq!(input_handler.value("steer"), -1.0);
+
+    // Negative sensitivity isn't a second way to invert.
+    input_handler.set_axis_sensitivity("steer", -1.0);
+    assert_eq!(input_handler.axis_sensitivity("steer"), 0.0);
+    assert_eq!(input_handler.value("steer"), 0.0);
+}
+
+#[test]
+fn override_only_replaces_what_it_sets() {
+    let mut input_handler = stick(0.5);
+    input_handler.set_axis_sensitivity("steer", 2.0);
+    input_handler.set_axis_inverted("steer", true);
+    input_handler.set_axis_override(
+        "menu",
+        "steer",
+        Some(AxisOverride {
+            deadzone: Some(0.75),
+            inverted: Some(false),
+            ..AxisOverride::default()
+        }),
+    );
+    assert_eq!(input_handler.value("steer"), -1.0);
+
+    input_handler.set_axis_context(Some("menu"));
+    assert_eq!(input_handler.value("steer"), 0.0);
+    input_handler.set_axis_inputs(vec![("stick_x", 0.875)]);
+    input_handler.update(vec![]);
+    // Past the override's deadzone, still doubled by the control's own sensitivity, and not flipped.
+    assert_eq!(input_handler.value("steer"), 1.0);
+    assert_eq!(input_handler.axis_deadzone("steer"), 0.0);
+    assert!(input_handler.axis_inverted("steer"));
+
+    // A context with no overrides for the control changes nothing.
+    input_handler.set_axis_context(Some("driving"));
+    assert_eq!(input_handler.value("steer"), -1.0);
+
+    input_handler.set_axis_context(Some("menu"));
+    input_handler.set_axis_override("menu", "steer", None);
+    assert_eq!(input_handler.axis_override("menu", "steer"), None);
+    assert_eq!(input_handler.value("steer"), -1.0);
+}
+
+#[test]
+fn merged_snapshot_takes_the_furthest_value() {
+    let mut player = stick(0.5);
+    let mut copilot = stick(-0.75);
+    copilot.axis_config_mut().insert("trigger", "brake");
+    copilot.set_axis_inputs(vec![("stick_x", -0.75), ("trigger", 0.25)]);
+    copilot.update(vec![]);
+
+    let merged = player.snapshot().merge(&copilot.snapshot());
+    assert_eq!(merged.value("steer"), -0.75);
+    // Axis controls only one of them has come through as they are.
+    assert_eq!(merged.value("brake"), 0.25);
+    assert_eq!(merged.value("unbound"), 0.0);
+
+    // On a tie, the snapshot being merged into wins.
+    player.set_axis_inputs(vec![("stick_x", 0.75)]);
+    player.update(vec![]);
+    assert_eq!(
+        player.snapshot().merge(&copilot.snapshot()).value("steer"),
+        0.75
+    );
+    assert_eq!(
+        copilot.snapshot().merge(&player.snapshot()).value("steer"),
+        -0.75
+    );
+}
+
+#[test]
+fn nan_settings_count_as_zero() {
+    let mut input_handler = stick(0.5);
+    input_handler.set_axis_deadzone("steer", f32::NAN);
+    assert_eq!(input_handler.axis_deadzone("steer"), 0.0);
+    assert_eq!(input_handler.value("steer"), 0.5);
+
+    input_handler.set_axis_override(
+        "menu",
+        "steer",
+        Some(AxisOverride {
+            deadzone: Some(f32::NAN),
+            sensitivity: Some(f32::NAN),
+            inverted: None,
+        }),
+    );
+    let settings = input_handler.axis_override("menu", "steer").unwrap();
+    assert_eq!(settings.deadzone, Some(0.0));
+    assert_eq!(settings.sensitivity, Some(0.0));
+    input_handler.set_axis_context(Some("menu"));
+    assert_eq!(input_handler.value("steer"), 0.0);
+
+    input_handler.set_axis_override(
+        "menu",
+        "steer",
+        Some(AxisOverride {
+            deadzone: Some(f32::NAN),
+            ..AxisOverride::default()
+        }),
+    );
+    assert_eq!(input_handler.value("steer"), 0.5);
+}
@@ -0,0 +1,28 @@
+use puppetmaster::EventInputHandler;
+
+#[test]
+fn event_flood_drains_at_the_limit() {
+    const MAX: usize = 64;
+    const INPUTS: u32 = 10_000;
+    let mut input_handler = EventInputHandler::new_with_controls((0..INPUTS).map(|i| (i, i % 4)));
+    input_handler.set_max_events_per_update(Some(MAX));
+    for i in 0..INPUTS {
+        input_handler.on_input_down(i);
+    }
+    for i in 0..INPUTS {
+        input_handler.on_input_up(i);
+    }
+    let total = 2 * INPUTS as usize;
+    assert_eq!(input_handler.queued_events(), total);
+
+    let mut updates = 0;
+    while input_handler.queued_events() > 0 {
+        let before = input_handler.queued_events();
+        input_handler.update();
+        updates += 1;
+        // Every update works through exactly as many events as it's allowed to, and no more.
+        assert_eq!(before - input_handler.queued_events(), MAX.min(before));
+    }
+    assert_eq!(updates, total.div_ceil(MAX));
+    assert_eq!(input_handler.all_pressed().count(), 0);
+}
@@ -0,0 +1,66 @@
+//! Check that include/puppetmaster.h declares exactly the functions src/ffi.rs exports.
+
+const SOURCE: &str = include_str!("../src/ffi.rs");
+const HEADER: &str = include_str!("../include/puppetmaster.h");
+
+/// The C spelling of a Rust type used in the FFI.
+fn c_type(rust: &str) -> String {
+    match rust {
+        "u32" => "uint32_t".to_owned(),
+        "bool" => "bool".to_owned(),
+        "*mut PuppetmasterHandler" => "PuppetmasterHandler *".to_owned(),
+        "*const PuppetmasterHandler" => "const PuppetmasterHandler *".to_owned(),
+        _ => panic!("no C type for `{rust}`; add it here and to the header"),
+    }
+}
+
+/// The C prototype of every `extern "C"` function in the source.
+fn prototypes_from_source() -> Vec<String> {
+    let mut prototypes = Vec::new();
+    const EXTERN: &str = "extern \"C\" fn ";
+    for (start, _) in SOURCE.match_indices(EXTERN) {
+        let rest = &SOURCE[start + EXTERN.len()..];
+        let signature = &rest[..rest.find('{').unwrap()];
+        let signature = signature.split_whitespace().collect::<Vec<_>>().join(" ");
+        let (name, rest) = signature.split_once('(').unwrap();
+        let (args, ret) = rest.rsplit_once(')').unwrap();
+        let args = args
+            .split(',')
+            .map(str::trim)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| {
+                let (name, ty) = arg.split_once(':').unwrap();
+                let ty = c_type(ty.trim());
+                if ty.ends_with('*') {
+                    format!("{ty}{}", name.trim())
+                } else {
+                    format!("{ty} {}", name.trim())
+                }
+            })
+            .collect::<Vec<_>>();
+        let args = if args.is_empty() {
+            "void".to_owned()
+        } else {
+            args.join(", ")
+        };
+        let ret = match ret.trim().strip_prefix("->") {
+            Some(ret) => c_type(ret.trim()),
+            None => "void".to_owned(),
+        };
+        let sep = if ret.ends_with('*') { "" } else { " " };
+        prototypes.push(format!("{ret}{sep}{name}({args});"));
+    }
+    prototypes
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let expected = prototypes_from_source();
+    assert!(!expected.is_empty());
+    let declared = HEADER
+        .lines()
+        .filter(|line| line.contains("puppetmaster_") && line.ends_with(");"))
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    assert_eq!(declared, expected);
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "mlua")]
+
+use mlua::Lua;
+use puppetmaster::{mlua::LuaBridge, DualAxis, PollingInputHandler};
+
+#[test]
+fn scripts_read_axes() {
+    let mut input_handler = PollingInputHandler::new();
+    input_handler.axis_config_mut().insert("stick_x", "walk_x");
+    input_handler.axis_config_mut().insert("stick_y", "walk_y");
+    input_handler.set_dual_axis(
+        "walk",
+        Some(DualAxis {
+            x: "walk_x",
+            y: "walk_y",
+            normalize: false,
+        }),
+    );
+    input_handler.set_axis_inputs(vec![("stick_x", 0.5), ("stick_y", -0.25)]);
+    input_handler.update(vec![]);
+
+    let bridge = LuaBridge::new(vec![
+        ("walk_x", "walk_x"),
+        ("walk_y", "walk_y"),
+        ("walk", "walk"),
+    ]);
+    let lua = Lua::new();
+    bridge
+        .publish(&lua, "controls", input_handler.snapshot())
+        .unwrap();
+
+    let x: f32 = lua
+        .load(r#"return controls:value("walk_x")"#)
+        .eval()
+        .unwrap();
+    assert_eq!(x, 0.5);
+    let walk: (f32, f32) = lua
+        .load(r#"return controls:vector("walk")"#)
+        .eval()
+        .unwrap();
+    assert_eq!(walk, (0.5, -0.25));
+    let unknown = lua.load(r#"return controls:value("jump")"#).eval::<f32>();
+    assert!(unknown.is_err());
+}
@@ -0,0 +1,217 @@
+use puppetmaster::{
+    Directions, Facing, MotionInput, MotionLeniency, MotionMatcher, MotionStep, PollingInputHandler,
+};
+
+fn fireball() -> MotionMatcher<&'static str, &'static str> {
+    let mut motions = MotionMatcher::new();
+    motions.add_motion(
+        "fireball",
+        [
+            MotionStep::new(["down"], 0),
+            MotionStep::new(["down", "forward"], 8),
+            MotionStep::new(["forward", "punch"], 8),
+        ],
+    );
+    motions
+}
+
+fn controls() -> Vec<(char, &'static str)> {
+    vec![('s', "down"), ('d', "forward"), ('j', "punch")]
+}
+
+#[test]
+fn frame_going_backwards_starts_over() {
+    let mut input_handler = PollingInputHandler::new_with_controls(controls());
+    let mut motions = fireball();
+    input_handler.update(vec![]);
+    let saved = input_handler.save_state();
+
+    for held in [vec!['s'], vec!['s', 'd']] {
+        input_handler.update(held);
+        assert!(motions.update(&input_handler).is_empty());
+    }
+
+    assert!(input_handler.restore_state(saved));
+    // The steps from before the restore are forgotten, so finishing the motion doesn't count.
+    input_handler.update(vec!['d', 'j']);
+    assert!(motions.update(&input_handler).is_empty());
+
+    for held in [vec![], vec!['s'], vec!['s', 'd'], vec!['d', 'j']] {
+        input_handler.update(held);
+        motions.update(&input_handler);
+    }
+    assert_eq!(motions.matched(), ["fireball"]);
+}
+
+#[test]
+fn fresh_handler_starts_over() {
+    let mut input_handler = PollingInputHandler::new_with_controls(controls());
+    let mut motions = fireball();
+    for _ in 0..50 {
+        input_handler.update(vec![]);
+        motions.update(&input_handler);
+    }
+
+    // A new handler starts counting from 0 again.
+    let mut input_handler = PollingInputHandler::new_with_controls(controls());
+    for held in [vec!['s'], vec!['s', 'd'], vec!['d', 'j']] {
+        input_handler.update(held);
+        motions.update(&input_handler);
+    }
+    assert_eq!(motions.matched(), ["fireball"]);
+}
+
+fn matches(motions: &mut MotionMatcher<&'static str, &'static str>, frames: &[&[char]]) -> bool {
+    let mut input_handler = PollingInputHandler::new_with_controls(vec![
+        ('w', "up"),
+        ('s', "down"),
+        ('a', "back"),
+        ('d', "forward"),
+        ('j', "punch"),
+    ]);
+    motions.reset();
+    for held in frames {
+        input_handler.update(held.to_vec());
+        motions.update(&input_handler);
+    }
+    !motions.matched().is_empty()
+}
+
+fn quarter_circle(leniency: MotionLeniency) -> MotionMatcher<&'static str, &'static str> {
+    let mut motions = MotionMatcher::new();
+    motions.set_directions(Some(Directions {
+        up: "up",
+        down: "down",
+        left: "back",
+        right: "forward",
+    }));
+    motions.add_motion_with_leniency(
+        "fireball",
+        [
+            MotionStep::new(["down"], 0),
+            MotionStep::new(["down", "forward"], 4),
+            MotionStep::new(["forward", "punch"], 4),
+        ],
+        leniency,
+    );
+    motions
+}
+
+#[test]
+fn leniency_max_gap() {
+    let slow: &[&[char]] = &[
+        &['s'],
+        &['s'],
+        &['s'],
+        &['s'],
+        &['s'],
+        &['s', 'd'],
+        &['d', 'j'],
+    ];
+    assert!(!matches(
+        &mut quarter_circle(MotionLeniency::default()),
+        slow
+    ));
+
+    let mut loose = quarter_circle(MotionLeniency {
+        max_gap: Some(10),
+        ..MotionLeniency::default()
+    });
+    assert!(matches(&mut loose, slow));
+
+    let quick: &[&[char]] = &[&['s'], &['s', 'd'], &['d'], &['d'], &['d', 'j']];
+    assert!(matches(
+        &mut quarter_circle(MotionLeniency::default()),
+        quick
+    ));
+    let mut tight = quarter_circle(MotionLeniency {
+        max_gap: Some(2),
+        ..MotionLeniency::default()
+    });
+    assert!(!matches(&mut tight, quick));
+}
+
+#[test]
+fn leniency_max_dirty() {
+    // Back slips in between Down and Down-Forward.
+    let dirty: &[&[char]] = &[&['s'], &['s', 'a'], &['s'], &['s', 'd'], &['d', 'j']];
+    assert!(matches(
+        &mut quarter_circle(MotionLeniency::default()),
+        dirty
+    ));
+
+    let mut clean = quarter_circle(MotionLeniency {
+        max_dirty: Some(0),
+        ..MotionLeniency::default()
+    });
+    assert!(!matches(&mut clean, dirty));
+    assert!(matches(&mut clean, &[&['s'], &['s', 'd'], &['d', 'j']]));
+
+    let mut sloppy = quarter_circle(MotionLeniency {
+        max_dirty: Some(1),
+        ..MotionLeniency::default()
+    });
+    assert!(matches(&mut sloppy, dirty));
+}
+
+#[test]
+fn leniency_diagonals_count_as_cardinals() {
+    let mut motions = MotionMatcher::new();
+    motions.set_directions(Some(Directions {
+        up: "up",
+        down: "down",
+        left: "back",
+        right: "forward",
+    }));
+    let steps = [
+        MotionStep::new(["down"], 0),
+        MotionStep::new(["forward", "punch"], 4),
+    ];
+    motions.add_motion("strict", steps.clone());
+    motions.add_motion_with_leniency(
+        "lenient",
+        steps,
+        MotionLeniency {
+            diagonals_count_as_cardinals: true,
+            ..MotionLeniency::default()
+        },
+    );
+
+    // Rolling from Down-Forward straight into Forward + Punch, without a clean Down.
+    let rolled: &[&[char]] = &[&['s', 'd'], &['d', 'j']];
+    assert!(matches(&mut motions, rolled));
+    assert_eq!(motions.matched(), ["lenient"]);
+
+    // Without knowing the directions, there are no diagonals.
+    motions.set_directions(None);
+    assert!(!matches(&mut motions, rolled));
+}
+
+#[test]
+fn facing_turns_back_around() {
+    let mut motions = MotionMatcher::new();
+    motions.add_motion(
+        "retreat",
+        [
+            MotionStep::new([MotionInput::Back], 0),
+            MotionStep::new([MotionInput::Back, MotionInput::Control("punch")], 4),
+        ],
+    );
+    let away_from_right: &[&[char]] = &[&['a'], &['a', 'j']];
+    // Without the directions, Back is never held.
+    assert!(!matches(&mut motions, away_from_right));
+
+    motions.set_directions(Some(Directions {
+        up: "up",
+        down: "down",
+        left: "back",
+        right: "forward",
+    }));
+    assert_eq!(motions.facing(), Facing::Right);
+    assert!(matches(&mut motions, away_from_right));
+    assert!(!matches(&mut motions, &[&['d'], &['d', 'j']]));
+
+    motions.set_facing(Facing::Left);
+    assert!(!matches(&mut motions, away_from_right));
+    assert!(matches(&mut motions, &[&['d'], &['d', 'j']]));
+}
@@ -0,0 +1,172 @@
+use puppetmaster::{
+    AxisConflict, AxisOverride, Chord, Debounce, DualAxis, EventInputHandler, LongPress,
+    PollingInputHandler, QueryInputHandler, ReleaseTimeout, TimingSettings, VirtualAxis,
+};
+
+#[test]
+fn restore_older_state_after_release() {
+    let mut input_handler = EventInputHandler::new_with_controls(vec![("a", "jump")]);
+    input_handler.update();
+    let saved = input_handler.save_state();
+
+    input_handler.on_input_down("a");
+    input_handler.update();
+    input_handler.on_input_up("a");
+    input_handler.update();
+    assert!(input_handler.just_released("jump"));
+
+    assert!(input_handler.restore_state(saved));
+    assert_eq!(input_handler.release_time("jump"), 0);
+    assert_eq!(input_handler.last_released_frame("jump"), None);
+    input_handler.update();
+    assert_eq!(input_handler.frame(), 2);
+    assert!(input_handler.up("jump"));
+}
+
+#[test]
+fn restore_older_state_with_release_timeout() {
+    let mut input_handler = EventInputHandler::new_with_controls(vec![("a", "jump")]);
+    input_handler.set_release_timeout(Some(ReleaseTimeout::Frames(2)));
+    input_handler.on_input_down("a");
+    input_handler.update();
+    let saved = input_handler.save_state();
+
+    for _ in 0..5 {
+        input_handler.on_input_down("a");
+        input_handler.update();
+    }
+    assert!(input_handler.down("jump"));
+
+    assert!(input_handler.restore_state(saved));
+    // The input isn't reported again, so it times out as if the later frames never happened.
+    input_handler.update();
+    assert!(input_handler.down("jump"));
+    input_handler.update();
+    assert!(input_handler.up("jump"));
+}
+
+#[test]
+fn restore_older_state_with_lockout_and_follow_up() {
+    let mut input_handler = PollingInputHandler::new_with_controls(vec![("a", "jump")]);
+    input_handler.set_lockout("jump", Some(100));
+    input_handler.update(vec![]);
+    let saved = input_handler.save_state();
+
+    input_handler.open_follow_up("combo", 100);
+    for _ in 0..10 {
+        input_handler.update(vec![]);
+    }
+    input_handler.update(vec!["a"]);
+    input_handler.update(vec![]);
+    assert!(input_handler.followed_up("combo", "jump"));
+
+    assert!(input_handler.restore_state(saved));
+    assert_eq!(input_handler.last_input(), None);
+    input_handler.update(vec!["a"]);
+    // The lockout from after the save is gone, so the press goes through.
+    assert!(input_handler.clicked("jump"));
+    assert!(!input_handler.followed_up("combo", "jump"));
+    assert_eq!(input_handler.last_clicked_frame("jump"), Some(2));
+}
+
+#[test]
+fn restore_keeps_control_settings_and_axes() {
+    let mut input_handler =
+        QueryInputHandler::new_with_controls(vec![("a", "left"), ("d", "right")]);
+    input_handler.set_timing(TimingSettings {
+        debounce: Some(Debounce::Frames(5)),
+        ..TimingSettings::default()
+    });
+    input_handler.set_debounce("left", None);
+    input_handler.set_turbo("right", Some(4));
+    input_handler.set_tap_window("right", Some(30));
+    input_handler.set_hold_threshold("left", Some(12));
+    input_handler.set_sticky("left", true);
+    input_handler.set_lockout("right", Some(3));
+    let long_press = LongPress {
+        short: "step",
+        long: "run",
+        frames: 20,
+    };
+    input_handler.set_long_press("right", Some(long_press.clone()));
+    let chord = Chord {
+        controls: vec!["left", "right"],
+    };
+    input_handler.set_chord("turn", Some(chord.clone()));
+    input_handler.axis_config_mut().insert("stick_x", "walk_x");
+    input_handler.set_axis_deadzone("walk_x", 0.2);
+    input_handler.set_axis_sensitivity("walk_x", 2.0);
+    input_handler.set_axis_inverted("walk_y", true);
+    let axis_override = AxisOverride {
+        inverted: Some(true),
+        ..AxisOverride::default()
+    };
+    input_handler.set_axis_override("mirrored", "walk_x", Some(axis_override));
+    input_handler.set_axis_context(Some("mirrored"));
+    let virtual_axis = VirtualAxis {
+        negative: "left",
+        positive: "right",
+        conflict: AxisConflict::Newest,
+    };
+    input_handler.set_virtual_axis("walk_x", Some(virtual_axis.clone()));
+    let dual_axis = DualAxis {
+        x: "walk_x",
+        y: "walk_y",
+        normalize: true,
+    };
+    input_handler.set_dual_axis("walk", Some(dual_axis.clone()));
+    let saved = input_handler.save_state();
+
+    let mut restored = QueryInputHandler::new();
+    assert!(restored.restore_state(saved));
+    assert_eq!(restored.debounce("left"), None);
+    assert_eq!(restored.debounce("right"), Some(Debounce::Frames(5)));
+    assert_eq!(restored.turbo("right"), Some(4));
+    assert_eq!(restored.tap_window("right"), 30);
+    assert_eq!(restored.hold_threshold("left"), Some(12));
+    assert!(restored.is_sticky("left"));
+    assert_eq!(restored.lockout("right"), Some(3));
+    assert_eq!(restored.long_press("right"), Some(&long_press));
+    assert_eq!(restored.chord("turn"), Some(&chord));
+    assert_eq!(restored.axis_config().get("stick_x"), Some(&"walk_x"));
+    assert_eq!(restored.axis_deadzone("walk_x"), 0.2);
+    assert_eq!(restored.axis_sensitivity("walk_x"), 2.0);
+    assert!(restored.axis_inverted("walk_y"));
+    assert_eq!(
+        restored.axis_override("mirrored", "walk_x"),
+        Some(&axis_override)
+    );
+    assert_eq!(restored.axis_context(), Some(&"mirrored"));
+    assert_eq!(restored.virtual_axis("walk_x"), Some(&virtual_axis));
+    assert_eq!(restored.dual_axis("walk"), Some(&dual_axis));
+
+    restored.update(|input| *input == "d");
+    assert_eq!(restored.vector("walk"), (-1.0, 0.0));
+}
+
+#[test]
+fn restore_keeps_toggles_switched_on() {
+    let mut input_handler =
+        PollingInputHandler::new_with_controls(vec![("c", "crouch"), ("v", "walk")]);
+    input_handler.set_toggle("crouch", true);
+    input_handler.set_toggle("walk", true);
+    input_handler.update(vec!["c", "v"]);
+    input_handler.update(vec!["v"]);
+    let saved = input_handler.save_state();
+
+    let mut input_handler = PollingInputHandler::new();
+    assert!(input_handler.restore_state(saved));
+    assert!(input_handler.is_toggle("crouch"));
+    input_handler.update(vec!["v"]);
+    assert!(input_handler.down("crouch"));
+    assert!(input_handler.down("walk"));
+    input_handler.update(vec![]);
+    assert!(input_handler.down("crouch"));
+    assert!(input_handler.down("walk"));
+
+    // The next press switches it off, even though it was down when it was saved.
+    input_handler.update(vec!["c"]);
+    assert!(input_handler.up("crouch"));
+    input_handler.update(vec!["v"]);
+    assert!(input_handler.up("walk"));
+}
@@ -0,0 +1,54 @@
+use puppetmaster::{DualAxis, PollingInputHandler};
+
+#[test]
+fn snapshot_knows_what_was_released() {
+    let mut input_handler =
+        PollingInputHandler::new_with_controls(vec![('x', "fire"), ('z', "jump")]);
+    input_handler.update(vec!['x', 'z']);
+    input_handler.update(vec!['z']);
+
+    let snapshot = input_handler.snapshot();
+    assert!(snapshot.just_released("fire"));
+    assert_eq!(snapshot.release_time("fire"), 1);
+    assert!(!snapshot.just_released("jump"));
+
+    input_handler.update(vec![]);
+    let snapshot = input_handler.snapshot();
+    assert!(!snapshot.just_released("fire"));
+    assert_eq!(snapshot.release_time("fire"), 2);
+    assert!(snapshot.just_released("jump"));
+
+    // Held down in either snapshot means it wasn't released.
+    let mut copilot = PollingInputHandler::new_with_controls(vec![('x', "fire")]);
+    copilot.update(vec!['x']);
+    let merged = snapshot.merge(&copilot.snapshot());
+    assert_eq!(merged.release_time("fire"), 0);
+    assert!(merged.just_released("jump"));
+}
+
+#[test]
+fn snapshot_keeps_up_with_changes_in_the_same_frame() {
+    let mut input_handler = PollingInputHandler::new();
+    input_handler.axis_config_mut().insert("stick_x", "walk_x");
+    input_handler.axis_config_mut().insert("stick_y", "walk_y");
+    input_handler.set_dual_axis(
+        "walk",
+        Some(DualAxis {
+            x: "walk_x",
+            y: "walk_y",
+            normalize: true,
+        }),
+    );
+    input_handler.set_axis_inputs(vec![("stick_x", 1.0), ("stick_y", 1.0)]);
+    input_handler.update(vec![]);
+
+    let snapshot = input_handler.snapshot();
+    let (x, y) = snapshot.vector("walk");
+    assert!((x.hypot(y) - 1.0).abs() < 1e-6);
+    assert_eq!(snapshot.vector("walk"), input_handler.vector("walk"));
+
+    // Changing a setting shows up in the next snapshot without waiting for an update.
+    input_handler.set_axis_inverted("walk_x", true);
+    assert_eq!(input_handler.snapshot().vector("walk"), (-x, y));
+    assert_eq!(snapshot.vector("walk"), (x, y));
+}